@@ -32,6 +32,13 @@ impl AuthorizedAddress {
         self
     }
 
+    /// Returns a copy of this address that authenticates with the given bearer token,
+    /// replacing any token taken from the URL.
+    pub fn with_bearer_token(mut self, bearer_token: String) -> AuthorizedAddress {
+        self.bearer_token = Some(bearer_token);
+        self
+    }
+
     pub fn bearer_header(&self) -> Option<String> {
         self.bearer_token
             .as_ref()
@@ -1,11 +1,15 @@
 use self::controller_address::AuthorizedAddress;
 use crate::{
-    names::{BackendName, DroneName},
+    names::{ApiKeyName, BackendName, DroneName, JoinTokenName, WarmPoolName},
     protocol::{MessageFromDns, MessageFromDrone, MessageFromProxy},
     typed_socket::client::TypedSocketConnector,
     types::{
-        backend_state::BackendStatusStreamEntry, ClusterName, ClusterState, ConnectRequest,
-        ConnectResponse, DrainResult, DronePoolName, RevokeRequest,
+        backend_state::BackendStatusStreamEntry, BackendLimitsRequest, BackendLimitsResponse,
+        BackendListQuery, BackendListResponse, BackendLogEntry, BackendLogsQuery,
+        ClusterEventStreamEntry, ClusterName, ClusterState, ConnectRequest, ConnectResponse,
+        CreateWarmPoolRequest, DeleteWarmPoolResult, DrainResult, DronePoolName, MintApiKeyRequest,
+        MintApiKeyResponse, MintJoinTokenRequest, MintJoinTokenResponse, RevokeApiKeyResult,
+        RevokeJoinTokenResult, RevokeRequest, TokenRefreshRequest, TokenRefreshResponse, WarmPool,
     },
 };
 use protocol::{ApiError, StatusResponse};
@@ -59,6 +63,7 @@ pub enum PlaneClientError {
 pub struct PlaneClient {
     client: reqwest::Client,
     controller_address: AuthorizedAddress,
+    join_token: Option<String>,
 }
 
 impl PlaneClient {
@@ -69,9 +74,26 @@ impl PlaneClient {
         Self {
             client,
             controller_address,
+            join_token: None,
         }
     }
 
+    /// Authenticates requests to the controller with the given API key, instead of any
+    /// bearer token included in the base URL.
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.controller_address = self
+            .controller_address
+            .with_bearer_token(api_key.to_string());
+        self
+    }
+
+    /// Presents the given join token when connecting drones, proxies, and DNS servers to
+    /// the controller.
+    pub fn with_join_token(mut self, join_token: &str) -> Self {
+        self.join_token = Some(join_token.to_string());
+        self
+    }
+
     pub async fn status(&self) -> Result<StatusResponse, PlaneClientError> {
         let addr = self.controller_address.join("/ctrl/status");
         authed_get(&self.client, &addr).await
@@ -92,7 +114,7 @@ impl PlaneClient {
                 .join(&format!("{}?pool={}", base_path, encoded_pool))
         }
         .to_websocket_address();
-        TypedSocketConnector::new(addr)
+        TypedSocketConnector::new(addr).with_join_token(self.join_token.clone())
     }
 
     pub fn proxy_connection(
@@ -103,7 +125,7 @@ impl PlaneClient {
             .controller_address
             .join(&format!("/ctrl/c/{}/proxy-socket", cluster))
             .to_websocket_address();
-        TypedSocketConnector::new(addr)
+        TypedSocketConnector::new(addr).with_join_token(self.join_token.clone())
     }
 
     pub fn dns_connection(&self) -> TypedSocketConnector<MessageFromDns> {
@@ -111,7 +133,7 @@ impl PlaneClient {
             .controller_address
             .join("/ctrl/dns-socket")
             .to_websocket_address();
-        TypedSocketConnector::new(url)
+        TypedSocketConnector::new(url).with_join_token(self.join_token.clone())
     }
 
     pub async fn connect(
@@ -161,6 +183,32 @@ impl PlaneClient {
         Ok(())
     }
 
+    pub async fn refresh_token(
+        &self,
+        backend_id: &BackendName,
+        request: &TokenRefreshRequest,
+    ) -> Result<TokenRefreshResponse, PlaneClientError> {
+        let addr = self
+            .controller_address
+            .join(&format!("/ctrl/b/{}/tokens/refresh", backend_id));
+
+        let response = authed_post(&self.client, &addr, request).await?;
+        Ok(response)
+    }
+
+    pub async fn update_limits(
+        &self,
+        backend_id: &BackendName,
+        request: &BackendLimitsRequest,
+    ) -> Result<BackendLimitsResponse, PlaneClientError> {
+        let addr = self
+            .controller_address
+            .join(&format!("/ctrl/b/{}/limits", backend_id));
+
+        let response = authed_post(&self.client, &addr, request).await?;
+        Ok(response)
+    }
+
     pub fn backend_status_url(&self, backend_id: &BackendName) -> Url {
         self.controller_address
             .join(&format!("/pub/b/{}/status", backend_id))
@@ -194,6 +242,60 @@ impl PlaneClient {
         Ok(stream)
     }
 
+    /// Returns the backend's most recent log lines, oldest first. If `tail` is provided, at
+    /// most that many lines are returned.
+    pub async fn backend_logs(
+        &self,
+        backend_id: &BackendName,
+        tail: Option<u32>,
+    ) -> Result<Vec<BackendLogEntry>, PlaneClientError> {
+        let addr = self
+            .controller_address
+            .join(&format!("/ctrl/b/{}/logs", backend_id));
+        let query = BackendLogsQuery {
+            tail,
+            follow: false,
+        };
+
+        let entries: Vec<BackendLogEntry> =
+            authed_get_with_query(&self.client, &addr, &query).await?;
+        Ok(entries)
+    }
+
+    /// Streams the backend's most recent log lines (at most `tail` of them, if provided),
+    /// followed by new lines as they are written.
+    pub async fn backend_logs_stream(
+        &self,
+        backend_id: &BackendName,
+        tail: Option<u32>,
+    ) -> Result<sse::SseStream<BackendLogEntry>, PlaneClientError> {
+        let mut addr = self
+            .controller_address
+            .join(&format!("/ctrl/b/{}/logs", backend_id));
+        {
+            let mut query = addr.url.query_pairs_mut();
+            query.append_pair("follow", "true");
+            if let Some(tail) = tail {
+                query.append_pair("tail", &tail.to_string());
+            }
+        }
+
+        let stream = sse::authed_sse_request(&addr, self.client.clone()).await?;
+        Ok(stream)
+    }
+
+    pub async fn cluster_events(
+        &self,
+        cluster: &ClusterName,
+    ) -> Result<sse::SseStream<ClusterEventStreamEntry>, PlaneClientError> {
+        let addr = self
+            .controller_address
+            .join(&format!("/ctrl/c/{}/events", cluster));
+
+        let stream = sse::authed_sse_request(&addr, self.client.clone()).await?;
+        Ok(stream)
+    }
+
     pub async fn cluster_state(
         &self,
         cluster: &ClusterName,
@@ -205,6 +307,90 @@ impl PlaneClient {
         Ok(cluster_state)
     }
 
+    /// Lists backends matching the given query. Results are paginated; pass the
+    /// returned `next_cursor` as `query.cursor` to fetch the next page.
+    pub async fn list_backends(
+        &self,
+        query: &BackendListQuery,
+    ) -> Result<BackendListResponse, PlaneClientError> {
+        let addr = self.controller_address.join("/ctrl/backends");
+        let result: BackendListResponse = authed_get_with_query(&self.client, &addr, query).await?;
+        Ok(result)
+    }
+
+    pub async fn mint_api_key(
+        &self,
+        request: &MintApiKeyRequest,
+    ) -> Result<MintApiKeyResponse, PlaneClientError> {
+        let addr = self.controller_address.join("/ctrl/api-keys");
+
+        let response = authed_post(&self.client, &addr, request).await?;
+        Ok(response)
+    }
+
+    pub async fn revoke_api_key(
+        &self,
+        id: &ApiKeyName,
+    ) -> Result<RevokeApiKeyResult, PlaneClientError> {
+        let addr = self
+            .controller_address
+            .join(&format!("/ctrl/api-keys/{}/revoke", id));
+
+        let result = authed_post(&self.client, &addr, &()).await?;
+        Ok(result)
+    }
+
+    pub async fn mint_join_token(
+        &self,
+        request: &MintJoinTokenRequest,
+    ) -> Result<MintJoinTokenResponse, PlaneClientError> {
+        let addr = self.controller_address.join("/ctrl/join-tokens");
+
+        let response = authed_post(&self.client, &addr, request).await?;
+        Ok(response)
+    }
+
+    pub async fn revoke_join_token(
+        &self,
+        id: &JoinTokenName,
+    ) -> Result<RevokeJoinTokenResult, PlaneClientError> {
+        let addr = self
+            .controller_address
+            .join(&format!("/ctrl/join-tokens/{}/revoke", id));
+
+        let result = authed_post(&self.client, &addr, &()).await?;
+        Ok(result)
+    }
+
+    pub async fn create_warm_pool(
+        &self,
+        request: &CreateWarmPoolRequest,
+    ) -> Result<WarmPool, PlaneClientError> {
+        let addr = self.controller_address.join("/ctrl/warm-pools");
+
+        let response = authed_post(&self.client, &addr, request).await?;
+        Ok(response)
+    }
+
+    pub async fn list_warm_pools(&self) -> Result<Vec<WarmPool>, PlaneClientError> {
+        let addr = self.controller_address.join("/ctrl/warm-pools");
+
+        let response = authed_get(&self.client, &addr).await?;
+        Ok(response)
+    }
+
+    pub async fn delete_warm_pool(
+        &self,
+        id: &WarmPoolName,
+    ) -> Result<DeleteWarmPoolResult, PlaneClientError> {
+        let addr = self
+            .controller_address
+            .join(&format!("/ctrl/warm-pools/{}/delete", id));
+
+        let result = authed_post(&self.client, &addr, &()).await?;
+        Ok(result)
+    }
+
     pub async fn health_check(&self) -> Result<(), PlaneClientError> {
         let url = self.controller_address.join("/pub/health");
         self.client.get(url.url).send().await?;
@@ -248,6 +434,20 @@ async fn authed_get<T: DeserializeOwned>(
     get_response(response).await
 }
 
+async fn authed_get_with_query<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    addr: &AuthorizedAddress,
+    query: &impl serde::Serialize,
+) -> Result<T, PlaneClientError> {
+    let mut req = client.get(addr.url.clone()).query(query);
+    if let Some(header) = addr.bearer_header() {
+        req = req.header("Authorization", header);
+    }
+
+    let response = req.send().await?;
+    get_response(response).await
+}
+
 async fn authed_post<T: DeserializeOwned>(
     client: &reqwest::Client,
     addr: &AuthorizedAddress,
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::net::SocketAddr;
+use std::{collections::BTreeMap, net::SocketAddr};
 use valuable::{Tuplable, TupleDef, Valuable, Value, Visit};
 
 // See: https://github.com/tokio-rs/valuable/issues/86#issuecomment-1760446976
@@ -46,3 +46,6 @@ impl Tuplable for BackendAddr {
         valuable::TupleDef::new_static(1)
     }
 }
+
+/// Addresses of a backend's named ports, by port name.
+pub type NamedBackendAddrs = BTreeMap<String, BackendAddr>;
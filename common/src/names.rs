@@ -163,6 +163,9 @@ entity_name!(ProxyName, Some("px"));
 entity_name!(DroneName, Some("dr"));
 entity_name!(AcmeDnsServerName, Some("ns"));
 entity_name!(BackendActionName, Some("ak"));
+entity_name!(ApiKeyName, Some("pk"));
+entity_name!(JoinTokenName, Some("jt"));
+entity_name!(WarmPoolName, Some("wp"));
 
 impl BackendName {
     pub fn from_container_id(container_id: String) -> Result<Self, NameError> {
@@ -1,29 +1,33 @@
 use std::fmt::Display;
 
 use crate::{
-    log_types::{BackendAddr, LoggableTime},
+    log_types::{BackendAddr, LoggableTime, NamedBackendAddrs},
     names::{BackendActionName, BackendName},
     typed_socket::ChannelMessage,
     types::{
-        backend_state::TerminationReason, BackendState, BearerToken, ClusterName, KeyConfig,
-        NodeId, SecretToken, Subdomain, TerminationKind,
+        backend_state::TerminationReason, BackendLogEntry, BackendState, BearerToken, ClusterName,
+        KeyConfig, NodeId, RestartPolicy, SecretToken, Subdomain, TerminationKind,
     },
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ApiErrorKind {
     FailedToAcquireKey,
     KeyUnheldNoSpawnConfig,
     KeyHeldUnhealthy,
     KeyHeld,
     NoDroneAvailable,
+    ClusterAtCapacity,
     FailedToRemoveKey,
     DatabaseError,
     NoClusterProvided,
     NotFound,
     InvalidClusterName,
+    Unauthorized,
+    Forbidden,
     Other,
 }
 
@@ -74,11 +78,18 @@ pub enum BackendAction {
         executable: Value,
         key: AcquiredKey,
         static_token: Option<BearerToken>,
+        /// What the drone does when the backend's process exits with an error.
+        #[serde(default, skip_serializing_if = "RestartPolicy::is_never")]
+        restart_policy: RestartPolicy,
     },
     Terminate {
         kind: TerminationKind,
         reason: TerminationReason,
     },
+    /// Pause the process of an idle backend.
+    Suspend,
+    /// Unpause the process of a suspended backend.
+    Resume,
 }
 
 impl valuable::Valuable for BackendAction {
@@ -101,6 +112,7 @@ impl valuable::Valuable for BackendAction {
                 visit.visit_entry(valuable::Value::String("kind"), kind.as_value());
                 visit.visit_entry(valuable::Value::String("reason"), reason.as_value());
             }
+            BackendAction::Suspend | BackendAction::Resume => {}
         }
     }
 }
@@ -110,6 +122,7 @@ impl valuable::Mappable for BackendAction {
         match self {
             BackendAction::Spawn { .. } => (2, Some(2)),
             BackendAction::Terminate { .. } => (2, Some(2)),
+            BackendAction::Suspend | BackendAction::Resume => (0, Some(0)),
         }
     }
 }
@@ -150,6 +163,35 @@ pub struct RenewKeyRequest {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Heartbeat {
     pub local_time: LoggableTime,
+
+    /// The drone's resources, used by the controller to place backends. Drones that cannot
+    /// determine their resources (and older drones) do not report them.
+    #[serde(default)]
+    pub resources: Option<DroneResources>,
+}
+
+/// Memory and CPU of a drone. CPU is measured in millicores (thousandths of a core).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DroneResources {
+    /// Total memory of the drone's host, in bytes.
+    pub total_memory_bytes: u64,
+
+    /// Memory available to backends, in bytes. This is the total less any memory reserved
+    /// for the host.
+    pub allocatable_memory_bytes: u64,
+
+    /// Memory currently used by the drone's backends, in bytes.
+    pub used_memory_bytes: u64,
+
+    /// Total CPU of the drone's host, in millicores.
+    pub total_cpu_millicores: u64,
+
+    /// CPU available to backends, in millicores. This is the total less any CPU reserved
+    /// for the host.
+    pub allocatable_cpu_millicores: u64,
+
+    /// CPU currently used by the drone's backends, in millicores.
+    pub used_cpu_millicores: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -157,10 +199,17 @@ pub enum MessageFromDrone {
     Heartbeat(Heartbeat),
     BackendEvent(BackendStateMessage),
     BackendMetrics(BackendMetricsMessage),
+    BackendLogs(Vec<BackendLogMessage>),
     AckAction { action_id: BackendActionName },
     RenewKey(RenewKeyRequest),
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackendLogMessage {
+    pub backend_id: BackendName,
+    pub entry: BackendLogEntry,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BackendMetricsMessage {
     pub backend_id: BackendName,
@@ -224,11 +273,27 @@ impl ChannelMessage for MessageToDrone {
 pub struct RouteInfo {
     pub backend_id: BackendName,
     pub address: BackendAddr,
+
+    /// Addresses of the backend's named ports, which requests reach with a token suffixed
+    /// by `~<name>`.
+    #[serde(default)]
+    pub named_addresses: NamedBackendAddrs,
+
     pub secret_token: SecretToken,
     pub cluster: ClusterName,
     pub user: Option<String>,
     pub user_data: Option<serde_json::Value>,
     pub subdomain: Option<Subdomain>,
+
+    /// The time at which the token expires. None for static tokens, which do not expire.
+    pub expiration_time: Option<DateTime<Utc>>,
+}
+
+impl RouteInfo {
+    pub fn is_expired(&self) -> bool {
+        self.expiration_time
+            .is_some_and(|expiration_time| expiration_time <= Utc::now())
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -281,6 +346,15 @@ impl ChannelMessage for CertManagerResponse {
     type Reply = CertManagerRequest;
 }
 
+/// Sent to proxies when a user's tokens for a backend have been revoked.
+/// Proxies should forget any cached routes for the user's tokens and close
+/// any connections that were established with them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct TokensRevoked {
+    pub backend: BackendName,
+    pub user: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum MessageFromProxy {
     RouteInfoRequest(RouteInfoRequest),
@@ -296,7 +370,20 @@ impl ChannelMessage for MessageFromProxy {
 pub enum MessageToProxy {
     RouteInfoResponse(RouteInfoResponse),
     CertManagerResponse(CertManagerResponse),
-    BackendRemoved { backend: BackendName },
+    BackendRemoved {
+        backend: BackendName,
+    },
+    /// The backend has been suspended, so cached routes to it should be dropped. The next
+    /// request for it resumes it.
+    BackendSuspended {
+        backend: BackendName,
+    },
+    /// The backend's process is restarting or has moved to a new address, so cached routes to
+    /// it should be dropped. The next request for it waits until it is ready again.
+    InvalidateRoutes {
+        backend: BackendName,
+    },
+    TokensRevoked(TokensRevoked),
 }
 
 impl ChannelMessage for MessageToProxy {
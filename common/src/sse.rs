@@ -1,7 +1,7 @@
 use super::PlaneClientError;
-use crate::exponential_backoff::ExponentialBackoff;
+use crate::{controller_address::AuthorizedAddress, exponential_backoff::ExponentialBackoff};
 use reqwest::{
-    header::{HeaderValue, ACCEPT, CONNECTION},
+    header::{HeaderValue, ACCEPT, AUTHORIZATION, CONNECTION},
     Client, Response,
 };
 use serde::de::DeserializeOwned;
@@ -83,6 +83,7 @@ impl RawSseStream {
 pub struct SseStream<T: DeserializeOwned> {
     url: Url,
     client: Client,
+    authorization: Option<String>,
     stream: Option<RawSseStream>,
     backoff: ExponentialBackoff,
     last_id: Option<String>,
@@ -90,10 +91,11 @@ pub struct SseStream<T: DeserializeOwned> {
 }
 
 impl<T: DeserializeOwned> SseStream<T> {
-    fn new(url: Url, client: Client) -> Self {
+    fn new(url: Url, client: Client, authorization: Option<String>) -> Self {
         Self {
             url,
             client,
+            authorization,
             stream: None,
             backoff: ExponentialBackoff::default(),
             last_id: None,
@@ -113,6 +115,10 @@ impl<T: DeserializeOwned> SseStream<T> {
                 request = request.header("Last-Event-ID", id);
             }
 
+            if let Some(authorization) = &self.authorization {
+                request = request.header(AUTHORIZATION, authorization);
+            }
+
             let response = request.send().await?;
 
             if response.status() != 200 {
@@ -165,7 +171,16 @@ pub async fn sse_request<T: DeserializeOwned>(
     url: Url,
     client: Client,
 ) -> Result<SseStream<T>, PlaneClientError> {
-    let mut stream = SseStream::new(url, client);
+    let mut stream = SseStream::new(url, client, None);
+    stream.ensure_stream().await?;
+    Ok(stream)
+}
+
+pub async fn authed_sse_request<T: DeserializeOwned>(
+    addr: &AuthorizedAddress,
+    client: Client,
+) -> Result<SseStream<T>, PlaneClientError> {
+    let mut stream = SseStream::new(addr.url.clone(), client, addr.bearer_header());
     stream.ensure_stream().await?;
     Ok(stream)
 }
@@ -2,6 +2,7 @@ use super::{ChannelMessage, Handshake, SocketAction, TypedSocket};
 use crate::controller_address::AuthorizedAddress;
 use crate::exponential_backoff::ExponentialBackoff;
 use crate::names::NodeName;
+use crate::types::DroneLabels;
 use crate::version::plane_version_info;
 use crate::PlaneClientError;
 use futures_util::{SinkExt, StreamExt};
@@ -17,8 +18,14 @@ use tungstenite::{error::ProtocolError, Message};
 
 type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
 
+/// Header used by nodes to present a join token when connecting to the controller.
+pub const JOIN_TOKEN_HEADER: &str = "x-plane-join-token";
+
 pub struct TypedSocketConnector<T: ChannelMessage> {
     authorized_address: AuthorizedAddress,
+    join_token: Option<String>,
+    labels: DroneLabels,
+    max_backends: Option<u32>,
     backoff: ExponentialBackoff,
     _phantom: PhantomData<T>,
 }
@@ -27,11 +34,32 @@ impl<T: ChannelMessage> TypedSocketConnector<T> {
     pub fn new(authorized_address: AuthorizedAddress) -> Self {
         Self {
             authorized_address,
+            join_token: None,
+            labels: DroneLabels::default(),
+            max_backends: None,
             backoff: ExponentialBackoff::default(),
             _phantom: PhantomData,
         }
     }
 
+    /// Sets the join token that is presented to the controller when connecting.
+    pub fn with_join_token(mut self, join_token: Option<String>) -> Self {
+        self.join_token = join_token;
+        self
+    }
+
+    /// Sets the labels that are advertised to the controller in the handshake.
+    pub fn with_labels(mut self, labels: DroneLabels) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Sets the backend capacity that is advertised to the controller in the handshake.
+    pub fn with_max_backends(mut self, max_backends: Option<u32>) -> Self {
+        self.max_backends = max_backends;
+        self
+    }
+
     /// Continually retry a connection, with exponential backoff and unlimited
     /// retries.
     ///
@@ -56,9 +84,11 @@ impl<T: ChannelMessage> TypedSocketConnector<T> {
         let handshake = Handshake {
             name: name.to_string(),
             version: plane_version_info(),
+            labels: self.labels.clone(),
+            max_backends: self.max_backends,
         };
 
-        let req = auth_url_to_request(&self.authorized_address)?;
+        let req = auth_url_to_request(&self.authorized_address, self.join_token.as_deref())?;
         let (mut socket, _) = tokio_tungstenite::connect_async(req).await?;
 
         socket
@@ -92,8 +122,11 @@ impl<T: ChannelMessage> TypedSocketConnector<T> {
     }
 }
 
-/// Creates a WebSocket request from an AuthorizedAddress.
-fn auth_url_to_request(addr: &AuthorizedAddress) -> Result<Request<()>, PlaneClientError> {
+/// Creates a WebSocket request from an AuthorizedAddress and optional join token.
+fn auth_url_to_request(
+    addr: &AuthorizedAddress,
+    join_token: Option<&str>,
+) -> Result<Request<()>, PlaneClientError> {
     let mut request = Request::builder()
         .method(Method::GET)
         .uri(addr.url.as_str())
@@ -118,6 +151,15 @@ fn auth_url_to_request(addr: &AuthorizedAddress) -> Result<Request<()>, PlaneCli
         );
     }
 
+    if let Some(join_token) = join_token {
+        request = request.header(
+            JOIN_TOKEN_HEADER,
+            HeaderValue::from_str(join_token).map_err(|_| {
+                PlaneClientError::BadConfiguration("Join token is not a valid header value.")
+            })?,
+        );
+    }
+
     Ok(request.body(()).expect("Request is valid"))
 }
 
@@ -198,15 +240,30 @@ mod test {
     fn test_url_no_token() {
         let url = url::Url::parse("https://foo.bar.com/").unwrap();
         let addr = AuthorizedAddress::from(url);
-        let request = super::auth_url_to_request(&addr).unwrap();
+        let request = super::auth_url_to_request(&addr, None).unwrap();
         assert!(request.headers().get("Authorization").is_none());
+        assert!(request.headers().get(super::JOIN_TOKEN_HEADER).is_none());
+    }
+
+    #[test]
+    fn test_url_with_join_token() {
+        let url = url::Url::parse("https://foo.bar.com/").unwrap();
+        let addr = AuthorizedAddress::from(url);
+        let request = super::auth_url_to_request(&addr, Some("jt-abc.def")).unwrap();
+        assert_eq!(
+            request
+                .headers()
+                .get(super::JOIN_TOKEN_HEADER)
+                .map(|d| d.to_str().unwrap()),
+            Some("jt-abc.def")
+        );
     }
 
     #[test]
     fn test_url_with_token() {
         let url = url::Url::parse("https://abcdefg@foo.bar.com/").unwrap();
         let addr = AuthorizedAddress::from(url);
-        let request = super::auth_url_to_request(&addr).unwrap();
+        let request = super::auth_url_to_request(&addr, None).unwrap();
         assert_eq!(
             request
                 .headers()
@@ -1,3 +1,4 @@
+use crate::types::DroneLabels;
 use crate::version::PlaneVersionInfo;
 use crate::PlaneClientError;
 use serde::de::DeserializeOwned;
@@ -105,6 +106,14 @@ impl<T: ChannelMessage> TypedSocket<T> {
 pub struct Handshake {
     pub version: PlaneVersionInfo,
     pub name: String,
+
+    /// Labels the node advertises about itself. Only used by drones.
+    #[serde(default, skip_serializing_if = "DroneLabels::is_empty")]
+    pub labels: DroneLabels,
+
+    /// The maximum number of backends the node can run at once. Only used by drones.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backends: Option<u32>,
 }
 
 impl Handshake {
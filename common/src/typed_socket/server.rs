@@ -1,4 +1,5 @@
 use super::{ChannelMessage, Handshake, SocketAction, TypedSocket};
+use crate::types::DroneLabels;
 use crate::version::plane_version_info;
 use axum::extract::ws::{CloseFrame, Message, WebSocket};
 use tokio::sync::mpsc::{Receiver, Sender};
@@ -41,17 +42,21 @@ pub async fn handle_messages<T: ChannelMessage>(
                     }
                 }
             }
-            Some(msg) = socket.recv() => {
+            msg = socket.recv() => {
                 let msg = match msg {
-                    Ok(Message::Text(msg)) => msg,
-                    Err(err) => {
+                    Some(Ok(Message::Text(msg))) => msg,
+                    Some(Err(err)) => {
                         tracing::error!(?err, "Failed to receive message from websocket.");
                         break;
                     }
-                    Ok(Message::Close(Some(CloseFrame { code: 1001, .. }))) => {
+                    Some(Ok(Message::Close(Some(CloseFrame { code: 1001, .. })))) => {
                         tracing::warn!("Websocket connection closed.");
                         break;
                     }
+                    Some(Ok(Message::Close(_))) | None => {
+                        tracing::info!("Websocket connection closed by client.");
+                        break;
+                    }
                     msg => {
                         tracing::warn!("Received ignored message: {:?}", msg);
                         continue;
@@ -103,6 +108,8 @@ pub async fn new_server<T: ChannelMessage>(
     let local_handshake = Handshake {
         version: plane_version_info(),
         name,
+        labels: DroneLabels::default(),
+        max_backends: None,
     };
     ws.send(Message::Text(serde_json::to_string(&local_handshake)?))
         .await?;
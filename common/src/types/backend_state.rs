@@ -1,4 +1,4 @@
-use crate::log_types::{BackendAddr, LoggableTime};
+use crate::log_types::{BackendAddr, LoggableTime, NamedBackendAddrs};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -7,6 +7,11 @@ use std::{fmt::Display, net::SocketAddr};
 #[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, PartialOrd)]
 #[serde(rename_all = "lowercase")]
 pub enum BackendStatus {
+    /// No drone was available to run the backend when it was requested, so it is waiting to be
+    /// scheduled to a drone as one becomes available.
+    /// This status is only assigned by the controller.
+    Queued,
+
     /// The backend has been scheduled to a drone, but has not yet been acknowledged.
     /// This status is only assigned by the controller; the drone will never assign it by definition.
     Scheduled,
@@ -17,12 +22,20 @@ pub enum BackendStatus {
     /// Telling Docker to start the container.
     Starting,
 
+    /// The backend's process exited with an error, and will be started again under the backend's
+    /// restart policy. Proxies should stop sending traffic to its old address.
+    Restarting,
+
     /// Wait for the backend to be ready to accept connections.
     Waiting,
 
     /// The backend is listening for connections.
     Ready,
 
+    /// The backend's process has been paused because it was idle. It keeps its memory, but uses
+    /// no CPU, and returns to `Ready` when it is resumed.
+    Suspended,
+
     /// The backend has been sent a SIGTERM, either because we sent it or the user did,
     /// and we are waiting for it to exit.
     /// Proxies should stop sending traffic to it, but we should not yet release the key.
@@ -45,11 +58,14 @@ impl BackendStatus {
     /// statuses.
     pub fn as_int(&self) -> i32 {
         match self {
+            BackendStatus::Queued => 5,
             BackendStatus::Scheduled => 10,
             BackendStatus::Loading => 20,
             BackendStatus::Starting => 30,
+            BackendStatus::Restarting => 35,
             BackendStatus::Waiting => 40,
             BackendStatus::Ready => 50,
+            BackendStatus::Suspended => 55,
             BackendStatus::Terminating => 60,
             BackendStatus::HardTerminating => 65,
             BackendStatus::Terminated => 70,
@@ -60,11 +76,14 @@ impl BackendStatus {
 impl valuable::Valuable for BackendStatus {
     fn as_value(&self) -> valuable::Value {
         match self {
+            BackendStatus::Queued => valuable::Value::String("queued"),
             BackendStatus::Scheduled => valuable::Value::String("scheduled"),
             BackendStatus::Loading => valuable::Value::String("loading"),
             BackendStatus::Starting => valuable::Value::String("starting"),
+            BackendStatus::Restarting => valuable::Value::String("restarting"),
             BackendStatus::Waiting => valuable::Value::String("waiting"),
             BackendStatus::Ready => valuable::Value::String("ready"),
+            BackendStatus::Suspended => valuable::Value::String("suspended"),
             BackendStatus::Terminating => valuable::Value::String("terminating"),
             BackendStatus::HardTerminating => valuable::Value::String("hard-terminating"),
             BackendStatus::Terminated => valuable::Value::String("terminated"),
@@ -83,17 +102,54 @@ pub enum TerminationKind {
     Hard,
 }
 
+/// Records the restarts of a backend's process under its restart policy.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, valuable::Valuable)]
+pub struct RestartInfo {
+    /// The number of times the process has been restarted.
+    pub restart_count: u32,
+
+    /// The exit code of the process before it was most recently restarted.
+    pub last_exit_code: Option<i32>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 #[serde(tag = "status", rename_all = "lowercase")]
 pub enum BackendState {
+    Queued,
     Scheduled,
     Loading,
     Starting,
+    /// The backend's process has exited, and is waiting out its restart delay before being
+    /// started again.
+    Restarting {
+        /// The restart in progress, whose count includes it.
+        restart: RestartInfo,
+    },
     Waiting {
         address: BackendAddr,
+        /// Set if the backend's process has been restarted after exiting with an error.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restart: Option<RestartInfo>,
+        /// Addresses of the backend's named ports, if it exposes any.
+        #[serde(default, skip_serializing_if = "NamedBackendAddrs::is_empty")]
+        named_addresses: NamedBackendAddrs,
     },
     Ready {
         address: BackendAddr,
+        /// Set if the backend's process has been restarted after exiting with an error.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restart: Option<RestartInfo>,
+        /// Addresses of the backend's named ports, if it exposes any.
+        #[serde(default, skip_serializing_if = "NamedBackendAddrs::is_empty")]
+        named_addresses: NamedBackendAddrs,
+    },
+    Suspended {
+        address: BackendAddr,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        restart: Option<RestartInfo>,
+        /// Addresses of the backend's named ports, if it exposes any.
+        #[serde(default, skip_serializing_if = "NamedBackendAddrs::is_empty")]
+        named_addresses: NamedBackendAddrs,
     },
     Terminating {
         /// Last status before either soft or hard termination.
@@ -123,6 +179,10 @@ impl valuable::Valuable for BackendState {
 
     fn visit(&self, visit: &mut dyn valuable::Visit) {
         match self {
+            BackendState::Queued => visit.visit_entry(
+                valuable::Value::String("status"),
+                valuable::Value::String("queued"),
+            ),
             BackendState::Scheduled => visit.visit_entry(
                 valuable::Value::String("status"),
                 valuable::Value::String("scheduled"),
@@ -135,19 +195,66 @@ impl valuable::Valuable for BackendState {
                 valuable::Value::String("status"),
                 valuable::Value::String("starting"),
             ),
-            BackendState::Waiting { address } => {
+            BackendState::Restarting { restart } => {
+                visit.visit_entry(
+                    valuable::Value::String("status"),
+                    valuable::Value::String("restarting"),
+                );
+                visit.visit_entry(valuable::Value::String("restart"), restart.as_value());
+            }
+            BackendState::Waiting {
+                address,
+                named_addresses,
+                restart,
+            } => {
                 visit.visit_entry(
                     valuable::Value::String("status"),
                     valuable::Value::String("waiting"),
                 );
                 visit.visit_entry(valuable::Value::String("address"), address.as_value());
+                for (name, address) in named_addresses {
+                    let key = format!("address.{}", name);
+                    visit.visit_entry(valuable::Value::String(&key), address.as_value());
+                }
+                if let Some(restart) = restart {
+                    visit.visit_entry(valuable::Value::String("restart"), restart.as_value());
+                }
             }
-            BackendState::Ready { address } => {
+            BackendState::Ready {
+                address,
+                named_addresses,
+                restart,
+            } => {
                 visit.visit_entry(
                     valuable::Value::String("status"),
                     valuable::Value::String("ready"),
                 );
                 visit.visit_entry(valuable::Value::String("address"), address.as_value());
+                for (name, address) in named_addresses {
+                    let key = format!("address.{}", name);
+                    visit.visit_entry(valuable::Value::String(&key), address.as_value());
+                }
+                if let Some(restart) = restart {
+                    visit.visit_entry(valuable::Value::String("restart"), restart.as_value());
+                }
+            }
+            BackendState::Suspended {
+                address,
+                named_addresses,
+                restart,
+            } => {
+                visit.visit_entry(
+                    valuable::Value::String("status"),
+                    valuable::Value::String("suspended"),
+                );
+                visit.visit_entry(valuable::Value::String("address"), address.as_value());
+                for (name, address) in named_addresses {
+                    let key = format!("address.{}", name);
+                    visit.visit_entry(valuable::Value::String(&key), address.as_value());
+                }
+                if let Some(restart) = restart {
+                    visit.visit_entry(valuable::Value::String("restart"), restart.as_value());
+                }
             }
             #[allow(deprecated)]
             BackendState::Terminating {
@@ -213,11 +320,14 @@ impl valuable::Mappable for BackendState {
         // These numbers should match the number of calls to visit_entry in visit.
         // (This is use as a hint; differences are not a correctness issue.)
         match self {
+            BackendState::Queued => (1, Some(1)),
             BackendState::Scheduled => (1, Some(1)),
             BackendState::Loading => (1, Some(1)),
             BackendState::Starting => (1, Some(1)),
-            BackendState::Waiting { .. } => (2, Some(2)),
-            BackendState::Ready { .. } => (1, Some(2)),
+            BackendState::Restarting { .. } => (2, Some(2)),
+            BackendState::Waiting { .. } => (2, Some(3)),
+            BackendState::Ready { .. } => (1, Some(3)),
+            BackendState::Suspended { .. } => (2, Some(3)),
             BackendState::Terminating { .. } => (1, Some(4)),
             BackendState::HardTerminating { .. } => (1, Some(3)),
             BackendState::Terminated { .. } => (2, Some(5)),
@@ -234,6 +344,8 @@ pub enum TerminationReason {
     Lost,
     StartupTimeout,
     InternalError,
+    QueueTimeout,
+    Unhealthy,
 }
 
 impl valuable::Valuable for TerminationReason {
@@ -245,6 +357,8 @@ impl valuable::Valuable for TerminationReason {
             TerminationReason::Lost => valuable::Value::String("lost"),
             TerminationReason::StartupTimeout => valuable::Value::String("startuptimeout"),
             TerminationReason::InternalError => valuable::Value::String("internalerror"),
+            TerminationReason::QueueTimeout => valuable::Value::String("queuetimeout"),
+            TerminationReason::Unhealthy => valuable::Value::String("unhealthy"),
         }
     }
 
@@ -262,21 +376,61 @@ pub enum BackendError {
 }
 
 impl BackendState {
+    /// Returns the addresses of the backend's named ports, which are empty if it is not running.
+    pub fn named_addresses(&self) -> NamedBackendAddrs {
+        match self {
+            BackendState::Waiting {
+                named_addresses, ..
+            }
+            | BackendState::Ready {
+                named_addresses, ..
+            }
+            | BackendState::Suspended {
+                named_addresses, ..
+            } => named_addresses.clone(),
+            _ => NamedBackendAddrs::default(),
+        }
+    }
+
     pub fn address(&self) -> Option<BackendAddr> {
         match self {
-            BackendState::Waiting { address } => Some(*address),
-            BackendState::Ready { address } => Some(*address),
+            BackendState::Waiting { address, .. } => Some(*address),
+            BackendState::Ready { address, .. } => Some(*address),
+            BackendState::Suspended { address, .. } => Some(*address),
             _ => None,
         }
     }
 
+    /// The number of times the backend's process has been restarted, as of this state.
+    pub fn restart_count(&self) -> u32 {
+        match self {
+            BackendState::Restarting { restart } => restart.restart_count,
+            BackendState::Waiting {
+                restart: Some(restart),
+                ..
+            }
+            | BackendState::Ready {
+                restart: Some(restart),
+                ..
+            }
+            | BackendState::Suspended {
+                restart: Some(restart),
+                ..
+            } => restart.restart_count,
+            _ => 0,
+        }
+    }
+
     pub fn status(&self) -> BackendStatus {
         match self {
+            BackendState::Queued => BackendStatus::Queued,
             BackendState::Scheduled => BackendStatus::Scheduled,
             BackendState::Loading => BackendStatus::Loading,
             BackendState::Starting => BackendStatus::Starting,
+            BackendState::Restarting { .. } => BackendStatus::Restarting,
             BackendState::Waiting { .. } => BackendStatus::Waiting,
             BackendState::Ready { .. } => BackendStatus::Ready,
+            BackendState::Suspended { .. } => BackendStatus::Suspended,
             BackendState::Terminating { .. } => BackendStatus::Terminating,
             BackendState::HardTerminating { .. } => BackendStatus::HardTerminating,
             BackendState::Terminated { .. } => BackendStatus::Terminated,
@@ -295,14 +449,105 @@ impl BackendState {
         BackendState::Starting
     }
 
-    pub fn to_waiting(&self, address: SocketAddr) -> BackendState {
+    pub fn to_waiting(
+        &self,
+        address: SocketAddr,
+        named_addresses: NamedBackendAddrs,
+    ) -> BackendState {
+        BackendState::Waiting {
+            address: BackendAddr(address),
+            named_addresses,
+            restart: None,
+        }
+    }
+
+    /// Returns the state of a backend whose process exited with `exit_code` and will be
+    /// restarted. The backend is not routable until the restarted process is ready.
+    pub fn to_restarting(&self, exit_code: Option<i32>) -> BackendState {
+        BackendState::Restarting {
+            restart: RestartInfo {
+                restart_count: self.restart_count() + 1,
+                last_exit_code: exit_code,
+            },
+        }
+    }
+
+    /// Returns the state of a restarting backend whose process has been started again and
+    /// is listening on `address`.
+    pub fn to_restarted(
+        &self,
+        address: SocketAddr,
+        named_addresses: NamedBackendAddrs,
+    ) -> BackendState {
+        let restart = match self {
+            BackendState::Restarting { restart } => Some(*restart),
+            _ => {
+                tracing::warn!(state=?self, "to_restarted called on backend that is not restarting.");
+                None
+            }
+        };
+
         BackendState::Waiting {
             address: BackendAddr(address),
+            named_addresses,
+            restart,
         }
     }
 
     pub fn to_ready(&self, address: BackendAddr) -> BackendState {
-        BackendState::Ready { address }
+        let (named_addresses, restart) = match self {
+            BackendState::Waiting {
+                named_addresses,
+                restart,
+                ..
+            } => (named_addresses.clone(), *restart),
+            _ => (NamedBackendAddrs::default(), None),
+        };
+        BackendState::Ready {
+            address,
+            named_addresses,
+            restart,
+        }
+    }
+
+    /// Returns the state of a ready backend whose process has been paused. Backends in any
+    /// other state are returned unchanged.
+    pub fn to_suspended(&self) -> BackendState {
+        match self {
+            BackendState::Ready {
+                address,
+                named_addresses,
+                restart,
+            } => BackendState::Suspended {
+                address: *address,
+                named_addresses: named_addresses.clone(),
+                restart: *restart,
+            },
+            _ => {
+                tracing::warn!(state=?self, "to_suspended called on backend that is not ready.");
+                self.clone()
+            }
+        }
+    }
+
+    /// Returns the state of a suspended backend whose process has been unpaused. Backends in
+    /// any other state are returned unchanged.
+    pub fn to_resumed(&self) -> BackendState {
+        match self {
+            BackendState::Suspended {
+                address,
+                named_addresses,
+                restart,
+            } => BackendState::Ready {
+                address: *address,
+                named_addresses: named_addresses.clone(),
+                restart: *restart,
+            },
+            _ => {
+                tracing::warn!(state=?self, "to_resumed called on backend that is not suspended.");
+                self.clone()
+            }
+        }
     }
 
     pub fn to_terminating(&self, reason: TerminationReason) -> BackendState {
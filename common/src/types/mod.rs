@@ -1,14 +1,23 @@
 use crate::{
-    names::{AnyNodeName, BackendName, ControllerName, DroneName},
+    names::{
+        AnyNodeName, ApiKeyName, BackendName, ControllerName, DroneName, JoinTokenName,
+        WarmPoolName,
+    },
     util::{random_prefixed_string, random_token},
     PlaneClient,
 };
 pub use backend_state::{BackendState, BackendStatus, TerminationKind, TerminationReason};
 use bollard::auth::DockerCredentials;
-use chrono::Duration;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
-use std::{collections::HashMap, fmt::Display, ops::Deref, path::PathBuf, str::FromStr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    ops::Deref,
+    path::PathBuf,
+    str::FromStr,
+};
 
 pub mod backend_state;
 
@@ -111,6 +120,45 @@ impl Deref for DronePoolName {
     }
 }
 
+/// Key/value labels a drone advertises about itself (e.g. `region=eu` or `local-ssd=true`),
+/// which spawn requests can select drones by.
+pub type DroneLabels = BTreeMap<String, String>;
+
+/// Constrains which drones a backend may be placed on, by their labels. A drone matches a set of
+/// labels if it has every one of them with the same value.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct LabelSelector {
+    /// Labels a drone must have for the backend to be placed on it.
+    #[serde(default)]
+    pub required: DroneLabels,
+
+    /// Labels that a drone is preferred for having. Among drones with room for the backend,
+    /// the one matching the most of these labels is chosen.
+    #[serde(default)]
+    pub preferred: DroneLabels,
+}
+
+impl LabelSelector {
+    pub fn is_empty(&self) -> bool {
+        self.required.is_empty() && self.preferred.is_empty()
+    }
+
+    /// Whether a drone with the given labels satisfies the required labels.
+    pub fn matches(&self, labels: &DroneLabels) -> bool {
+        self.required
+            .iter()
+            .all(|(key, value)| labels.get(key) == Some(value))
+    }
+
+    /// The number of preferred labels that a drone with the given labels has.
+    pub fn preference_score(&self, labels: &DroneLabels) -> usize {
+        self.preferred
+            .iter()
+            .filter(|(key, value)| labels.get(*key) == Some(*value))
+            .count()
+    }
+}
+
 #[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, valuable::Valuable, PartialEq)]
 pub enum PullPolicy {
     #[default]
@@ -192,6 +240,11 @@ impl ResourceLimits {
         let quota = cpu_period.0.mul_f64((pc as f64) / 100.0);
         Some(quota)
     }
+
+    /// The number of cores the container may use, in millicores (thousandths of a core).
+    pub fn cpu_millicores(&self) -> Option<u64> {
+        Some(self.cpu_period_percent? as u64 * 10)
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, valuable::Valuable, PartialEq)]
@@ -231,6 +284,32 @@ pub struct DockerExecutorConfig {
     pub resource_limits: ResourceLimits,
     pub mount: Option<Mount>,
     pub network_name: Option<String>,
+    /// Port the backend listens for HTTP requests on inside its container, passed to it as
+    /// the `PORT` environment variable. Defaults to 8080.
+    pub port: Option<u16>,
+    /// Additional ports inside the container to expose, by name. The proxy routes requests
+    /// made with a token suffixed by `~<name>` to the port with that name.
+    #[serde(default)]
+    pub ports: HashMap<String, u16>,
+    /// Command to run in the container, in place of the image's `CMD`.
+    pub cmd: Option<Vec<String>>,
+    /// Entrypoint of the container, in place of the image's `ENTRYPOINT`.
+    pub entrypoint: Option<Vec<String>>,
+    /// User to run the container's process as, in place of the image's `USER`.
+    pub user: Option<String>,
+    /// Working directory of the container's process, in place of the image's `WORKDIR`.
+    pub working_dir: Option<String>,
+    /// Hostname of the container.
+    pub hostname: Option<String>,
+    /// Labels to add to the container. Labels starting with `dev.plane.` are reserved.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// How the drone checks that the backend is ready, before marking it ready. If not
+    /// provided, the backend is ready once it responds to an HTTP request with any status.
+    pub readiness_probe: Option<ReadinessProbe>,
+    /// How the drone checks that a ready backend is still healthy. If not provided, the
+    /// backend is not checked once it is ready.
+    pub liveness_probe: Option<LivenessProbe>,
 }
 
 impl DockerExecutorConfig {
@@ -243,10 +322,199 @@ impl DockerExecutorConfig {
             credentials: None,
             mount: None,
             network_name: None,
+            port: None,
+            ports: HashMap::default(),
+            cmd: None,
+            entrypoint: None,
+            user: None,
+            working_dir: None,
+            hostname: None,
+            labels: HashMap::default(),
+            readiness_probe: None,
+            liveness_probe: None,
         }
     }
 }
 
+/// A check the drone runs against a backend.
+#[derive(Clone, Serialize, Deserialize, Debug, valuable::Valuable, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ProbeCheck {
+    /// An HTTP `GET` request to `path` on the backend's port, which passes if the response
+    /// status is one of `expected_status`, or any 2xx or 3xx status if that is not provided.
+    Http {
+        path: String,
+        expected_status: Option<Vec<u16>>,
+    },
+
+    /// A TCP connection to the backend's port, which passes if the connection is accepted.
+    Tcp,
+
+    /// A command run inside the backend's container, which passes if it exits with code 0.
+    Exec { command: Vec<String> },
+}
+
+/// Default time the drone waits for a backend to become ready.
+const DEFAULT_READINESS_TIMEOUT_SECONDS: u64 = 300;
+
+/// Default time between readiness checks.
+const DEFAULT_READINESS_INTERVAL_MILLIS: u64 = 100;
+
+/// Shortest time between readiness checks, so that a probe can't check in a busy loop.
+const MIN_READINESS_INTERVAL_MILLIS: u64 = 10;
+
+#[derive(Clone, Serialize, Deserialize, Debug, valuable::Valuable, PartialEq, Eq)]
+pub struct ReadinessProbe {
+    #[serde(flatten)]
+    pub check: ProbeCheck,
+
+    /// How long to wait for the check to pass before the backend is terminated with the
+    /// reason `startuptimeout`. Defaults to 300 seconds.
+    pub timeout_seconds: Option<u64>,
+
+    /// Time between checks, in milliseconds. Defaults to 100, and is at least 10.
+    pub interval_millis: Option<u64>,
+}
+
+impl ReadinessProbe {
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(
+            self.timeout_seconds
+                .unwrap_or(DEFAULT_READINESS_TIMEOUT_SECONDS),
+        )
+    }
+
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(
+            self.interval_millis
+                .unwrap_or(DEFAULT_READINESS_INTERVAL_MILLIS)
+                .max(MIN_READINESS_INTERVAL_MILLIS),
+        )
+    }
+}
+
+/// Default time between liveness checks.
+const DEFAULT_LIVENESS_INTERVAL_SECONDS: u64 = 10;
+
+/// Default time a liveness check may take before it counts as failed.
+const DEFAULT_LIVENESS_TIMEOUT_SECONDS: u64 = 5;
+
+/// Default number of liveness checks in a row a backend may fail before it is terminated.
+const DEFAULT_LIVENESS_FAILURE_THRESHOLD: u32 = 3;
+
+#[derive(Clone, Serialize, Deserialize, Debug, valuable::Valuable, PartialEq, Eq)]
+pub struct LivenessProbe {
+    #[serde(flatten)]
+    pub check: ProbeCheck,
+
+    /// Time between checks, in seconds. Defaults to 10.
+    pub interval_seconds: Option<u64>,
+
+    /// How long a check may take before it counts as failed, in seconds. Defaults to 5.
+    pub timeout_seconds: Option<u64>,
+
+    /// Number of checks in a row the backend may fail before it is terminated with the
+    /// reason `unhealthy`. Defaults to 3.
+    pub failure_threshold: Option<u32>,
+}
+
+impl LivenessProbe {
+    pub fn interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(
+            self.interval_seconds
+                .unwrap_or(DEFAULT_LIVENESS_INTERVAL_SECONDS),
+        )
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(
+            self.timeout_seconds
+                .unwrap_or(DEFAULT_LIVENESS_TIMEOUT_SECONDS),
+        )
+    }
+
+    pub fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+            .unwrap_or(DEFAULT_LIVENESS_FAILURE_THRESHOLD)
+            .max(1)
+    }
+}
+
+/// The longest the drone waits before restarting a backend's process.
+const MAX_RESTART_BACKOFF_SECONDS: u64 = 300;
+
+fn default_restart_backoff_seconds() -> u64 {
+    1
+}
+
+/// Whether a backend's process is restarted, under the same backend and key, when it exits
+/// with a non-zero exit code.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(tag = "policy", rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// The backend is terminated when its process exits.
+    #[default]
+    Never,
+
+    /// The process is restarted up to `max_attempts` times. The drone waits `backoff_seconds`
+    /// before the first restart, doubling the wait for each restart after that.
+    OnFailure {
+        max_attempts: u32,
+        #[serde(default = "default_restart_backoff_seconds")]
+        backoff_seconds: u64,
+    },
+}
+
+impl RestartPolicy {
+    pub fn is_never(&self) -> bool {
+        matches!(self, RestartPolicy::Never)
+    }
+
+    /// Returns how long to wait before restarting a process that exited with `exit_code`
+    /// after already being restarted `restart_count` times, or `None` if it should not be
+    /// restarted.
+    pub fn restart_delay(
+        &self,
+        restart_count: u32,
+        exit_code: Option<i32>,
+    ) -> Option<std::time::Duration> {
+        let RestartPolicy::OnFailure {
+            max_attempts,
+            backoff_seconds,
+        } = self
+        else {
+            return None;
+        };
+
+        if exit_code.unwrap_or_default() == 0 || restart_count >= *max_attempts {
+            return None;
+        }
+
+        let backoff = backoff_seconds
+            .saturating_mul(2u64.saturating_pow(restart_count))
+            .min(MAX_RESTART_BACKOFF_SECONDS);
+        Some(std::time::Duration::from_secs(backoff))
+    }
+}
+
+/// What happens to a backend once it has had no inbound connections for `max_idle_seconds`.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleAction {
+    /// The backend is terminated.
+    #[default]
+    Terminate,
+
+    /// The backend's process is paused, and resumed when the next request for it arrives.
+    Suspend,
+}
+
+impl IdleAction {
+    pub fn is_terminate(&self) -> bool {
+        matches!(self, IdleAction::Terminate)
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct SpawnConfig {
     /// ID to assign to the new backend. Must be unique.
@@ -262,6 +530,14 @@ pub struct SpawnConfig {
     #[serde(default)]
     pub pool: DronePoolName,
 
+    /// Pools to try, in order, if no drone in `pool` is available to spawn the backend.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_pools: Vec<DronePoolName>,
+
+    /// Labels that the drone the backend is spawned on must (or should) have, within the pool.
+    #[serde(default, skip_serializing_if = "LabelSelector::is_empty")]
+    pub label_selector: LabelSelector,
+
     /// Config to use to spawn the backend process.
     pub executable: Value,
 
@@ -273,12 +549,41 @@ pub struct SpawnConfig {
     /// stay alive with no inbound connections to it.
     pub max_idle_seconds: Option<i32>,
 
+    /// What happens to the backend once `max_idle_seconds` is reached.
+    #[serde(default, skip_serializing_if = "IdleAction::is_terminate")]
+    pub idle_action: IdleAction,
+
     /// If true, the backend will have a single connection token associated with it at spawn
     /// time instead of dynamic tokens for each user.
     #[serde(default)]
     pub use_static_token: bool,
 
     pub subdomain: Option<Subdomain>,
+
+    /// If provided, and no drone is available to run the backend, the backend is queued for
+    /// up to this many seconds until one is, instead of the request failing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_timeout_seconds: Option<i32>,
+
+    /// What the drone does when the backend's process exits with an error.
+    #[serde(default, skip_serializing_if = "RestartPolicy::is_never")]
+    pub restart_policy: RestartPolicy,
+}
+
+impl SpawnConfig {
+    /// The pools to try spawning the backend in, in order of preference.
+    pub fn pools(&self) -> impl Iterator<Item = &DronePoolName> {
+        std::iter::once(&self.pool).chain(self.fallback_pools.iter())
+    }
+
+    /// The resource limits requested by the executable config, used to place the backend.
+    /// Executables without (valid) resource limits are treated as requesting none.
+    pub fn resource_limits(&self) -> ResourceLimits {
+        self.executable
+            .get("resource_limits")
+            .and_then(|limits| serde_json::from_value(limits.clone()).ok())
+            .unwrap_or_default()
+    }
 }
 
 #[derive(
@@ -328,6 +633,10 @@ pub struct ConnectRequest {
     /// Passed to the backend through the X-Plane-Auth header.
     #[serde(default)]
     pub auth: Map<String, Value>,
+
+    /// Number of seconds the generated connection token is valid for.
+    /// Defaults to one hour. Has no effect on static tokens, which do not expire.
+    pub token_lifetime_seconds: Option<u64>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Hash, valuable::Valuable)]
@@ -400,6 +709,14 @@ pub struct ConnectResponse {
 
     /// The drone that spawned this backend, if the request resulted in a spawn.
     pub drone: Option<DroneName>,
+
+    /// The pool the backend was spawned in, if the request resulted in a spawn. This may be one of
+    /// the spawn config's `fallback_pools` if its `pool` had no drone available.
+    pub pool: Option<DronePoolName>,
+
+    /// The time at which the token expires, unless it is refreshed.
+    /// None if the token is a static token, which does not expire.
+    pub token_expiration_time: Option<DateTime<Utc>>,
 }
 
 impl ConnectResponse {
@@ -414,6 +731,8 @@ impl ConnectResponse {
         subdomain: Option<Subdomain>,
         client: &PlaneClient,
         drone: Option<DroneName>,
+        pool: Option<DronePoolName>,
+        token_expiration_time: Option<DateTime<Utc>>,
     ) -> Self {
         let protocol = if cluster.is_https() { "https" } else { "http" };
         let url = if let Some(subdomain) = &subdomain {
@@ -434,6 +753,8 @@ impl ConnectResponse {
             secret_token,
             status_url,
             drone,
+            pool,
+            token_expiration_time,
         }
     }
 }
@@ -469,11 +790,239 @@ pub struct RevokeRequest {
     pub user: String,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TokenRefreshRequest {
+    /// The (non-static) token to refresh.
+    pub token: BearerToken,
+
+    /// Number of seconds from now that the token should be valid for.
+    /// Defaults to one hour.
+    pub lifetime_seconds: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TokenRefreshResponse {
+    pub token: BearerToken,
+
+    /// The new time at which the token expires.
+    pub expiration_time: DateTime<Utc>,
+}
+
+/// New limits for a running backend. Limits that are not provided are left unchanged.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct BackendLimitsRequest {
+    /// If provided, the maximum amount of time the backend will be allowed to
+    /// stay alive, counted from now. This replaces the backend's current
+    /// expiration time, so it can be used to shorten as well as extend it.
+    pub lifetime_limit_seconds: Option<i32>,
+
+    /// If provided, the maximum amount of time the backend will be allowed to
+    /// stay alive with no inbound connections to it.
+    pub max_idle_seconds: Option<i32>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackendLimitsResponse {
+    /// The time at which the backend will be terminated, if it has a lifetime limit.
+    pub expiration_time: Option<DateTime<Utc>>,
+
+    /// The backend's idle limit, if it has one.
+    pub max_idle_seconds: Option<i32>,
+}
+
+/// The output stream of a backend's process that a log line was written to.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+impl Display for LogStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogStream::Stdout => write!(f, "stdout"),
+            LogStream::Stderr => write!(f, "stderr"),
+        }
+    }
+}
+
+impl FromStr for LogStream {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stdout" => Ok(LogStream::Stdout),
+            "stderr" => Ok(LogStream::Stderr),
+            _ => Err(format!("Invalid log stream: {}", s)),
+        }
+    }
+}
+
+/// A line written by a backend's process.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct BackendLogEntry {
+    /// The time the line was written, according to the drone's runtime.
+    pub timestamp: DateTime<Utc>,
+
+    pub stream: LogStream,
+
+    /// The line, without its trailing newline.
+    pub text: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct BackendLogsQuery {
+    /// If provided, only this many of the most recent lines are returned.
+    pub tail: Option<u32>,
+
+    /// If true, the response is an event stream of lines, which continues with new lines as
+    /// they are written.
+    #[serde(default)]
+    pub follow: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DrainResult {
     pub updated: bool,
 }
 
+/// An operation on the controller's `/ctrl` routes that an API key may be granted.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum ApiKeyScope {
+    /// Connect to (and spawn) backends, refresh connection tokens, and update backend limits.
+    Connect,
+
+    /// Terminate backends and revoke connection tokens.
+    Terminate,
+
+    /// Drain drones.
+    Drain,
+
+    /// Read cluster state, cluster events, backend listings, backend logs, and controller
+    /// metrics.
+    ReadState,
+
+    /// Connect drones, proxies, and DNS servers to the controller.
+    NodeSocket,
+
+    /// Mint and revoke API keys.
+    Admin,
+}
+
+impl Display for ApiKeyScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let result = serde_json::to_value(self);
+        match result {
+            Ok(Value::String(v)) => write!(f, "{}", v),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl FromStr for ApiKeyScope {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(Value::String(s.to_string()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MintApiKeyRequest {
+    /// Operations the key may perform.
+    pub scopes: Vec<ApiKeyScope>,
+
+    /// If provided, the key may only act on this cluster.
+    pub cluster: Option<ClusterName>,
+
+    /// If provided, the key may only act on backends whose key is in this namespace.
+    pub key_namespace: Option<String>,
+
+    /// Human-readable description of what the key is used for.
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MintApiKeyResponse {
+    pub id: ApiKeyName,
+
+    /// The secret API key, sent as a bearer token. This is only returned when the key is
+    /// minted; the controller stores only a hash of it.
+    pub api_key: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RevokeApiKeyResult {
+    /// False if the key did not exist or was already revoked.
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MintJoinTokenRequest {
+    /// If provided, the token may only be used by drones and proxies in this cluster.
+    /// Otherwise, it may be used by any node, including DNS servers.
+    pub cluster: Option<ClusterName>,
+
+    /// Human-readable description of what the token is used for.
+    pub description: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MintJoinTokenResponse {
+    pub id: JoinTokenName,
+
+    /// The secret join token, passed to nodes in their configuration. This is only returned
+    /// when the token is minted; the controller stores only a hash of it.
+    pub join_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RevokeJoinTokenResult {
+    /// False if the token did not exist or was already revoked.
+    pub revoked: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateWarmPoolRequest {
+    /// Cluster to keep the warm backends in. If not provided, the controller's default
+    /// cluster is used.
+    pub cluster: Option<ClusterName>,
+
+    /// Drone pool to keep the warm backends in.
+    #[serde(default)]
+    pub pool: DronePoolName,
+
+    /// Executable config of the warm backends. Connect requests are only given a warm
+    /// backend if their spawn config has the same executable config.
+    pub executable: Value,
+
+    /// Number of unclaimed backends to keep in the pool.
+    pub size: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WarmPool {
+    pub id: WarmPoolName,
+    pub cluster: ClusterName,
+    pub pool: DronePoolName,
+    pub executable: Value,
+    pub size: u32,
+
+    /// Unclaimed backends that are ready to be claimed.
+    pub ready: u32,
+
+    /// Unclaimed backends that are still starting.
+    pub starting: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DeleteWarmPoolResult {
+    /// False if the warm pool did not exist.
+    pub deleted: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct DroneState {
     pub ready: bool,
@@ -481,6 +1030,11 @@ pub struct DroneState {
     #[serde(with = "crate::serialization::serialize_duration_as_seconds")]
     pub last_heartbeat_age: Duration,
     pub backend_count: u32,
+
+    /// The maximum number of backends the drone can run at once, if it has a limit.
+    #[serde(default)]
+    pub max_backends: Option<u32>,
+
     pub node: NodeState,
 }
 
@@ -500,6 +1054,122 @@ pub struct ClusterState {
     pub proxies: Vec<NodeState>,
 }
 
+/// A change in a cluster, as emitted by the cluster event stream.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClusterEvent {
+    /// A backend in the cluster transitioned to a new state.
+    BackendStateChange {
+        backend: BackendName,
+
+        /// The key the backend holds, if it still held one when it changed state.
+        key: Option<KeyConfig>,
+
+        /// The status of the backend before the transition, or None if it was just created.
+        previous_status: Option<BackendStatus>,
+
+        state: BackendState,
+    },
+
+    /// A drone or proxy in the cluster connected to a controller.
+    NodeConnected { node: AnyNodeName },
+
+    /// A drone or proxy in the cluster disconnected from its controller.
+    NodeDisconnected { node: AnyNodeName },
+}
+
+/// Payload POSTed to webhook URLs when a backend changes state.
+///
+/// The body is signed with HMAC-SHA256 using the controller's webhook secret, and the
+/// hex-encoded signature is sent in the `X-Plane-Signature` header as `sha256=<signature>`.
+/// Each delivery is identified by the `X-Plane-Delivery-Id` header, which stays the same
+/// across retries.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackendStateWebhook {
+    /// The time the backend changed state.
+    pub timestamp: DateTime<Utc>,
+
+    pub cluster: ClusterName,
+    pub backend: BackendName,
+    pub key: Option<KeyConfig>,
+    pub previous_status: Option<BackendStatus>,
+    pub status: BackendStatus,
+
+    /// Set if the new status is `terminated`.
+    pub termination_reason: Option<TerminationReason>,
+
+    /// The exit code of the backend's process, if the new status is `terminated` and it exited.
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ClusterEventStreamEntry {
+    pub timestamp: DateTime<Utc>,
+    pub event: ClusterEvent,
+}
+
+/// Filters and pagination options for listing backends. All filters are optional,
+/// and a backend must match every provided filter to be returned.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BackendListQuery {
+    pub cluster: Option<ClusterName>,
+
+    /// Name of the drone the backend was scheduled on.
+    pub drone: Option<DroneName>,
+
+    /// Pool of the drone the backend was scheduled on.
+    pub pool: Option<DronePoolName>,
+
+    /// Only return backends whose last status is at or after this status in the lifecycle.
+    pub min_status: Option<BackendStatus>,
+
+    /// Only return backends whose last status is at or before this status in the lifecycle.
+    pub max_status: Option<BackendStatus>,
+
+    /// Namespace of the key the backend was created with.
+    pub key_namespace: Option<String>,
+
+    /// Only return backends created at or after this time.
+    pub created_after: Option<DateTime<Utc>>,
+
+    /// Only return backends created before this time.
+    pub created_before: Option<DateTime<Utc>>,
+
+    /// Opaque cursor returned as `next_cursor` by a previous request. If provided, only
+    /// backends after the last backend of that page are returned.
+    pub cursor: Option<String>,
+
+    /// Maximum number of backends to return. The controller caps this value.
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackendListEntry {
+    pub id: BackendName,
+    pub cluster: ClusterName,
+    /// The drone the backend was scheduled on. `None` while the backend is queued.
+    pub drone: Option<DroneName>,
+    /// The pool of the drone the backend was scheduled on. `None` while the backend is queued.
+    pub pool: Option<DronePoolName>,
+    pub key_namespace: Option<String>,
+    pub status: BackendStatus,
+    pub state: BackendState,
+    pub created_at: DateTime<Utc>,
+    pub last_status_time: DateTime<Utc>,
+    pub last_keepalive: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BackendListResponse {
+    /// Backends matching the query, ordered by creation time (oldest first).
+    pub backends: Vec<BackendListEntry>,
+
+    /// Cursor to pass to the next request to fetch the next page. `None` if this
+    /// is the last page.
+    pub next_cursor: Option<String>,
+}
+
 #[derive(thiserror::Error, Debug)]
 #[error("Invalid subdomain: {0}")]
 pub struct InvalidSubdomain(String);
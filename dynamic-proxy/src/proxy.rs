@@ -11,6 +11,11 @@ use std::{convert::Infallible, time::Duration};
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Marker inserted into the extensions of error responses generated by the proxy when the
+/// upstream request fails, to distinguish them from error responses returned by the upstream.
+#[derive(Clone, Copy, Debug)]
+pub struct UpstreamError;
+
 /// A client for proxying HTTP requests to an upstream server.
 #[derive(Clone)]
 pub struct ProxyClient {
@@ -53,6 +58,7 @@ impl ProxyClient {
                 return Ok((
                     Response::builder()
                         .status(StatusCode::GATEWAY_TIMEOUT)
+                        .extension(UpstreamError)
                         .body(simple_empty_body())
                         .expect("Failed to build response"),
                     None,
@@ -63,6 +69,7 @@ impl ProxyClient {
                 return Ok((
                     Response::builder()
                         .status(StatusCode::BAD_GATEWAY)
+                        .extension(UpstreamError)
                         .body(simple_empty_body())
                         .expect("Failed to build response"),
                     None,
@@ -0,0 +1,254 @@
+use common::test_env::TestEnvironment;
+use plane_common::{
+    names::{DroneName, Name},
+    protocol::ApiErrorKind,
+    types::{
+        ApiKeyScope, ClusterName, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction,
+        LabelSelector, MintApiKeyRequest, RestartPolicy, SpawnConfig,
+    },
+    PlaneClientError,
+};
+use plane_test_macro::plane_test;
+use reqwest::StatusCode;
+
+mod common;
+
+fn assert_api_error<T: std::fmt::Debug>(result: Result<T, PlaneClientError>, kind: ApiErrorKind) {
+    let expected_status = match kind {
+        ApiErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
+        _ => StatusCode::FORBIDDEN,
+    };
+
+    match result {
+        Err(PlaneClientError::PlaneError(error, status)) => {
+            assert_eq!(status, expected_status);
+            assert_eq!(error.kind, kind);
+        }
+        result => panic!("Expected {:?} error, got {:?}", kind, result),
+    }
+}
+
+#[plane_test]
+async fn api_key_required(env: TestEnvironment) {
+    let controller = env.controller_with_api_keys().await;
+
+    assert_api_error(
+        controller.client().status().await,
+        ApiErrorKind::Unauthorized,
+    );
+    assert_api_error(
+        controller
+            .client()
+            .with_api_key("pk-notarealkey.secret")
+            .status()
+            .await,
+        ApiErrorKind::Unauthorized,
+    );
+
+    let minted = env
+        .db()
+        .await
+        .api_keys()
+        .mint(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::ReadState],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    // The secret part of the key must match.
+    let (id, _) = minted.api_key.split_once('.').unwrap();
+    assert_api_error(
+        controller
+            .client()
+            .with_api_key(&format!("{}.wrong", id))
+            .status()
+            .await,
+        ApiErrorKind::Unauthorized,
+    );
+
+    let client = controller.client().with_api_key(&minted.api_key);
+    client.status().await.unwrap();
+}
+
+#[plane_test]
+async fn api_key_scopes(env: TestEnvironment) {
+    let controller = env.controller_with_api_keys().await;
+
+    let minted = env
+        .db()
+        .await
+        .api_keys()
+        .mint(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::Connect, ApiKeyScope::ReadState],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let client = controller.client().with_api_key(&minted.api_key);
+
+    client.cluster_state(&env.cluster).await.unwrap();
+
+    // The key does not have the `drain` scope.
+    assert_api_error(
+        client.drain(&env.cluster, &DroneName::new_random()).await,
+        ApiErrorKind::Forbidden,
+    );
+
+    // The key does not have the `admin` scope.
+    assert_api_error(
+        client.mint_api_key(&MintApiKeyRequest::default()).await,
+        ApiErrorKind::Forbidden,
+    );
+}
+
+#[plane_test]
+async fn api_key_cluster_restriction(env: TestEnvironment) {
+    let controller = env.controller_with_api_keys().await;
+
+    let minted = env
+        .db()
+        .await
+        .api_keys()
+        .mint(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::ReadState],
+            cluster: Some(env.cluster.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let client = controller.client().with_api_key(&minted.api_key);
+
+    client.cluster_state(&env.cluster).await.unwrap();
+
+    let other_cluster: ClusterName = "other.plane.test".parse().unwrap();
+    assert_api_error(
+        client.cluster_state(&other_cluster).await,
+        ApiErrorKind::Forbidden,
+    );
+}
+
+#[plane_test]
+async fn api_key_mint_and_revoke(env: TestEnvironment) {
+    let controller = env.controller_with_api_keys().await;
+
+    let admin_key = env
+        .db()
+        .await
+        .api_keys()
+        .mint(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::Admin],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let admin_client = controller.client().with_api_key(&admin_key.api_key);
+
+    let minted = admin_client
+        .mint_api_key(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::ReadState],
+            description: Some("test key".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let client = controller.client().with_api_key(&minted.api_key);
+    client.cluster_state(&env.cluster).await.unwrap();
+
+    let result = admin_client.revoke_api_key(&minted.id).await.unwrap();
+    assert!(result.revoked);
+
+    let result = admin_client.revoke_api_key(&minted.id).await.unwrap();
+    assert!(!result.revoked);
+
+    assert_api_error(
+        client.cluster_state(&env.cluster).await,
+        ApiErrorKind::Unauthorized,
+    );
+}
+
+#[plane_test]
+async fn api_key_namespace_restriction(env: TestEnvironment) {
+    let controller = env.controller_with_api_keys().await;
+
+    let minted = env
+        .db()
+        .await
+        .api_keys()
+        .mint(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::ReadState, ApiKeyScope::Drain],
+            key_namespace: Some("tenant".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let client = controller.client().with_api_key(&minted.api_key);
+
+    // Cluster-wide routes would expose or act on backends in other namespaces.
+    assert_api_error(
+        client.cluster_state(&env.cluster).await,
+        ApiErrorKind::Forbidden,
+    );
+    assert_api_error(
+        client.drain(&env.cluster, &DroneName::new_random()).await,
+        ApiErrorKind::Forbidden,
+    );
+}
+
+#[plane_test]
+async fn api_key_cluster_restriction_on_connect(env: TestEnvironment) {
+    let controller = env.controller_with_api_keys().await;
+
+    let minted = env
+        .db()
+        .await
+        .api_keys()
+        .mint(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::Connect],
+            cluster: Some(env.cluster.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let client = controller.client().with_api_key(&minted.api_key);
+
+    let mut spawn_config = SpawnConfig {
+        id: None,
+        cluster: Some("other.plane.test".parse().unwrap()),
+        pool: DronePoolName::default(),
+        fallback_pools: Vec::new(),
+        label_selector: LabelSelector::default(),
+        executable: serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine"))
+            .unwrap(),
+        lifetime_limit_seconds: None,
+        max_idle_seconds: None,
+        idle_action: IdleAction::Terminate,
+        use_static_token: false,
+        subdomain: None,
+        queue_timeout_seconds: None,
+        restart_policy: RestartPolicy::Never,
+    };
+    let result = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(spawn_config.clone()),
+            ..Default::default()
+        })
+        .await;
+    assert_api_error(result, ApiErrorKind::Forbidden);
+
+    // In the key's own cluster, the request gets past authorization.
+    spawn_config.cluster = Some(env.cluster.clone());
+    let result = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(spawn_config),
+            ..Default::default()
+        })
+        .await;
+    match result {
+        Err(PlaneClientError::PlaneError(error, _)) => {
+            assert_eq!(error.kind, ApiErrorKind::NoDroneAvailable);
+        }
+        result => panic!("Expected NoDroneAvailable error, got {:?}", result),
+    }
+}
@@ -7,7 +7,7 @@ use plane_common::{
     protocol::{BackendAction, BackendActionMessage, Heartbeat, MessageFromDrone, MessageToDrone},
     types::{
         ClusterName, ConnectRequest, ConnectResponse, DockerExecutorConfig, DronePoolName,
-        SpawnConfig,
+        IdleAction, LabelSelector, RestartPolicy, SpawnConfig,
     },
     PlaneClientError,
 };
@@ -26,11 +26,16 @@ fn connect_request(cluster: &ClusterName) -> ConnectRequest {
             id: None,
             cluster: Some(cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable,
             lifetime_limit_seconds: None,
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         ..Default::default()
     }
@@ -70,6 +75,7 @@ async fn backend_action_resent_if_not_acked(env: TestEnvironment) {
         drone_connection
             .send(MessageFromDrone::Heartbeat(Heartbeat {
                 local_time: LoggableTime(Utc::now()),
+                resources: None,
             }))
             .unwrap();
 
@@ -4,8 +4,8 @@ use plane_common::{
     names::{Name, ProxyName},
     protocol::{MessageFromProxy, MessageToProxy, RouteInfoRequest, RouteInfoResponse},
     types::{
-        BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, PullPolicy,
-        ResourceLimits, SpawnConfig,
+        BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction,
+        LabelSelector, PullPolicy, ResourceLimits, RestartPolicy, SpawnConfig,
     },
 };
 use plane_test_macro::plane_test;
@@ -30,6 +30,8 @@ async fn backend_lifecycle_docker(env: TestEnvironment) {
             id: None,
             cluster: Some(env.cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable: serde_json::to_value(DockerExecutorConfig {
                 image: "ghcr.io/jamsocket/demo-image-drop-four".to_string(),
                 pull_policy: Some(PullPolicy::IfNotPresent),
@@ -38,16 +40,30 @@ async fn backend_lifecycle_docker(env: TestEnvironment) {
                 credentials: None,
                 mount: None,
                 network_name: None,
+                port: None,
+                ports: HashMap::default(),
+                cmd: None,
+                entrypoint: None,
+                user: None,
+                working_dir: None,
+                hostname: None,
+                labels: HashMap::default(),
+                readiness_probe: None,
+                liveness_probe: None,
             })
             .unwrap(),
             lifetime_limit_seconds: None,
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         key: None,
         user: None,
         auth: Map::default(),
+        token_lifetime_seconds: None,
     };
     let response = client.connect(&connect_request).await.unwrap();
     tracing::info!("Got response.");
@@ -8,13 +8,16 @@ use plane_common::{
     names::{Name, ProxyName},
     protocol::{MessageFromProxy, MessageToProxy, RouteInfoRequest, RouteInfoResponse},
     types::{
-        BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, PullPolicy,
-        ResourceLimits, SpawnConfig,
+        BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction,
+        LabelSelector, PullPolicy, ResourceLimits, RestartPolicy, SpawnConfig,
     },
 };
 use plane_test_macro::plane_test;
 use serde_json::Map;
-use std::{collections::HashMap, net::SocketAddr};
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+};
 
 mod common;
 
@@ -35,6 +38,16 @@ async fn backend_lifecycle_socket(env: TestEnvironment) {
         credentials: None,
         mount: None,
         network_name: None,
+        port: None,
+        ports: HashMap::default(),
+        cmd: None,
+        entrypoint: None,
+        user: None,
+        working_dir: None,
+        hostname: None,
+        labels: HashMap::default(),
+        readiness_probe: None,
+        liveness_probe: None,
     };
 
     tracing::info!("Requesting backend.");
@@ -43,15 +56,21 @@ async fn backend_lifecycle_socket(env: TestEnvironment) {
             id: None,
             cluster: Some(env.cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable: serde_json::to_value(executor_config.clone()).unwrap(),
             lifetime_limit_seconds: None,
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         key: None,
         user: None,
         auth: Map::default(),
+        token_lifetime_seconds: None,
     };
     let response = client.connect(&connect_request).await.unwrap();
     tracing::info!("Got response.");
@@ -132,6 +151,7 @@ async fn backend_lifecycle_socket(env: TestEnvironment) {
             MessageToClient::SpawnResult(Ok(SpawnResult {
                 container_id: ContainerId::from("=no-container=".to_string()),
                 port: 80,
+                named_ports: BTreeMap::default(),
             })),
         )
         .await;
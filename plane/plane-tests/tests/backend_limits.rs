@@ -0,0 +1,170 @@
+use crate::common::timeout::WithTimeout;
+use chrono::Utc;
+use common::test_env::TestEnvironment;
+use hyper::StatusCode;
+use plane::drone::runtime::{
+    docker::{types::ContainerId, SpawnResult},
+    unix_socket::{MessageToClient, MessageToServer},
+};
+use plane_common::{
+    names::{BackendName, Name},
+    protocol::ApiErrorKind,
+    types::{
+        BackendLimitsRequest, BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName,
+        IdleAction, LabelSelector, RestartPolicy, SpawnConfig,
+    },
+    PlaneClientError,
+};
+use plane_test_macro::plane_test;
+use std::collections::BTreeMap;
+
+mod common;
+
+/// Tests that the limits of a running backend can be changed, and that the sweep enforces
+/// the new limits.
+#[plane_test]
+async fn update_backend_limits(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+    let mut drone = env.drone_with_socket(&controller).await;
+
+    // Wait for the drone to register.
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    let executor_config = DockerExecutorConfig::from_image_with_defaults("alpine");
+
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable: serde_json::to_value(executor_config.clone()).unwrap(),
+                lifetime_limit_seconds: Some(3600),
+                max_idle_seconds: None,
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let backend_id = response.backend_id.clone();
+
+    let mut backend_status_stream = client
+        .backend_status_stream(&backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Prepare(..)));
+    drone
+        .send_response(&message, MessageToClient::PrepareResult(Ok(())))
+        .await;
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Spawn(..)));
+    drone
+        .send_response(
+            &message,
+            MessageToClient::SpawnResult(Ok(SpawnResult {
+                container_id: ContainerId::from("=no-container=".to_string()),
+                port: 80,
+                named_ports: BTreeMap::default(),
+            })),
+        )
+        .await;
+
+    let message = drone.receive_request().await;
+    drone
+        .send_response(&message, MessageToClient::WaitForBackendResult(Ok(())))
+        .await;
+
+    loop {
+        let status = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status;
+        if status == BackendStatus::Ready {
+            break;
+        }
+    }
+
+    let result = client
+        .update_limits(
+            &BackendName::new_random(),
+            &BackendLimitsRequest {
+                lifetime_limit_seconds: Some(60),
+                ..Default::default()
+            },
+        )
+        .await;
+    match result {
+        Err(PlaneClientError::PlaneError(error, _)) => {
+            assert_eq!(error.kind, ApiErrorKind::NotFound);
+        }
+        result => panic!("Expected NotFound error, got {:?}", result),
+    }
+
+    let result = client
+        .update_limits(
+            &backend_id,
+            &BackendLimitsRequest {
+                max_idle_seconds: Some(-1),
+                ..Default::default()
+            },
+        )
+        .await;
+    match result {
+        Err(PlaneClientError::PlaneError(error, status)) => {
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(error.kind, ApiErrorKind::Other);
+            assert_eq!(error.message, "Limits must not be negative.");
+        }
+        result => panic!("Expected negative limit to be rejected, got {:?}", result),
+    }
+
+    // Setting only the idle limit leaves the lifetime limit in place.
+    let limits = client
+        .update_limits(
+            &backend_id,
+            &BackendLimitsRequest {
+                max_idle_seconds: Some(600),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(limits.max_idle_seconds, Some(600));
+    let expiration_time = limits.expiration_time.unwrap();
+    assert!(expiration_time > Utc::now() + chrono::Duration::seconds(3000));
+
+    tracing::info!("Shortening the backend's lifetime.");
+    let limits = client
+        .update_limits(
+            &backend_id,
+            &BackendLimitsRequest {
+                lifetime_limit_seconds: Some(1),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(limits.max_idle_seconds, Some(600));
+    assert!(limits.expiration_time.unwrap() < expiration_time);
+
+    let message = drone.receive_request().with_timeout(20).await.unwrap();
+    assert_eq!(
+        MessageToServer::Terminate(backend_id.clone(), false),
+        message.message
+    );
+}
@@ -0,0 +1,292 @@
+use crate::common::timeout::WithTimeout;
+use chrono::Utc;
+use common::test_env::{DroneWithSocket, TestEnvironment};
+use plane::{
+    database::backend_log::BACKEND_LOG_BUFFER_LINES,
+    drone::{
+        metrics::DRONE_METRICS,
+        runtime::{
+            docker::{types::ContainerId, SpawnResult, TerminateEvent},
+            unix_socket::{MessageToClient, MessageToServer},
+        },
+    },
+};
+use plane_common::{
+    names::{BackendName, Name},
+    protocol::{ApiErrorKind, BackendLogMessage},
+    sse::SseStream,
+    types::{
+        backend_state::BackendStatusStreamEntry, BackendLogEntry, BackendStatus, ConnectRequest,
+        DockerExecutorConfig, DronePoolName, IdleAction, LabelSelector, LogStream, RestartPolicy,
+        SpawnConfig,
+    },
+    PlaneClient, PlaneClientError,
+};
+use plane_test_macro::plane_test;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+mod common;
+
+fn log_message(backend_id: &BackendName, stream: LogStream, text: &str) -> MessageToClient {
+    MessageToClient::LogMessage(BackendLogMessage {
+        backend_id: backend_id.clone(),
+        entry: BackendLogEntry {
+            timestamp: Utc::now(),
+            stream,
+            text: text.to_string(),
+        },
+    })
+}
+
+async fn wait_for_lines(client: &PlaneClient, backend_id: &BackendName, count: usize) {
+    for _ in 0..50 {
+        let entries = client.backend_logs(backend_id, None).await.unwrap();
+        if entries.len() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Timed out waiting for {} log lines.", count);
+}
+
+fn texts(entries: &[BackendLogEntry]) -> Vec<&str> {
+    entries.iter().map(|entry| entry.text.as_str()).collect()
+}
+
+/// Connects to a new backend on the socket drone, and waits for it to become ready.
+async fn start_backend(
+    env: &TestEnvironment,
+    client: &PlaneClient,
+    drone: &mut DroneWithSocket,
+) -> (BackendName, SseStream<BackendStatusStreamEntry>) {
+    let executor_config = DockerExecutorConfig::from_image_with_defaults("alpine");
+
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable: serde_json::to_value(executor_config.clone()).unwrap(),
+                lifetime_limit_seconds: None,
+                max_idle_seconds: None,
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let backend_id = response.backend_id.clone();
+
+    let mut backend_status_stream = client
+        .backend_status_stream(&backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Prepare(..)));
+    drone
+        .send_response(&message, MessageToClient::PrepareResult(Ok(())))
+        .await;
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Spawn(..)));
+    drone
+        .send_response(
+            &message,
+            MessageToClient::SpawnResult(Ok(SpawnResult {
+                container_id: ContainerId::from("=no-container=".to_string()),
+                port: 80,
+                named_ports: BTreeMap::default(),
+            })),
+        )
+        .await;
+
+    let message = drone.receive_request().await;
+    drone
+        .send_response(&message, MessageToClient::WaitForBackendResult(Ok(())))
+        .await;
+
+    loop {
+        let status = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status;
+        if status == BackendStatus::Ready {
+            break;
+        }
+    }
+
+    (backend_id, backend_status_stream)
+}
+
+/// Tests that lines a backend writes are forwarded by the drone, can be read and followed
+/// through the controller, and stay available after the backend terminates.
+#[plane_test]
+async fn backend_logs(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+    let mut drone = env.drone_with_socket(&controller).await;
+
+    // Wait for the drone to register.
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    let (backend_id, mut backend_status_stream) = start_backend(&env, &client, &mut drone).await;
+
+    match client.backend_logs(&BackendName::new_random(), None).await {
+        Err(PlaneClientError::PlaneError(error, _)) => {
+            assert_eq!(error.kind, ApiErrorKind::NotFound);
+        }
+        result => panic!("Expected NotFound error, got {:?}", result),
+    }
+
+    drone
+        .send_message(log_message(&backend_id, LogStream::Stdout, "one"))
+        .await;
+    drone
+        .send_message(log_message(&backend_id, LogStream::Stderr, "two"))
+        .await;
+    drone
+        .send_message(log_message(&backend_id, LogStream::Stdout, "three"))
+        .await;
+    wait_for_lines(&client, &backend_id, 3).await;
+
+    let entries = client.backend_logs(&backend_id, None).await.unwrap();
+    assert_eq!(texts(&entries), vec!["one", "two", "three"]);
+    assert_eq!(entries[1].stream, LogStream::Stderr);
+
+    let entries = client.backend_logs(&backend_id, Some(2)).await.unwrap();
+    assert_eq!(texts(&entries), vec!["two", "three"]);
+
+    let mut log_stream = client
+        .backend_logs_stream(&backend_id, Some(1))
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+    let entry = log_stream.next().with_timeout(10).await.unwrap().unwrap();
+    assert_eq!(entry.text, "three");
+
+    drone
+        .send_message(log_message(&backend_id, LogStream::Stdout, "four"))
+        .await;
+    let entry = log_stream.next().with_timeout(10).await.unwrap().unwrap();
+    assert_eq!(entry.text, "four");
+
+    tracing::info!("Exiting the backend.");
+    drone
+        .send_message(MessageToClient::TerminateEvent(TerminateEvent {
+            backend_id: backend_id.clone(),
+            exit_code: Some(0),
+        }))
+        .await;
+
+    loop {
+        let status = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status;
+        if status == BackendStatus::Terminated {
+            break;
+        }
+    }
+
+    let entries = client.backend_logs(&backend_id, None).await.unwrap();
+    assert_eq!(texts(&entries), vec!["one", "two", "three", "four"]);
+}
+
+/// Tests that a backend writing lines faster than they can be stored does not delay the
+/// drone's other messages, such as key renewals.
+#[plane_test(90)]
+async fn backend_log_burst(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+    let mut drone = env.drone_with_socket(&controller).await;
+
+    // Wait for the drone to register.
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    let (backend_id, _backend_status_stream) = start_backend(&env, &client, &mut drone).await;
+
+    // The drone renews the backend's key 30 seconds after it was acquired. Write lines until
+    // the renewal has completed.
+    let renewals = &DRONE_METRICS.key_renewal_duration_seconds;
+    let mut written = 0;
+    let deadline = Instant::now() + Duration::from_secs(45);
+    while renewals.get_sample_count() == 0 && Instant::now() < deadline {
+        for _ in 0..100 {
+            drone
+                .send_message(log_message(
+                    &backend_id,
+                    LogStream::Stdout,
+                    &format!("line {}", written),
+                ))
+                .await;
+            written += 1;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    assert_eq!(renewals.get_sample_count(), 1, "Key was not renewed.");
+    assert!(
+        renewals.get_sample_sum() < 1.0,
+        "Key renewal took {} seconds.",
+        renewals.get_sample_sum()
+    );
+
+    // Only the most recent lines are kept.
+    wait_for_lines(&client, &backend_id, BACKEND_LOG_BUFFER_LINES as usize).await;
+    let entries = client.backend_logs(&backend_id, None).await.unwrap();
+    assert_eq!(entries.len(), BACKEND_LOG_BUFFER_LINES as usize);
+    assert!(written > BACKEND_LOG_BUFFER_LINES as usize);
+}
+
+/// Tests that a batch with lines for a backend that no longer exists still stores the lines
+/// of the other backends.
+#[plane_test]
+async fn backend_log_batch_skips_missing_backends(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+    let db = env.db().await;
+    let mut drone = env.drone_with_socket(&controller).await;
+
+    // Wait for the drone to register.
+    tokio::time::sleep(Duration::from_secs(5)).await;
+    let (backend_id, _backend_status_stream) = start_backend(&env, &client, &mut drone).await;
+    let missing_backend_id = BackendName::new_random();
+
+    let messages: Vec<BackendLogMessage> = [
+        (&backend_id, "first"),
+        (&missing_backend_id, "lost"),
+        (&backend_id, "second"),
+    ]
+    .into_iter()
+    .map(|(backend_id, text)| BackendLogMessage {
+        backend_id: backend_id.clone(),
+        entry: BackendLogEntry {
+            timestamp: Utc::now(),
+            stream: LogStream::Stdout,
+            text: text.to_string(),
+        },
+    })
+    .collect();
+    db.backend_logs().append(&messages).await.unwrap();
+
+    let entries = client.backend_logs(&backend_id, None).await.unwrap();
+    assert_eq!(texts(&entries), vec!["first", "second"]);
+}
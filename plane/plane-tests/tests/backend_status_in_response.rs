@@ -1,8 +1,8 @@
 use crate::common::timeout::WithTimeout;
 use common::test_env::TestEnvironment;
 use plane_common::types::{
-    BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, KeyConfig, PullPolicy,
-    ResourceLimits, SpawnConfig,
+    BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction, KeyConfig,
+    LabelSelector, PullPolicy, ResourceLimits, RestartPolicy, SpawnConfig,
 };
 use plane_test_macro::plane_test;
 use serde_json::Map;
@@ -25,6 +25,8 @@ async fn backend_status_in_response(env: TestEnvironment) {
             id: None,
             cluster: Some(env.cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable: serde_json::to_value(DockerExecutorConfig {
                 image: "ghcr.io/jamsocket/demo-image-drop-four".to_string(),
                 pull_policy: Some(PullPolicy::IfNotPresent),
@@ -33,12 +35,25 @@ async fn backend_status_in_response(env: TestEnvironment) {
                 credentials: None,
                 mount: None,
                 network_name: None,
+                port: None,
+                ports: HashMap::default(),
+                cmd: None,
+                entrypoint: None,
+                user: None,
+                working_dir: None,
+                hostname: None,
+                labels: HashMap::default(),
+                readiness_probe: None,
+                liveness_probe: None,
             })
             .unwrap(),
             lifetime_limit_seconds: Some(5),
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         key: Some(KeyConfig {
             name: "reuse-key".to_string(),
@@ -47,6 +62,7 @@ async fn backend_status_in_response(env: TestEnvironment) {
         }),
         user: None,
         auth: Map::default(),
+        token_lifetime_seconds: None,
     };
 
     let response = client.connect(&connect_request).await.unwrap();
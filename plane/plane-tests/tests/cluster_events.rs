@@ -0,0 +1,101 @@
+use chrono::Utc;
+use common::{test_env::TestEnvironment, timeout::WithTimeout};
+use plane_common::{
+    log_types::LoggableTime,
+    names::{AnyNodeName, DroneName, Name},
+    protocol::{Heartbeat, MessageFromDrone},
+    types::{
+        BackendState, ClusterEvent, ConnectRequest, DockerExecutorConfig, DronePoolName,
+        IdleAction, LabelSelector, RestartPolicy, SpawnConfig,
+    },
+};
+use plane_test_macro::plane_test;
+use std::time::Duration;
+
+mod common;
+
+/// Tests that node and backend lifecycle changes are emitted on the cluster event stream.
+#[plane_test]
+async fn cluster_events(env: TestEnvironment) {
+    let drone_id = DroneName::new_random();
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let mut events = client.cluster_events(&env.cluster).await.unwrap();
+
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&drone_id)
+        .await
+        .unwrap();
+
+    let entry = events
+        .next()
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .expect("Expected node connected event.");
+    let ClusterEvent::NodeConnected { node } = entry.event else {
+        panic!("Unexpected event: {:?}", entry.event);
+    };
+    assert_eq!(node, AnyNodeName::Drone(drone_id.clone()));
+
+    drone_connection
+        .send(MessageFromDrone::Heartbeat(Heartbeat {
+            local_time: LoggableTime(Utc::now()),
+            resources: None,
+        }))
+        .unwrap();
+
+    // Wait for the drone to be registered.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let executable =
+        serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine")).unwrap();
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable,
+                lifetime_limit_seconds: None,
+                max_idle_seconds: None,
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let entry = events
+        .next()
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .expect("Expected backend state change event.");
+    let ClusterEvent::BackendStateChange { backend, state, .. } = entry.event else {
+        panic!("Unexpected event: {:?}", entry.event);
+    };
+    assert_eq!(backend, response.backend_id);
+    assert!(matches!(state, BackendState::Scheduled));
+
+    drone_connection.close().await;
+
+    let entry = events
+        .next()
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .expect("Expected node disconnected event.");
+    let ClusterEvent::NodeDisconnected { node } = entry.event else {
+        panic!("Unexpected event: {:?}", entry.event);
+    };
+    assert_eq!(node, AnyNodeName::Drone(drone_id));
+}
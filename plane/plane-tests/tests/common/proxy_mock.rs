@@ -1,7 +1,7 @@
 use plane::proxy::{connection_monitor::BackendEntry, proxy_server::ProxyState};
 use plane_common::{
     names::BackendName,
-    protocol::{RouteInfoRequest, RouteInfoResponse},
+    protocol::{RouteInfoRequest, RouteInfoResponse, TokensRevoked},
 };
 use plane_dynamic_proxy::server::{HttpsConfig, SimpleHttpServer};
 use std::net::SocketAddr;
@@ -85,4 +85,8 @@ impl MockProxy {
     pub async fn send_route_info_response(&mut self, response: RouteInfoResponse) {
         self.proxy_state.inner.route_map.receive(response);
     }
+
+    pub fn revoke_tokens(&self, revoked: TokensRevoked) {
+        self.proxy_state.revoke_tokens(revoked);
+    }
 }
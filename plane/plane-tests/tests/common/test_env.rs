@@ -4,7 +4,7 @@ use super::{
 };
 use chrono::Duration;
 use plane::{
-    controller::ControllerServer,
+    controller::{webhooks::WebhookConfig, ControllerServer},
     database::PlaneDatabase,
     dns::run_dns_with_listener,
     drone::{
@@ -22,7 +22,7 @@ use plane::{
 };
 use plane_common::{
     names::{AcmeDnsServerName, ControllerName, DroneName, Name, ProxyName},
-    types::{ClusterName, DronePoolName},
+    types::{ClusterName, DroneLabels, DronePoolName},
     util::random_string,
     PlaneClient,
 };
@@ -121,6 +121,59 @@ impl TestEnvironment {
             None,
             None,
             None,
+            false,
+            false,
+            None,
+        )
+        .await
+        .expect("Unable to construct controller.")
+    }
+
+    pub async fn controller_with_api_keys(&mut self) -> ControllerServer {
+        let db = self.db().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: Url = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        ControllerServer::run_with_listener(
+            db.clone(),
+            listener,
+            ControllerName::new_random(),
+            url,
+            None,
+            None,
+            None,
+            None,
+            true,
+            false,
+            None,
+        )
+        .await
+        .expect("Unable to construct controller.")
+    }
+
+    /// Starts a controller that requires join tokens. Join tokens are only allowed along
+    /// with another form of authentication, so it also requires API keys.
+    pub async fn controller_with_join_tokens(&mut self) -> ControllerServer {
+        let db = self.db().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: Url = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        ControllerServer::run_with_listener(
+            db.clone(),
+            listener,
+            ControllerName::new_random(),
+            url,
+            None,
+            None,
+            None,
+            None,
+            true,
+            true,
+            None,
         )
         .await
         .expect("Unable to construct controller.")
@@ -179,6 +232,33 @@ impl TestEnvironment {
             None,
             None,
             Some(forward_auth.clone()),
+            false,
+            false,
+            None,
+        )
+        .await
+        .expect("Unable to construct controller.")
+    }
+
+    pub async fn controller_with_webhook(&mut self, webhook: WebhookConfig) -> ControllerServer {
+        let db = self.db().await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url: Url = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+
+        ControllerServer::run_with_listener(
+            db.clone(),
+            listener,
+            ControllerName::new_random(),
+            url,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            Some(webhook),
         )
         .await
         .expect("Unable to construct controller.")
@@ -188,6 +268,7 @@ impl TestEnvironment {
         &mut self,
         controller: &ControllerServer,
         pool: &DronePoolName,
+        labels: &DroneLabels,
         mount_base: Option<&PathBuf>,
     ) -> Drone {
         let docker_config = DockerRuntimeConfig {
@@ -205,19 +286,30 @@ impl TestEnvironment {
             ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
             db_path: Some(self.scratch_dir.join("drone.db")),
             pool: pool.clone(),
+            labels: labels.clone(),
             auto_prune: None,
             cleanup_min_age: None,
             executor_config: Some(ExecutorConfig::Docker(docker_config)),
             docker_config: None,
             controller_url: controller.url().clone(),
+            join_token: None,
+            metrics_port: None,
+            reserved_memory_bytes: None,
+            reserved_cpu_millicores: None,
+            max_backends: None,
         };
 
         Drone::run(drone_config).await.unwrap()
     }
 
     pub async fn drone(&mut self, controller: &ControllerServer) -> Drone {
-        self.drone_internal(controller, &self.pool.clone(), None)
-            .await
+        self.drone_internal(
+            controller,
+            &self.pool.clone(),
+            &DroneLabels::default(),
+            None,
+        )
+        .await
     }
 
     pub async fn drone_in_pool(
@@ -225,7 +317,17 @@ impl TestEnvironment {
         controller: &ControllerServer,
         pool: &DronePoolName,
     ) -> Drone {
-        self.drone_internal(controller, pool, None).await
+        self.drone_internal(controller, pool, &DroneLabels::default(), None)
+            .await
+    }
+
+    pub async fn drone_with_labels(
+        &mut self,
+        controller: &ControllerServer,
+        labels: &DroneLabels,
+    ) -> Drone {
+        self.drone_internal(controller, &self.pool.clone(), labels, None)
+            .await
     }
 
     pub async fn drone_with_mount_base(
@@ -233,8 +335,13 @@ impl TestEnvironment {
         controller: &ControllerServer,
         mount_base: &PathBuf,
     ) -> Drone {
-        self.drone_internal(controller, &self.pool.clone(), Some(mount_base))
-            .await
+        self.drone_internal(
+            controller,
+            &self.pool.clone(),
+            &DroneLabels::default(),
+            Some(mount_base),
+        )
+        .await
     }
 
     pub async fn drone_with_socket(&mut self, controller: &ControllerServer) -> DroneWithSocket {
@@ -251,11 +358,17 @@ impl TestEnvironment {
             ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
             db_path: Some(self.scratch_dir.join("drone.db")),
             pool: self.pool.clone(),
+            labels: DroneLabels::default(),
             auto_prune: None,
             cleanup_min_age: None,
             executor_config: Some(executor_config),
             docker_config: None,
             controller_url: controller.url().clone(),
+            join_token: None,
+            metrics_port: None,
+            reserved_memory_bytes: None,
+            reserved_cpu_millicores: None,
+            max_backends: None,
         };
 
         let drone = Drone::run(drone_config).await.unwrap();
@@ -0,0 +1,98 @@
+use common::test_env::TestEnvironment;
+use plane_common::types::{
+    ApiKeyScope, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction, LabelSelector,
+    MintApiKeyRequest, RestartPolicy, SpawnConfig,
+};
+use plane_test_macro::plane_test;
+use reqwest::StatusCode;
+
+mod common;
+
+/// Tests that failed connect requests are reported by error kind on the controller's metrics endpoint.
+#[plane_test]
+async fn controller_metrics(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let executable =
+        serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine")).unwrap();
+
+    // No drone is connected, so this connect request fails.
+    client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable,
+                lifetime_limit_seconds: None,
+                max_idle_seconds: None,
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+
+    let response = reqwest::get(controller.url().join("ctrl/metrics").unwrap())
+        .await
+        .unwrap();
+    assert!(response.status().is_success());
+
+    let body = response.text().await.unwrap();
+    assert!(body.contains(r#"plane_connect_requests_total{result="NoDroneAvailable"}"#));
+    assert!(body.contains("plane_db_query_duration_seconds_bucket"));
+}
+
+/// Tests that metrics require an API key with the `read-state` scope that is not restricted to
+/// a cluster or key namespace.
+#[plane_test]
+async fn controller_metrics_require_api_key(env: TestEnvironment) {
+    let controller = env.controller_with_api_keys().await;
+    let url = controller.url().join("ctrl/metrics").unwrap();
+    let db = env.db().await;
+
+    let status = |api_key: Option<String>| {
+        let url = url.clone();
+        async move {
+            let mut request = reqwest::Client::new().get(url);
+            if let Some(api_key) = api_key {
+                request = request.bearer_auth(api_key);
+            }
+            request.send().await.unwrap().status()
+        }
+    };
+
+    let mint = |scopes: Vec<ApiKeyScope>, key_namespace: Option<&str>| {
+        let db = db.clone();
+        let key_namespace = key_namespace.map(str::to_string);
+        async move {
+            db.api_keys()
+                .mint(&MintApiKeyRequest {
+                    scopes,
+                    key_namespace,
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .api_key
+        }
+    };
+
+    assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+
+    let connect_key = mint(vec![ApiKeyScope::Connect], None).await;
+    assert_eq!(status(Some(connect_key)).await, StatusCode::FORBIDDEN);
+
+    let restricted_key = mint(vec![ApiKeyScope::ReadState], Some("namespace")).await;
+    assert_eq!(status(Some(restricted_key)).await, StatusCode::FORBIDDEN);
+
+    let read_key = mint(vec![ApiKeyScope::ReadState], None).await;
+    assert_eq!(status(Some(read_key)).await, StatusCode::OK);
+}
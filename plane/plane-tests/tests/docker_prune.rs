@@ -0,0 +1,61 @@
+use chrono::Utc;
+use common::{test_env::TestEnvironment, timeout::WithTimeout};
+use futures_util::StreamExt;
+use plane::drone::runtime::{
+    docker::{DockerRuntime, DockerRuntimeConfig},
+    Runtime,
+};
+use plane_common::{
+    names::{BackendName, Name},
+    types::DockerExecutorConfig,
+};
+use plane_test_macro::plane_test;
+use serde_json::json;
+
+mod common;
+
+/// Tests that pruning keeps the exited containers of backends that may still be restarted,
+/// so that they can be restarted after a prune, and removes the others.
+#[plane_test(120)]
+async fn prune_keeps_restartable_containers(_: TestEnvironment) {
+    let runtime = DockerRuntime::new(DockerRuntimeConfig::default())
+        .await
+        .unwrap();
+
+    let executor_config = json!(DockerExecutorConfig::from_image_with_defaults(
+        "ghcr.io/jamsocket/demo-image-drop-four"
+    ));
+    runtime.prepare(&executor_config).await.unwrap();
+
+    let restartable = BackendName::new_random();
+    let finished = BackendName::new_random();
+    {
+        let restartable = restartable.clone();
+        runtime.retain_callback(Box::new(move |backend_id| backend_id == &restartable));
+    }
+
+    let mut events = runtime.events();
+    for backend_id in [&restartable, &finished] {
+        runtime
+            .spawn(backend_id, &executor_config, None, None)
+            .await
+            .unwrap();
+        runtime.terminate(backend_id, true).await.unwrap();
+    }
+
+    // Wait for both containers to exit.
+    let mut exited = 0;
+    while exited < 2 {
+        let event = events.next().with_timeout(30).await.unwrap().unwrap();
+        if event.backend_id == restartable || event.backend_id == finished {
+            exited += 1;
+        }
+    }
+
+    runtime.prune_containers(Utc::now()).await.unwrap();
+
+    runtime.restart(&restartable).await.unwrap();
+    assert!(runtime.restart(&finished).await.is_err());
+
+    runtime.terminate(&restartable, true).await.unwrap();
+}
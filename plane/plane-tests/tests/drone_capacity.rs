@@ -0,0 +1,88 @@
+use chrono::Utc;
+use common::test_env::TestEnvironment;
+use hyper::StatusCode;
+use plane_common::{
+    log_types::LoggableTime,
+    names::{DroneName, Name},
+    protocol::{ApiErrorKind, Heartbeat, MessageFromDrone},
+    types::{
+        ClusterName, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction,
+        LabelSelector, RestartPolicy, SpawnConfig,
+    },
+    PlaneClientError,
+};
+use plane_test_macro::plane_test;
+use std::time::Duration;
+
+mod common;
+
+/// Return a dummy connect request, which does not use a key.
+fn connect_request(cluster: &ClusterName) -> ConnectRequest {
+    let executable =
+        serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine")).unwrap();
+
+    ConnectRequest {
+        spawn_config: Some(SpawnConfig {
+            id: None,
+            cluster: Some(cluster.clone()),
+            pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
+            executable,
+            lifetime_limit_seconds: None,
+            max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
+            use_static_token: false,
+            subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
+        }),
+        ..Default::default()
+    }
+}
+
+/// Tests that a drone is not given more backends than its `max_backends`.
+#[plane_test]
+async fn drone_at_capacity(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .with_max_backends(Some(1))
+        .connect(&DroneName::new_random())
+        .await
+        .unwrap();
+
+    tracing::info!("Sending initial heartbeat message (mocking the drone).");
+    drone_connection
+        .send(MessageFromDrone::Heartbeat(Heartbeat {
+            local_time: LoggableTime(Utc::now()),
+            resources: None,
+        }))
+        .unwrap();
+
+    // Wait for the drone to be registered.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let response = client
+        .connect(&connect_request(&env.cluster))
+        .await
+        .unwrap();
+    assert!(response.spawned);
+
+    let result = client
+        .connect(&connect_request(&env.cluster))
+        .await
+        .unwrap_err();
+    let PlaneClientError::PlaneError(error, status) = result else {
+        panic!("Unexpected error: {:?}", result);
+    };
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(matches!(error.kind, ApiErrorKind::ClusterAtCapacity));
+
+    let cluster_state = client.cluster_state(&env.cluster).await.unwrap();
+    let drone_state = &cluster_state.drones[0];
+    assert_eq!(drone_state.backend_count, 1);
+    assert_eq!(drone_state.max_backends, Some(1));
+}
@@ -0,0 +1,93 @@
+use crate::common::wait_until_backend_terminated;
+use common::test_env::TestEnvironment;
+use plane_common::types::{
+    ConnectRequest, DockerExecutorConfig, DroneLabels, DronePoolName, IdleAction, LabelSelector,
+    RestartPolicy, SpawnConfig,
+};
+use plane_test_macro::plane_test;
+
+mod common;
+
+fn labels(region: &str) -> DroneLabels {
+    DroneLabels::from([("region".to_string(), region.to_string())])
+}
+
+#[plane_test]
+async fn drone_labels(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+    let eu_drone = env.drone_with_labels(&controller, &labels("eu")).await;
+    let us_drone = env.drone_with_labels(&controller, &labels("us")).await;
+
+    // Wait for the drones to register.
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+
+    let spawn_config = SpawnConfig {
+        id: None,
+        cluster: Some(env.cluster.clone()),
+        pool: DronePoolName::default(),
+        fallback_pools: Vec::new(),
+        label_selector: LabelSelector::default(),
+        executable: serde_json::to_value(DockerExecutorConfig::from_image_with_defaults(
+            "ghcr.io/jamsocket/demo-image-drop-four",
+        ))
+        .unwrap(),
+        lifetime_limit_seconds: Some(5),
+        max_idle_seconds: None,
+        idle_action: IdleAction::Terminate,
+        use_static_token: false,
+        subdomain: None,
+        queue_timeout_seconds: None,
+        restart_policy: RestartPolicy::Never,
+    };
+
+    tracing::info!("Requesting backend that requires a label.");
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                label_selector: LabelSelector {
+                    required: labels("eu"),
+                    ..Default::default()
+                },
+                ..spawn_config.clone()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(response.drone.unwrap(), eu_drone.id);
+
+    tracing::info!("Requesting backend that prefers a label.");
+    let preferred_response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                label_selector: LabelSelector {
+                    preferred: labels("us"),
+                    ..Default::default()
+                },
+                ..spawn_config.clone()
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(preferred_response.drone.unwrap(), us_drone.id);
+
+    tracing::info!("Requesting backend that requires a label no drone has.");
+    let result = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                label_selector: LabelSelector {
+                    required: labels("ap"),
+                    ..Default::default()
+                },
+                ..spawn_config
+            }),
+            ..Default::default()
+        })
+        .await;
+    assert!(result.is_err());
+
+    wait_until_backend_terminated(&client, &response.backend_id).await;
+    wait_until_backend_terminated(&client, &preferred_response.backend_id).await;
+}
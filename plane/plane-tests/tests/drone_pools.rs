@@ -1,8 +1,8 @@
 use crate::common::wait_until_backend_terminated;
 use common::test_env::TestEnvironment;
 use plane_common::types::{
-    ConnectRequest, DockerExecutorConfig, DronePoolName, KeyConfig, PullPolicy, ResourceLimits,
-    SpawnConfig,
+    ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction, KeyConfig, LabelSelector,
+    PullPolicy, ResourceLimits, RestartPolicy, SpawnConfig,
 };
 use plane_test_macro::plane_test;
 use std::collections::HashMap;
@@ -27,6 +27,8 @@ async fn drone_pools(env: TestEnvironment) {
             id: None,
             cluster: Some(env.cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable: serde_json::to_value(DockerExecutorConfig {
                 image: "ghcr.io/jamsocket/demo-image-drop-four".to_string(),
                 pull_policy: Some(PullPolicy::IfNotPresent),
@@ -35,12 +37,25 @@ async fn drone_pools(env: TestEnvironment) {
                 credentials: None,
                 mount: None,
                 network_name: None,
+                port: None,
+                ports: HashMap::default(),
+                cmd: None,
+                entrypoint: None,
+                user: None,
+                working_dir: None,
+                hostname: None,
+                labels: HashMap::default(),
+                readiness_probe: None,
+                liveness_probe: None,
             })
             .unwrap(),
             lifetime_limit_seconds: Some(5),
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         key: Some(KeyConfig {
             name: "reuse-key".to_string(),
@@ -60,7 +75,7 @@ async fn drone_pools(env: TestEnvironment) {
     tracing::info!("Requesting backend from pool.");
     let connect_request_with_pool = ConnectRequest {
         spawn_config: Some(SpawnConfig {
-            pool,
+            pool: pool.clone(),
             ..connect_request.spawn_config.unwrap()
         }),
         key: Some(KeyConfig {
@@ -76,12 +91,13 @@ async fn drone_pools(env: TestEnvironment) {
     assert!(response_from_pool.spawned);
     let response_from_pool_drone = response_from_pool.drone.unwrap().clone();
     assert_eq!(response_from_pool_drone, drone_in_pool.id);
+    assert_eq!(response_from_pool.pool, Some(pool.clone()));
 
     tracing::info!("Requesting backend from pool with different key.");
     let mut connect_request_with_pool_different_key = connect_request_with_pool.clone();
     connect_request_with_pool_different_key.key = Some(KeyConfig {
         name: "different-key".to_string(),
-        ..connect_request_with_pool.key.unwrap()
+        ..connect_request_with_pool.key.clone().unwrap()
     });
 
     let response_from_pool_different_key = client
@@ -99,9 +115,34 @@ async fn drone_pools(env: TestEnvironment) {
     assert_ne!(response_from_pool_drone, response_drone);
     assert_ne!(response_from_pool_different_key_drone, response_drone);
 
+    tracing::info!("Requesting backend from empty pool with fallback.");
+    let connect_request_with_fallback = ConnectRequest {
+        spawn_config: Some(SpawnConfig {
+            pool: DronePoolName::from("empty"),
+            fallback_pools: vec![pool.clone()],
+            ..connect_request_with_pool.spawn_config.clone().unwrap()
+        }),
+        key: Some(KeyConfig {
+            name: "fallback-key".to_string(),
+            ..connect_request_with_pool.key.clone().unwrap()
+        }),
+        ..Default::default()
+    };
+
+    let response_from_fallback = client
+        .connect(&connect_request_with_fallback)
+        .await
+        .unwrap();
+    tracing::info!("Got response from fallback pool.");
+
+    assert!(response_from_fallback.spawned);
+    assert_eq!(response_from_fallback.drone.unwrap(), drone_in_pool.id);
+    assert_eq!(response_from_fallback.pool, Some(pool.clone()));
+
     tracing::info!("Waiting for all backends to terminate.");
     wait_until_backend_terminated(&client, &response.backend_id).await;
     wait_until_backend_terminated(&client, &response_from_pool.backend_id).await;
     wait_until_backend_terminated(&client, &response_from_pool_different_key.backend_id).await;
+    wait_until_backend_terminated(&client, &response_from_fallback.backend_id).await;
     tracing::info!("All backends terminated.");
 }
@@ -1,5 +1,6 @@
-use common::test_env::TestEnvironment;
+use common::{test_env::TestEnvironment, timeout::WithTimeout};
 use plane::database::{node::NodeConnectionStatusChangeNotification, subscribe::Subscription};
+use plane_common::names::{DroneName, Name};
 use plane_test_macro::plane_test;
 
 mod common;
@@ -18,3 +19,29 @@ async fn controller_registers_drone(env: TestEnvironment) {
     assert_eq!(drone_status_list.len(), 1);
     assert_eq!(drone_status_list[0].id, drone_status.payload.node_id);
 }
+
+/// Tests that the controller notices when a drone closes its connection with a normal close
+/// frame, rather than only when the connection drops.
+#[plane_test]
+async fn controller_notices_closed_drone_connection(env: TestEnvironment) {
+    let db = env.db().await;
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let mut listener: Subscription<NodeConnectionStatusChangeNotification> = db.subscribe();
+
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&DroneName::new_random())
+        .await
+        .unwrap();
+
+    let connected = listener.next().with_timeout(10).await.unwrap().unwrap();
+    assert!(connected.payload.connected);
+
+    drone_connection.close().await;
+
+    let disconnected = listener.next().with_timeout(10).await.unwrap().unwrap();
+    assert!(!disconnected.payload.connected);
+    assert_eq!(disconnected.payload.node_id, connected.payload.node_id);
+}
@@ -0,0 +1,183 @@
+use common::test_env::TestEnvironment;
+use plane::controller::ControllerServer;
+use plane_common::{
+    names::{AcmeDnsServerName, ControllerName, DroneName, Name, ProxyName},
+    types::{ApiKeyScope, ClusterName, MintApiKeyRequest, MintJoinTokenRequest},
+    PlaneClient,
+};
+use plane_test_macro::plane_test;
+use tokio::net::TcpListener;
+
+mod common;
+
+/// Returns a client for the controller with an API key that may connect nodes and
+/// manage join tokens.
+async fn api_key_client(env: &mut TestEnvironment, controller: &ControllerServer) -> PlaneClient {
+    let minted = env
+        .db()
+        .await
+        .api_keys()
+        .mint(&MintApiKeyRequest {
+            scopes: vec![ApiKeyScope::NodeSocket, ApiKeyScope::Admin],
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    controller.client().with_api_key(&minted.api_key)
+}
+
+#[plane_test]
+async fn join_token_requires_authenticated_routes(env: TestEnvironment) {
+    let db = env.db().await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    let result = ControllerServer::run_with_listener(
+        db,
+        listener,
+        ControllerName::new_random(),
+        url,
+        None,
+        None,
+        None,
+        None,
+        false,
+        true,
+        None,
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+#[plane_test]
+async fn join_token_required(env: TestEnvironment) {
+    let controller = env.controller_with_join_tokens().await;
+    let client = api_key_client(&mut env, &controller).await;
+
+    assert!(client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&DroneName::new_random())
+        .await
+        .is_err());
+
+    assert!(client
+        .clone()
+        .with_join_token("jt-notarealtoken.secret")
+        .proxy_connection(&env.cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .is_err());
+
+    let minted = env
+        .db()
+        .await
+        .join_tokens()
+        .mint(&MintJoinTokenRequest::default())
+        .await
+        .unwrap();
+
+    // The secret part of the token must match.
+    let (id, _) = minted.join_token.split_once('.').unwrap();
+    assert!(client
+        .clone()
+        .with_join_token(&format!("{}.wrong", id))
+        .proxy_connection(&env.cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .is_err());
+
+    let client = client.with_join_token(&minted.join_token);
+    client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&DroneName::new_random())
+        .await
+        .unwrap();
+    client
+        .proxy_connection(&env.cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .unwrap();
+    client
+        .dns_connection()
+        .connect(&AcmeDnsServerName::new_random())
+        .await
+        .unwrap();
+}
+
+#[plane_test]
+async fn join_token_cluster_restriction(env: TestEnvironment) {
+    let controller = env.controller_with_join_tokens().await;
+    let client = api_key_client(&mut env, &controller).await;
+
+    let minted = env
+        .db()
+        .await
+        .join_tokens()
+        .mint(&MintJoinTokenRequest {
+            cluster: Some(env.cluster.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let client = client.with_join_token(&minted.join_token);
+
+    client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&DroneName::new_random())
+        .await
+        .unwrap();
+
+    let other_cluster: ClusterName = "other.plane.test".parse().unwrap();
+    assert!(client
+        .drone_connection(&other_cluster, &env.pool)
+        .connect(&DroneName::new_random())
+        .await
+        .is_err());
+    assert!(client
+        .proxy_connection(&other_cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .is_err());
+
+    // DNS servers serve every cluster, so they may not use a cluster-restricted token.
+    assert!(client
+        .dns_connection()
+        .connect(&AcmeDnsServerName::new_random())
+        .await
+        .is_err());
+}
+
+#[plane_test]
+async fn join_token_mint_and_revoke(env: TestEnvironment) {
+    let controller = env.controller_with_join_tokens().await;
+    let admin_client = api_key_client(&mut env, &controller).await;
+
+    let minted = admin_client
+        .mint_join_token(&MintJoinTokenRequest {
+            cluster: Some(env.cluster.clone()),
+            description: Some("test token".to_string()),
+        })
+        .await
+        .unwrap();
+
+    let client = admin_client.clone().with_join_token(&minted.join_token);
+    client
+        .proxy_connection(&env.cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .unwrap();
+
+    let result = admin_client.revoke_join_token(&minted.id).await.unwrap();
+    assert!(result.revoked);
+
+    let result = admin_client.revoke_join_token(&minted.id).await.unwrap();
+    assert!(!result.revoked);
+
+    assert!(client
+        .proxy_connection(&env.cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .is_err());
+}
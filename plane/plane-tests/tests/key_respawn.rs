@@ -0,0 +1,122 @@
+use crate::common::timeout::WithTimeout;
+use common::test_env::TestEnvironment;
+use plane_common::{
+    protocol::ApiErrorKind,
+    types::{
+        BackendStatus, ClusterName, ConnectRequest, ConnectResponse, DockerExecutorConfig,
+        DronePoolName, IdleAction, KeyConfig, LabelSelector, RestartPolicy, SpawnConfig,
+    },
+    PlaneClient, PlaneClientError,
+};
+use plane_test_macro::plane_test;
+
+mod common;
+
+fn spawn_config(cluster: &ClusterName) -> SpawnConfig {
+    SpawnConfig {
+        id: None,
+        cluster: Some(cluster.clone()),
+        pool: DronePoolName::default(),
+        fallback_pools: Vec::new(),
+        label_selector: LabelSelector::default(),
+        executable: serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine"))
+            .unwrap(),
+        lifetime_limit_seconds: None,
+        max_idle_seconds: None,
+        idle_action: IdleAction::Terminate,
+        use_static_token: false,
+        subdomain: None,
+        // With no drone running, the backend is queued and then times out, which releases
+        // its key.
+        queue_timeout_seconds: Some(1),
+        restart_policy: RestartPolicy::Never,
+    }
+}
+
+async fn wait_for_termination(client: &PlaneClient, response: &ConnectResponse) {
+    let mut backend_status_stream = client
+        .backend_status_stream(&response.backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    loop {
+        let entry = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap();
+        if entry.status == BackendStatus::Terminated {
+            break;
+        }
+    }
+}
+
+fn assert_no_spawn_config<T: std::fmt::Debug>(result: Result<T, PlaneClientError>) {
+    match result {
+        Err(PlaneClientError::PlaneError(error, _)) => {
+            assert_eq!(error.kind, ApiErrorKind::KeyUnheldNoSpawnConfig);
+        }
+        result => panic!("Expected KeyUnheldNoSpawnConfig error, got {:?}", result),
+    }
+}
+
+/// Tests that a connect request with only a key respawns the key's backend from the spawn config
+/// it was last spawned with.
+#[plane_test]
+async fn key_respawns_from_last_spawn_config(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let key = KeyConfig {
+        name: "document".to_string(),
+        ..Default::default()
+    };
+
+    // A key that has never been spawned can't be connected to without a spawn config.
+    assert_no_spawn_config(
+        client
+            .connect(&ConnectRequest {
+                key: Some(key.clone()),
+                ..Default::default()
+            })
+            .await,
+    );
+
+    let response = client
+        .connect(&ConnectRequest {
+            key: Some(key.clone()),
+            spawn_config: Some(spawn_config(&env.cluster)),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    wait_for_termination(&client, &response).await;
+
+    let respawned = client
+        .connect(&ConnectRequest {
+            key: Some(key.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(respawned.spawned);
+    assert_ne!(respawned.backend_id, response.backend_id);
+    assert_eq!(respawned.status, BackendStatus::Queued);
+    wait_for_termination(&client, &respawned).await;
+
+    // A request with a different tag does not respawn from the stored config.
+    assert_no_spawn_config(
+        client
+            .connect(&ConnectRequest {
+                key: Some(KeyConfig {
+                    tag: "other".to_string(),
+                    ..key.clone()
+                }),
+                ..Default::default()
+            })
+            .await,
+    );
+}
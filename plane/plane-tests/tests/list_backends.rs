@@ -0,0 +1,148 @@
+use chrono::Utc;
+use common::test_env::TestEnvironment;
+use plane_common::{
+    log_types::LoggableTime,
+    names::{DroneName, Name},
+    protocol::{Heartbeat, MessageFromDrone},
+    types::{
+        BackendListQuery, BackendStatus, ClusterName, ConnectRequest, DockerExecutorConfig,
+        DronePoolName, IdleAction, KeyConfig, LabelSelector, RestartPolicy, SpawnConfig,
+    },
+};
+use plane_test_macro::plane_test;
+use std::time::Duration;
+
+mod common;
+
+fn connect_request(cluster: &ClusterName, namespace: &str) -> ConnectRequest {
+    let executable =
+        serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine")).unwrap();
+
+    ConnectRequest {
+        spawn_config: Some(SpawnConfig {
+            id: None,
+            cluster: Some(cluster.clone()),
+            pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
+            executable,
+            lifetime_limit_seconds: None,
+            max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
+            use_static_token: false,
+            subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
+        }),
+        key: Some(KeyConfig {
+            namespace: namespace.to_string(),
+            ..KeyConfig::new_random()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Tests filtering and paginating the backend listing.
+#[plane_test]
+async fn list_backends(env: TestEnvironment) {
+    let drone_id = DroneName::new_random();
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&drone_id)
+        .await
+        .unwrap();
+
+    drone_connection
+        .send(MessageFromDrone::Heartbeat(Heartbeat {
+            local_time: LoggableTime(Utc::now()),
+            resources: None,
+        }))
+        .unwrap();
+
+    // Wait for the drone to be registered.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let mut backend_ids = Vec::new();
+    for namespace in ["a", "a", "b"] {
+        let response = client
+            .connect(&connect_request(&env.cluster, namespace))
+            .await
+            .unwrap();
+        backend_ids.push(response.backend_id);
+    }
+
+    let all = client
+        .list_backends(&BackendListQuery {
+            cluster: Some(env.cluster.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        all.backends
+            .iter()
+            .map(|b| b.id.clone())
+            .collect::<Vec<_>>(),
+        backend_ids
+    );
+    assert!(all.next_cursor.is_none());
+    assert!(all
+        .backends
+        .iter()
+        .all(|b| b.drone.as_ref() == Some(&drone_id)));
+
+    let first_page = client
+        .list_backends(&BackendListQuery {
+            limit: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(first_page.backends.len(), 2);
+    let cursor = first_page.next_cursor.expect("Expected another page.");
+
+    let second_page = client
+        .list_backends(&BackendListQuery {
+            limit: Some(2),
+            cursor: Some(cursor),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(second_page.backends.len(), 1);
+    assert_eq!(second_page.backends[0].id, backend_ids[2]);
+    assert!(second_page.next_cursor.is_none());
+
+    let namespace_b = client
+        .list_backends(&BackendListQuery {
+            key_namespace: Some("b".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(namespace_b.backends.len(), 1);
+    assert_eq!(namespace_b.backends[0].id, backend_ids[2]);
+
+    let ready = client
+        .list_backends(&BackendListQuery {
+            min_status: Some(BackendStatus::Ready),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(ready.backends.is_empty());
+
+    let other_drone = client
+        .list_backends(&BackendListQuery {
+            drone: Some(DroneName::new_random()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(other_drone.backends.is_empty());
+
+    drone_connection.close().await;
+}
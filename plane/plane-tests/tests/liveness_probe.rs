@@ -0,0 +1,136 @@
+use crate::common::timeout::WithTimeout;
+use common::test_env::TestEnvironment;
+use plane::drone::runtime::{
+    docker::{types::ContainerId, SpawnResult},
+    unix_socket::{MessageToClient, MessageToServer},
+};
+use plane_common::types::{
+    BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction, LabelSelector,
+    LivenessProbe, ProbeCheck, RestartPolicy, SpawnConfig, TerminationKind, TerminationReason,
+};
+use plane_test_macro::plane_test;
+use std::{collections::BTreeMap, net::SocketAddr};
+
+mod common;
+
+/// Tests that a ready backend that fails its liveness probe enough times in a row is
+/// terminated with the reason `unhealthy`, and that a passing check resets the count.
+#[plane_test]
+async fn unhealthy_backend_is_terminated(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+    let mut drone = env.drone_with_socket(&controller).await;
+
+    // Wait for the drone to register.
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    let mut executor_config = DockerExecutorConfig::from_image_with_defaults("alpine");
+    let check = ProbeCheck::Http {
+        path: "/health".to_string(),
+        expected_status: None,
+    };
+    executor_config.liveness_probe = Some(LivenessProbe {
+        check: check.clone(),
+        interval_seconds: Some(1),
+        timeout_seconds: None,
+        failure_threshold: Some(2),
+    });
+
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable: serde_json::to_value(executor_config.clone()).unwrap(),
+                lifetime_limit_seconds: None,
+                max_idle_seconds: None,
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let backend_id = response.backend_id.clone();
+
+    let mut backend_status_stream = client
+        .backend_status_stream(&backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Prepare(..)));
+    drone
+        .send_response(&message, MessageToClient::PrepareResult(Ok(())))
+        .await;
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Spawn(..)));
+    drone
+        .send_response(
+            &message,
+            MessageToClient::SpawnResult(Ok(SpawnResult {
+                container_id: ContainerId::from("=no-container=".to_string()),
+                port: 80,
+                named_ports: BTreeMap::default(),
+            })),
+        )
+        .await;
+
+    let message = drone.receive_request().await;
+    drone
+        .send_response(&message, MessageToClient::WaitForBackendResult(Ok(())))
+        .await;
+
+    loop {
+        let status = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status;
+        if status == BackendStatus::Ready {
+            break;
+        }
+    }
+
+    // Fail, pass, then fail twice. Only the last two failures are in a row.
+    for passed in [false, true, false, false] {
+        let message = drone.receive_request().await;
+        assert_eq!(
+            MessageToServer::CheckLiveness(
+                backend_id.clone(),
+                SocketAddr::from(([127, 0, 0, 1], 80)),
+                check.clone(),
+            ),
+            message.message
+        );
+        drone
+            .send_response(&message, MessageToClient::CheckLivenessResult(passed))
+            .await;
+    }
+
+    let entry = backend_status_stream
+        .next()
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.status, BackendStatus::HardTerminating);
+    assert_eq!(entry.termination_reason, Some(TerminationReason::Unhealthy));
+    assert_eq!(entry.termination_kind, Some(TerminationKind::Hard));
+
+    let message = drone.receive_request().await;
+    assert_eq!(
+        MessageToServer::Terminate(backend_id.clone(), true),
+        message.message
+    );
+}
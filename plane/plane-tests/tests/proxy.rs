@@ -1,18 +1,21 @@
+use chrono::Utc;
 use common::{
     localhost_resolver::localhost_client,
     proxy_mock::MockProxy,
     simple_axum_server::{RequestInfo, SimpleAxumServer},
     test_env::TestEnvironment,
 };
+use plane::proxy::metrics::run_metrics_server;
 use plane_common::{
-    log_types::BackendAddr,
+    log_types::{BackendAddr, NamedBackendAddrs},
     names::{BackendName, Name},
-    protocol::{RouteInfo, RouteInfoResponse},
+    protocol::{RouteInfo, RouteInfoResponse, TokensRevoked},
     types::{BearerToken, ClusterName, SecretToken, Subdomain},
 };
 use plane_test_macro::plane_test;
 use reqwest::StatusCode;
 use std::{net::SocketAddr, str::FromStr};
+use tokio::net::TcpListener;
 
 mod common;
 
@@ -94,11 +97,13 @@ async fn proxy_backend_unreachable(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(SocketAddr::from(([123, 234, 123, 234], 12345))),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -108,6 +113,51 @@ async fn proxy_backend_unreachable(env: TestEnvironment) {
     assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
 }
 
+#[plane_test]
+async fn proxy_metrics(env: TestEnvironment) {
+    let metrics_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let metrics_url = format!("http://{}/metrics", metrics_listener.local_addr().unwrap());
+    let _metrics_server = tokio::spawn(run_metrics_server(metrics_listener));
+
+    let mut proxy = MockProxy::new().await;
+    let port = proxy.port();
+    let cluster = ClusterName::from_str(&format!("plane.test:{}", port)).unwrap();
+    let url = format!("http://plane.test:{port}/abc123/");
+    let client = localhost_client();
+    let handle = tokio::spawn(client.get(url).send());
+
+    proxy.recv_route_info_request().await;
+    proxy
+        .send_route_info_response(RouteInfoResponse {
+            token: BearerToken::from("abc123".to_string()),
+            route_info: Some(RouteInfo {
+                backend_id: BackendName::new_random(),
+                address: BackendAddr(SocketAddr::from(([123, 234, 123, 234], 12345))),
+                named_addresses: NamedBackendAddrs::default(),
+                secret_token: SecretToken::from("secret".to_string()),
+                cluster,
+                user: None,
+                user_data: None,
+                subdomain: None,
+                expiration_time: None,
+            }),
+        })
+        .await;
+
+    let response = handle.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+    let metrics = reqwest::get(metrics_url)
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(metrics.contains(r#"plane_proxy_upstream_errors_total{kind="bad_gateway"}"#));
+    assert!(metrics.contains(r#"plane_proxy_request_duration_seconds_count{status="502"}"#));
+    assert!(metrics.contains(r#"plane_proxy_route_cache_lookups_total{result="miss"}"#));
+}
+
 #[plane_test]
 async fn proxy_backend_accepts(env: TestEnvironment) {
     let server = SimpleAxumServer::new().await;
@@ -131,11 +181,13 @@ async fn proxy_backend_accepts(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -147,6 +199,111 @@ async fn proxy_backend_accepts(env: TestEnvironment) {
     assert_eq!(request_info.method, "GET");
 }
 
+#[plane_test]
+async fn proxy_revoked_token(env: TestEnvironment) {
+    let server = SimpleAxumServer::new().await;
+
+    let mut proxy = MockProxy::new().await;
+    let port = proxy.port();
+    let cluster = ClusterName::from_str(&format!("plane.test:{}", port)).unwrap();
+    let url = format!("http://plane.test:{port}/abc123/");
+    let client = localhost_client();
+    let handle = tokio::spawn(client.get(&url).send());
+
+    proxy.recv_route_info_request().await;
+
+    let backend_id = BackendName::new_random();
+    proxy
+        .send_route_info_response(RouteInfoResponse {
+            token: BearerToken::from("abc123".to_string()),
+            route_info: Some(RouteInfo {
+                backend_id: backend_id.clone(),
+                address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
+                secret_token: SecretToken::from("secret".to_string()),
+                cluster,
+                user: Some("alice".to_string()),
+                user_data: None,
+                subdomain: None,
+                expiration_time: None,
+            }),
+        })
+        .await;
+
+    let response = handle.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Revoking another user's tokens should not affect the route.
+    proxy.revoke_tokens(TokensRevoked {
+        backend: backend_id.clone(),
+        user: "bob".to_string(),
+    });
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    proxy.revoke_tokens(TokensRevoked {
+        backend: backend_id,
+        user: "alice".to_string(),
+    });
+    let response = client.get(&url).send().await.unwrap();
+    assert_eq!(response.status(), StatusCode::GONE);
+    proxy.expect_no_route_info_request().await;
+}
+
+#[plane_test]
+async fn proxy_expired_token(env: TestEnvironment) {
+    let server = SimpleAxumServer::new().await;
+
+    let mut proxy = MockProxy::new().await;
+    let port = proxy.port();
+    let cluster = ClusterName::from_str(&format!("plane.test:{}", port)).unwrap();
+    let url = format!("http://plane.test:{port}/abc123/");
+    let client = localhost_client();
+    let handle = tokio::spawn(client.get(&url).send());
+
+    proxy.recv_route_info_request().await;
+
+    proxy
+        .send_route_info_response(RouteInfoResponse {
+            token: BearerToken::from("abc123".to_string()),
+            route_info: Some(RouteInfo {
+                backend_id: BackendName::new_random(),
+                address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
+                secret_token: SecretToken::from("secret".to_string()),
+                cluster,
+                user: None,
+                user_data: None,
+                subdomain: None,
+                expiration_time: Some(Utc::now() + chrono::Duration::seconds(1)),
+            }),
+        })
+        .await;
+
+    let response = handle.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    tokio::time::sleep(std::time::Duration::from_millis(1_100)).await;
+
+    // Once the cached route has expired, the proxy should ask the controller again.
+    let handle = tokio::spawn(client.get(&url).send());
+    let route_info_request = proxy.recv_route_info_request().await;
+    assert_eq!(
+        route_info_request.token,
+        BearerToken::from("abc123".to_string())
+    );
+
+    proxy
+        .send_route_info_response(RouteInfoResponse {
+            token: BearerToken::from("abc123".to_string()),
+            route_info: None,
+        })
+        .await;
+
+    let response = handle.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::GONE);
+}
+
 #[plane_test]
 async fn proxy_static_token(env: TestEnvironment) {
     let server = SimpleAxumServer::new().await;
@@ -170,11 +327,13 @@ async fn proxy_static_token(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -209,11 +368,13 @@ async fn proxy_expected_subdomain_not_present(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: Some(Subdomain::from_str("missing-subdomain").unwrap()),
+                expiration_time: None,
             }),
         })
         .await;
@@ -245,11 +406,13 @@ async fn proxy_expected_subdomain_is_present(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: Some(Subdomain::from_str("mysubdomain").unwrap()),
+                expiration_time: None,
             }),
         })
         .await;
@@ -281,11 +444,13 @@ async fn proxy_backend_passes_forwarded_headers(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -322,11 +487,13 @@ async fn proxy_returns_backend_id_in_header(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: backend_id.clone(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -3,16 +3,17 @@ use common::{
     simple_axum_server::SimpleAxumServer, test_env::TestEnvironment,
     websocket_echo_server::WebSocketEchoServer,
 };
+use futures_util::StreamExt;
 use plane_common::{
-    log_types::BackendAddr,
+    log_types::{BackendAddr, NamedBackendAddrs},
     names::{BackendName, Name},
-    protocol::{RouteInfo, RouteInfoResponse},
+    protocol::{RouteInfo, RouteInfoResponse, TokensRevoked},
     types::{BearerToken, ClusterName, SecretToken},
 };
 use plane_test_macro::plane_test;
 use reqwest::StatusCode;
 use std::str::FromStr;
-use tokio_tungstenite::connect_async;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
 
 mod common;
 
@@ -44,11 +45,13 @@ async fn proxy_marks_backend_as_recently_active(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: backend_name.clone(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -87,11 +90,13 @@ async fn proxy_marks_websocket_backend_as_active(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: backend_name.clone(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -112,3 +117,57 @@ async fn proxy_marks_websocket_backend_as_active(env: TestEnvironment) {
     assert_eq!(backend_entry.active_connections, 0);
     assert!(backend_entry.had_recent_connection);
 }
+
+#[plane_test]
+async fn proxy_closes_websocket_after_missed_revocation(env: TestEnvironment) {
+    let server = WebSocketEchoServer::new().await;
+    let backend_name = BackendName::new_random();
+
+    let mut proxy = MockProxy::new().await;
+    let port = proxy.port();
+    let cluster = ClusterName::from_str(&format!("localhost:{}", port)).unwrap();
+    let url = format!("ws://localhost:{port}/abc123/ws");
+
+    let handle = tokio::spawn(connect_async(url));
+
+    proxy.recv_route_info_request().await;
+    proxy
+        .send_route_info_response(RouteInfoResponse {
+            token: BearerToken::from("abc123".to_string()),
+            route_info: Some(RouteInfo {
+                backend_id: backend_name.clone(),
+                address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
+                secret_token: SecretToken::from("secret".to_string()),
+                cluster,
+                user: Some("alice".to_string()),
+                user_data: None,
+                subdomain: None,
+                expiration_time: None,
+            }),
+        })
+        .await;
+
+    let (mut ws_stream, _) = handle.await.unwrap().unwrap();
+
+    // The test runtime is single-threaded, so the connection cannot receive revocations until
+    // this loop yields. By then, alice's revocation has been pushed out of the channel.
+    proxy.revoke_tokens(TokensRevoked {
+        backend: backend_name.clone(),
+        user: "alice".to_string(),
+    });
+    for _ in 0..1_000 {
+        proxy.revoke_tokens(TokensRevoked {
+            backend: backend_name.clone(),
+            user: "bob".to_string(),
+        });
+    }
+
+    let result = tokio::time::timeout(std::time::Duration::from_secs(5), ws_stream.next())
+        .await
+        .expect("Connection should be closed after a missed revocation.");
+    assert!(!matches!(
+        result,
+        Some(Ok(Message::Text(_) | Message::Binary(_)))
+    ));
+}
@@ -3,7 +3,7 @@ use common::{
     simple_axum_server::SimpleAxumServer, test_env::TestEnvironment,
 };
 use plane_common::{
-    log_types::BackendAddr,
+    log_types::{BackendAddr, NamedBackendAddrs},
     names::{BackendName, Name},
     protocol::{RouteInfo, RouteInfoResponse},
     types::{BearerToken, ClusterName, SecretToken},
@@ -90,11 +90,13 @@ async fn proxy_valid_request_has_cors_headers(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -5,7 +5,7 @@ use common::{
     test_env::TestEnvironment,
 };
 use plane_common::{
-    log_types::BackendAddr,
+    log_types::{BackendAddr, NamedBackendAddrs},
     names::{BackendName, Name},
     protocol::{RouteInfo, RouteInfoResponse},
     types::{BearerToken, ClusterName, SecretToken},
@@ -47,11 +47,13 @@ async fn proxy_fake_verified_headers_are_stripped(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::new_random(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret".to_string()),
                 cluster,
                 user: None,
                 user_data: None,
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -93,6 +95,7 @@ async fn proxy_plane_headers_are_set(env: TestEnvironment) {
             route_info: Some(RouteInfo {
                 backend_id: BackendName::try_from("backend123".to_string()).unwrap(),
                 address: BackendAddr(server.addr()),
+                named_addresses: NamedBackendAddrs::default(),
                 secret_token: SecretToken::from("secret987".to_string()),
                 cluster,
                 user: Some("auser123".to_string()),
@@ -101,6 +104,7 @@ async fn proxy_plane_headers_are_set(env: TestEnvironment) {
                     "email": "a@example.com",
                 })),
                 subdomain: None,
+                expiration_time: None,
             }),
         })
         .await;
@@ -0,0 +1,96 @@
+use common::{
+    localhost_resolver::localhost_client,
+    proxy_mock::MockProxy,
+    simple_axum_server::{RequestInfo, SimpleAxumServer},
+    test_env::TestEnvironment,
+};
+use plane_common::{
+    log_types::{BackendAddr, NamedBackendAddrs},
+    names::{BackendName, Name},
+    protocol::{RouteInfo, RouteInfoResponse},
+    types::{BearerToken, ClusterName, SecretToken},
+};
+use plane_test_macro::plane_test;
+use reqwest::StatusCode;
+use std::{net::SocketAddr, str::FromStr};
+
+mod common;
+
+fn route_info(cluster: ClusterName, named_addresses: NamedBackendAddrs) -> RouteInfo {
+    RouteInfo {
+        backend_id: BackendName::new_random(),
+        // Nothing listens here, so requests routed to the main port fail.
+        address: BackendAddr(SocketAddr::from(([123, 234, 123, 234], 12345))),
+        named_addresses,
+        secret_token: SecretToken::from("secret".to_string()),
+        cluster,
+        user: None,
+        user_data: None,
+        subdomain: None,
+        expiration_time: None,
+    }
+}
+
+#[plane_test]
+async fn proxy_routes_to_named_port(env: TestEnvironment) {
+    let server = SimpleAxumServer::new().await;
+
+    let mut proxy = MockProxy::new().await;
+    let port = proxy.port();
+    let cluster = ClusterName::from_str(&format!("plane.test:{}", port)).unwrap();
+    let url = format!("http://plane.test:{port}/abc123~metrics/stats");
+    let client = localhost_client();
+    let handle = tokio::spawn(client.get(url).send());
+
+    let route_info_request = proxy.recv_route_info_request().await;
+    assert_eq!(
+        route_info_request.token,
+        BearerToken::from("abc123".to_string())
+    );
+
+    proxy
+        .send_route_info_response(RouteInfoResponse {
+            token: BearerToken::from("abc123".to_string()),
+            route_info: Some(route_info(
+                cluster,
+                NamedBackendAddrs::from([("metrics".to_string(), BackendAddr(server.addr()))]),
+            )),
+        })
+        .await;
+
+    let response = handle.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let request_info: RequestInfo = response.json().await.unwrap();
+    assert_eq!(request_info.path, "/stats");
+}
+
+#[plane_test]
+async fn proxy_unknown_port_name_is_not_found(env: TestEnvironment) {
+    let server = SimpleAxumServer::new().await;
+
+    let mut proxy = MockProxy::new().await;
+    let port = proxy.port();
+    let cluster = ClusterName::from_str(&format!("plane.test:{}", port)).unwrap();
+    let url = format!("http://plane.test:{port}/abc123~debug/");
+    let client = localhost_client();
+    let handle = tokio::spawn(client.get(url).send());
+
+    let route_info_request = proxy.recv_route_info_request().await;
+    assert_eq!(
+        route_info_request.token,
+        BearerToken::from("abc123".to_string())
+    );
+
+    proxy
+        .send_route_info_response(RouteInfoResponse {
+            token: BearerToken::from("abc123".to_string()),
+            route_info: Some(route_info(
+                cluster,
+                NamedBackendAddrs::from([("metrics".to_string(), BackendAddr(server.addr()))]),
+            )),
+        })
+        .await;
+
+    let response = handle.await.unwrap().unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
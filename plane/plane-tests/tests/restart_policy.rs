@@ -0,0 +1,236 @@
+use crate::common::timeout::WithTimeout;
+use common::test_env::TestEnvironment;
+use plane::drone::runtime::{
+    docker::{types::ContainerId, SpawnResult, TerminateEvent},
+    unix_socket::{MessageToClient, MessageToServer},
+};
+use plane_common::{
+    log_types::BackendAddr,
+    names::{Name, ProxyName},
+    protocol::{MessageFromProxy, MessageToProxy, RouteInfoRequest, RouteInfoResponse},
+    types::{
+        backend_state::RestartInfo, BackendState, BackendStatus, ConnectRequest,
+        DockerExecutorConfig, DronePoolName, IdleAction, LabelSelector, RestartPolicy, SpawnConfig,
+    },
+};
+use plane_test_macro::plane_test;
+use std::{collections::BTreeMap, net::SocketAddr};
+
+mod common;
+
+fn spawn_result(port: u16) -> SpawnResult {
+    SpawnResult {
+        container_id: ContainerId::from("=no-container=".to_string()),
+        port,
+        named_ports: BTreeMap::default(),
+    }
+}
+
+/// Tests that a backend whose process exits with an error is restarted under the same backend
+/// id, until its restart policy's attempts run out, and that proxies drop the route to its old
+/// address.
+#[plane_test]
+async fn restart_on_failure(env: TestEnvironment) {
+    let db = env.db().await;
+    let controller = env.controller().await;
+    let client = controller.client();
+    let mut drone = env.drone_with_socket(&controller).await;
+
+    // Wait for the drone to register.
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    let executor_config = DockerExecutorConfig::from_image_with_defaults("alpine");
+
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable: serde_json::to_value(executor_config.clone()).unwrap(),
+                lifetime_limit_seconds: None,
+                max_idle_seconds: None,
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::OnFailure {
+                    max_attempts: 1,
+                    backoff_seconds: 0,
+                },
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let backend_id = response.backend_id.clone();
+
+    let mut backend_status_stream = client
+        .backend_status_stream(&backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Prepare(..)));
+    drone
+        .send_response(&message, MessageToClient::PrepareResult(Ok(())))
+        .await;
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Spawn(..)));
+    drone
+        .send_response(&message, MessageToClient::SpawnResult(Ok(spawn_result(80))))
+        .await;
+
+    let message = drone.receive_request().await;
+    drone
+        .send_response(&message, MessageToClient::WaitForBackendResult(Ok(())))
+        .await;
+
+    loop {
+        let status = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status;
+        if status == BackendStatus::Ready {
+            break;
+        }
+    }
+
+    let mut proxy = client
+        .proxy_connection(&env.cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .unwrap();
+    let route_info_request = MessageFromProxy::RouteInfoRequest(RouteInfoRequest {
+        token: response.token.clone(),
+    });
+
+    proxy.send(route_info_request.clone()).unwrap();
+    let result = proxy.recv().with_timeout(10).await.unwrap().unwrap();
+    let MessageToProxy::RouteInfoResponse(RouteInfoResponse { route_info, .. }) = result else {
+        panic!("Unexpected message: {:?}", result);
+    };
+    assert_eq!(
+        route_info.unwrap().address,
+        BackendAddr(SocketAddr::from(([127, 0, 0, 1], 80)))
+    );
+
+    tracing::info!("Crashing the backend.");
+    drone
+        .send_message(MessageToClient::TerminateEvent(TerminateEvent {
+            backend_id: backend_id.clone(),
+            exit_code: Some(1),
+        }))
+        .await;
+
+    // The backend is not routable while it restarts, so proxies drop the route to it.
+    assert_eq!(
+        backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        BackendStatus::Restarting,
+    );
+    let result = proxy.recv().with_timeout(10).await.unwrap().unwrap();
+    assert_eq!(
+        result,
+        MessageToProxy::InvalidateRoutes {
+            backend: backend_id.clone()
+        }
+    );
+
+    // A request for the route waits until the restarted backend is ready.
+    proxy.send(route_info_request).unwrap();
+
+    let message = drone.receive_request().await;
+    assert_eq!(
+        MessageToServer::Restart(backend_id.clone()),
+        message.message
+    );
+    drone
+        .send_response(
+            &message,
+            MessageToClient::RestartResult(Ok(spawn_result(81))),
+        )
+        .await;
+
+    let message = drone.receive_request().await;
+    assert_eq!(
+        MessageToServer::WaitForBackend(backend_id.clone(), SocketAddr::from(([127, 0, 0, 1], 81))),
+        message.message
+    );
+    drone
+        .send_response(&message, MessageToClient::WaitForBackendResult(Ok(())))
+        .await;
+
+    for expected in [BackendStatus::Waiting, BackendStatus::Ready] {
+        assert_eq!(
+            backend_status_stream
+                .next()
+                .with_timeout(10)
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            expected,
+        );
+    }
+
+    // The proxy is told to drop routes again when the backend moves to its new address, and
+    // then gets the route to the new address.
+    let result = proxy.recv().with_timeout(10).await.unwrap().unwrap();
+    assert_eq!(
+        result,
+        MessageToProxy::InvalidateRoutes {
+            backend: backend_id.clone()
+        }
+    );
+    let result = proxy.recv().with_timeout(10).await.unwrap().unwrap();
+    let MessageToProxy::RouteInfoResponse(RouteInfoResponse { token, route_info }) = result else {
+        panic!("Unexpected message: {:?}", result);
+    };
+    assert_eq!(token, response.token);
+    assert_eq!(
+        route_info.unwrap().address,
+        BackendAddr(SocketAddr::from(([127, 0, 0, 1], 81)))
+    );
+
+    let backend = db.backend().backend(&backend_id).await.unwrap().unwrap();
+    let BackendState::Ready { restart, .. } = backend.state else {
+        panic!("Unexpected state: {:?}", backend.state);
+    };
+    assert_eq!(
+        restart,
+        Some(RestartInfo {
+            restart_count: 1,
+            last_exit_code: Some(1),
+        })
+    );
+
+    tracing::info!("Crashing the backend again, after its restart attempts have run out.");
+    drone
+        .send_message(MessageToClient::TerminateEvent(TerminateEvent {
+            backend_id: backend_id.clone(),
+            exit_code: Some(2),
+        }))
+        .await;
+
+    let entry = backend_status_stream
+        .next()
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(entry.status, BackendStatus::Terminated);
+    assert_eq!(entry.exit_error, Some(true));
+}
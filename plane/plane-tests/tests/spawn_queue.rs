@@ -0,0 +1,152 @@
+use crate::common::timeout::WithTimeout;
+use chrono::Utc;
+use common::test_env::TestEnvironment;
+use plane_common::{
+    log_types::LoggableTime,
+    names::{DroneName, Name},
+    protocol::{BackendAction, BackendActionMessage, Heartbeat, MessageFromDrone, MessageToDrone},
+    types::{
+        BackendStatus, ClusterName, ConnectRequest, DockerExecutorConfig, DronePoolName,
+        IdleAction, LabelSelector, RestartPolicy, SpawnConfig, TerminationReason,
+    },
+};
+use plane_test_macro::plane_test;
+
+mod common;
+
+/// Return a connect request that queues the backend for up to `queue_timeout_seconds`.
+fn connect_request(cluster: &ClusterName, queue_timeout_seconds: i32) -> ConnectRequest {
+    let executable =
+        serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine")).unwrap();
+
+    ConnectRequest {
+        spawn_config: Some(SpawnConfig {
+            id: None,
+            cluster: Some(cluster.clone()),
+            pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
+            executable,
+            lifetime_limit_seconds: None,
+            max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
+            use_static_token: false,
+            subdomain: None,
+            queue_timeout_seconds: Some(queue_timeout_seconds),
+            restart_policy: RestartPolicy::Never,
+        }),
+        ..Default::default()
+    }
+}
+
+/// Tests that a backend requested while no drone is available is queued, and scheduled to a
+/// drone once one connects.
+#[plane_test]
+async fn queued_backend_is_scheduled(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let response = client
+        .connect(&connect_request(&env.cluster, 60))
+        .await
+        .unwrap();
+    assert!(response.spawned);
+    assert_eq!(response.status, BackendStatus::Queued);
+    assert!(response.drone.is_none());
+
+    let mut backend_status_stream = client
+        .backend_status_stream(&response.backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        BackendStatus::Queued,
+    );
+
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&DroneName::new_random())
+        .await
+        .unwrap();
+
+    tracing::info!("Sending initial heartbeat message (mocking the drone).");
+    drone_connection
+        .send(MessageFromDrone::Heartbeat(Heartbeat {
+            local_time: LoggableTime(Utc::now()),
+            resources: None,
+        }))
+        .unwrap();
+
+    tracing::info!("Waiting for the queued backend to be sent to the drone.");
+    let message = drone_connection
+        .recv()
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+    let MessageToDrone::Action(BackendActionMessage {
+        backend_id, action, ..
+    }) = message
+    else {
+        panic!("Unexpected message: {:?}", message);
+    };
+    assert_eq!(backend_id, response.backend_id);
+    assert!(matches!(action, BackendAction::Spawn { .. }));
+
+    assert_eq!(
+        backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        BackendStatus::Scheduled,
+    );
+}
+
+/// Tests that a queued backend is terminated once its queue timeout passes.
+#[plane_test]
+async fn queued_backend_times_out(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let response = client
+        .connect(&connect_request(&env.cluster, 1))
+        .await
+        .unwrap();
+    assert_eq!(response.status, BackendStatus::Queued);
+
+    let mut backend_status_stream = client
+        .backend_status_stream(&response.backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    loop {
+        let entry = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap();
+
+        if entry.status == BackendStatus::Terminated {
+            assert_eq!(
+                entry.termination_reason,
+                Some(TerminationReason::QueueTimeout)
+            );
+            break;
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::common::{test_env::TestEnvironment, wait_until_backend_terminated};
 use plane_common::types::{
-    ConnectRequest, DockerExecutorConfig, DronePoolName, PullPolicy, ResourceLimits, SpawnConfig,
-    Subdomain,
+    ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction, LabelSelector, PullPolicy,
+    ResourceLimits, RestartPolicy, SpawnConfig, Subdomain,
 };
 use plane_test_macro::plane_test;
 use serde_json::Map;
@@ -24,6 +24,8 @@ async fn subdomains(env: TestEnvironment) {
             id: None,
             cluster: Some(env.cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable: serde_json::to_value(DockerExecutorConfig {
                 image: "ghcr.io/jamsocket/demo-image-drop-four".to_string(),
                 pull_policy: Some(PullPolicy::IfNotPresent),
@@ -32,16 +34,30 @@ async fn subdomains(env: TestEnvironment) {
                 credentials: None,
                 mount: None,
                 network_name: None,
+                port: None,
+                ports: HashMap::default(),
+                cmd: None,
+                entrypoint: None,
+                user: None,
+                working_dir: None,
+                hostname: None,
+                labels: HashMap::default(),
+                readiness_probe: None,
+                liveness_probe: None,
             })
             .unwrap(),
             lifetime_limit_seconds: Some(5),
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         key: None,
         user: None,
         auth: Map::default(),
+        token_lifetime_seconds: None,
     };
 
     // Connect request with subdomain
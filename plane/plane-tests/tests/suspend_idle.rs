@@ -0,0 +1,170 @@
+use crate::common::timeout::WithTimeout;
+use common::test_env::TestEnvironment;
+use plane::drone::runtime::{
+    docker::{types::ContainerId, SpawnResult},
+    unix_socket::{MessageToClient, MessageToServer},
+};
+use plane_common::{
+    log_types::BackendAddr,
+    names::{Name, ProxyName},
+    protocol::{MessageFromProxy, MessageToProxy, RouteInfoRequest, RouteInfoResponse},
+    types::{
+        BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction,
+        LabelSelector, RestartPolicy, SpawnConfig,
+    },
+};
+use plane_test_macro::plane_test;
+use std::{collections::BTreeMap, net::SocketAddr};
+
+mod common;
+
+/// Tests that a backend with the suspend idle action is suspended once idle, and resumed
+/// when a proxy next asks for its route.
+#[plane_test]
+async fn suspend_and_resume_idle_backend(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+    let mut drone = env.drone_with_socket(&controller).await;
+
+    // Wait for the drone to register.
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    let executor_config = DockerExecutorConfig::from_image_with_defaults("alpine");
+
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable: serde_json::to_value(executor_config.clone()).unwrap(),
+                lifetime_limit_seconds: None,
+                max_idle_seconds: Some(1),
+                idle_action: IdleAction::Suspend,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let backend_id = response.backend_id.clone();
+
+    let mut backend_status_stream = client
+        .backend_status_stream(&backend_id)
+        .with_timeout(10)
+        .await
+        .unwrap()
+        .unwrap();
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Prepare(..)));
+    drone
+        .send_response(&message, MessageToClient::PrepareResult(Ok(())))
+        .await;
+
+    let message = drone.receive_request().await;
+    assert!(matches!(message.message, MessageToServer::Spawn(..)));
+    drone
+        .send_response(
+            &message,
+            MessageToClient::SpawnResult(Ok(SpawnResult {
+                container_id: ContainerId::from("=no-container=".to_string()),
+                port: 80,
+                named_ports: BTreeMap::default(),
+            })),
+        )
+        .await;
+
+    let message = drone.receive_request().await;
+    drone
+        .send_response(&message, MessageToClient::WaitForBackendResult(Ok(())))
+        .await;
+
+    loop {
+        let status = backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status;
+        if status == BackendStatus::Ready {
+            break;
+        }
+    }
+
+    let mut proxy = client
+        .proxy_connection(&env.cluster)
+        .connect(&ProxyName::new_random())
+        .await
+        .unwrap();
+
+    tracing::info!("Waiting for the idle backend to be suspended.");
+    let message = drone.receive_request().with_timeout(20).await.unwrap();
+    assert_eq!(
+        MessageToServer::Suspend(backend_id.clone()),
+        message.message
+    );
+    drone
+        .send_response(&message, MessageToClient::SuspendResult(Ok(())))
+        .await;
+
+    assert_eq!(
+        backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        BackendStatus::Suspended,
+    );
+
+    let result = proxy.recv().with_timeout(10).await.unwrap().unwrap();
+    assert_eq!(
+        result,
+        MessageToProxy::BackendSuspended {
+            backend: backend_id.clone()
+        }
+    );
+
+    tracing::info!("Requesting route info, which should resume the backend.");
+    proxy
+        .send(MessageFromProxy::RouteInfoRequest(RouteInfoRequest {
+            token: response.token.clone(),
+        }))
+        .unwrap();
+
+    let message = drone.receive_request().with_timeout(10).await.unwrap();
+    assert_eq!(MessageToServer::Resume(backend_id.clone()), message.message);
+    drone
+        .send_response(&message, MessageToClient::ResumeResult(Ok(())))
+        .await;
+
+    let result = proxy.recv().with_timeout(10).await.unwrap().unwrap();
+    let MessageToProxy::RouteInfoResponse(RouteInfoResponse { token, route_info }) = result else {
+        panic!("Unexpected message: {:?}", result);
+    };
+    assert_eq!(token, response.token);
+    let route_info = route_info.unwrap();
+    assert_eq!(route_info.backend_id, backend_id);
+    assert_eq!(
+        route_info.address,
+        BackendAddr(SocketAddr::from(([127, 0, 0, 1], 80)))
+    );
+
+    assert_eq!(
+        backend_status_stream
+            .next()
+            .with_timeout(10)
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        BackendStatus::Ready,
+    );
+}
@@ -0,0 +1,148 @@
+use chrono::Utc;
+use common::test_env::TestEnvironment;
+use plane_common::{
+    log_types::LoggableTime,
+    names::{DroneName, Name},
+    protocol::{Heartbeat, MessageFromDrone},
+    types::{
+        BackendListQuery, BearerToken, ConnectRequest, DockerExecutorConfig, DronePoolName,
+        IdleAction, LabelSelector, RestartPolicy, SpawnConfig, TokenRefreshRequest,
+    },
+    PlaneClientError,
+};
+use plane_test_macro::plane_test;
+use std::time::Duration;
+
+mod common;
+
+/// Tests that tokens are issued with the requested lifetime and can be refreshed, and that
+/// invalid lifetimes are rejected.
+#[plane_test]
+async fn token_refresh(env: TestEnvironment) {
+    let drone_id = DroneName::new_random();
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&drone_id)
+        .await
+        .unwrap();
+
+    drone_connection
+        .send(MessageFromDrone::Heartbeat(Heartbeat {
+            local_time: LoggableTime(Utc::now()),
+            resources: None,
+        }))
+        .unwrap();
+
+    // Wait for the drone to be registered.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let executable =
+        serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine")).unwrap();
+    let spawn_config = SpawnConfig {
+        id: None,
+        cluster: Some(env.cluster.clone()),
+        pool: DronePoolName::default(),
+        fallback_pools: Vec::new(),
+        label_selector: LabelSelector::default(),
+        executable,
+        lifetime_limit_seconds: None,
+        max_idle_seconds: None,
+        idle_action: IdleAction::Terminate,
+        use_static_token: false,
+        subdomain: None,
+        queue_timeout_seconds: None,
+        restart_policy: RestartPolicy::Never,
+    };
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(spawn_config.clone()),
+            token_lifetime_seconds: Some(60),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let expiration_time = response
+        .token_expiration_time
+        .expect("Expected token to have an expiration time.");
+    assert!(expiration_time <= Utc::now() + chrono::Duration::seconds(60));
+
+    let refreshed = client
+        .refresh_token(
+            &response.backend_id,
+            &TokenRefreshRequest {
+                token: response.token.clone(),
+                lifetime_seconds: Some(8 * 3600),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(refreshed.token, response.token);
+    assert!(refreshed.expiration_time > Utc::now() + chrono::Duration::hours(7));
+
+    let result = client
+        .refresh_token(
+            &response.backend_id,
+            &TokenRefreshRequest {
+                token: BearerToken::from("not-a-real-token".to_string()),
+                lifetime_seconds: None,
+            },
+        )
+        .await;
+    assert!(matches!(
+        result,
+        Err(PlaneClientError::PlaneError(
+            _,
+            reqwest::StatusCode::NOT_FOUND
+        ))
+    ));
+
+    tracing::info!("Requests with an invalid token lifetime are rejected without spawning.");
+    for lifetime_seconds in [0, u64::MAX] {
+        let result = client
+            .connect(&ConnectRequest {
+                spawn_config: Some(spawn_config.clone()),
+                token_lifetime_seconds: Some(lifetime_seconds),
+                ..Default::default()
+            })
+            .await;
+        assert!(matches!(
+            result,
+            Err(PlaneClientError::PlaneError(
+                _,
+                reqwest::StatusCode::BAD_REQUEST
+            ))
+        ));
+
+        let result = client
+            .refresh_token(
+                &response.backend_id,
+                &TokenRefreshRequest {
+                    token: response.token.clone(),
+                    lifetime_seconds: Some(lifetime_seconds),
+                },
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(PlaneClientError::PlaneError(
+                _,
+                reqwest::StatusCode::BAD_REQUEST
+            ))
+        ));
+    }
+
+    let backends = client
+        .list_backends(&BackendListQuery {
+            cluster: Some(env.cluster.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(backends.backends.len(), 1);
+
+    drone_connection.close().await;
+}
@@ -1,8 +1,8 @@
 use crate::common::test_env::TestEnvironment;
 use crate::common::wait_until_backend_terminated;
 use plane_common::types::{
-    ConnectRequest, DockerExecutorConfig, DronePoolName, KeyConfig, Mount, PullPolicy,
-    ResourceLimits, SpawnConfig,
+    ConnectRequest, DockerExecutorConfig, DronePoolName, IdleAction, KeyConfig, LabelSelector,
+    Mount, PullPolicy, ResourceLimits, RestartPolicy, SpawnConfig,
 };
 use plane_test_macro::plane_test;
 use serde_json::Map;
@@ -36,6 +36,8 @@ async fn volume_mounts(env: TestEnvironment) {
             id: None,
             cluster: Some(env.cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable: serde_json::to_value(DockerExecutorConfig {
                 image: "ghcr.io/jamsocket/demo-image-drop-four".to_string(),
                 pull_policy: Some(PullPolicy::IfNotPresent),
@@ -44,16 +46,30 @@ async fn volume_mounts(env: TestEnvironment) {
                 credentials: None,
                 mount: Some(Mount::Path(PathBuf::from(mount))),
                 network_name: None,
+                port: None,
+                ports: HashMap::default(),
+                cmd: None,
+                entrypoint: None,
+                user: None,
+                working_dir: None,
+                hostname: None,
+                labels: HashMap::default(),
+                readiness_probe: None,
+                liveness_probe: None,
             })
             .unwrap(),
             lifetime_limit_seconds: Some(5),
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         key: None,
         user: None,
         auth: Map::default(),
+        token_lifetime_seconds: None,
     };
 
     let response_custom_mount = client.connect(&connect_request_custom_mount).await.unwrap();
@@ -67,6 +83,8 @@ async fn volume_mounts(env: TestEnvironment) {
             id: None,
             cluster: Some(env.cluster.clone()),
             pool: DronePoolName::default(),
+            fallback_pools: Vec::new(),
+            label_selector: LabelSelector::default(),
             executable: serde_json::to_value(DockerExecutorConfig {
                 image: "ghcr.io/jamsocket/demo-image-drop-four".to_string(),
                 pull_policy: Some(PullPolicy::IfNotPresent),
@@ -75,12 +93,25 @@ async fn volume_mounts(env: TestEnvironment) {
                 credentials: None,
                 mount: Some(Mount::Bool(true)),
                 network_name: None,
+                port: None,
+                ports: HashMap::default(),
+                cmd: None,
+                entrypoint: None,
+                user: None,
+                working_dir: None,
+                hostname: None,
+                labels: HashMap::default(),
+                readiness_probe: None,
+                liveness_probe: None,
             })
             .unwrap(),
             lifetime_limit_seconds: Some(5),
             max_idle_seconds: None,
+            idle_action: IdleAction::Terminate,
             use_static_token: false,
             subdomain: None,
+            queue_timeout_seconds: None,
+            restart_policy: RestartPolicy::Never,
         }),
         key: Some(KeyConfig {
             name: key.to_string(),
@@ -89,6 +120,7 @@ async fn volume_mounts(env: TestEnvironment) {
         }),
         user: None,
         auth: Map::default(),
+        token_lifetime_seconds: None,
     };
 
     let response_key_mount = client.connect(&connect_request_key_mount).await.unwrap();
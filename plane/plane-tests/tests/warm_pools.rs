@@ -0,0 +1,191 @@
+use crate::common::timeout::WithTimeout;
+use chrono::Utc;
+use common::test_env::TestEnvironment;
+use plane_common::{
+    log_types::{BackendAddr, LoggableTime},
+    names::{BackendName, DroneName, Name},
+    protocol::{
+        BackendAction, BackendActionMessage, BackendEventId, BackendStateMessage, Heartbeat,
+        MessageFromDrone, MessageToDrone,
+    },
+    typed_socket::TypedSocket,
+    types::{
+        BackendState, BackendStatus, ConnectRequest, CreateWarmPoolRequest, DockerExecutorConfig,
+        IdleAction, KeyConfig, LabelSelector, RestartPolicy, SpawnConfig,
+    },
+};
+use plane_test_macro::plane_test;
+use std::time::Duration;
+
+mod common;
+
+/// Receives the next backend action sent to the (mock) drone, and acks it.
+async fn next_action(
+    drone_connection: &mut TypedSocket<MessageFromDrone>,
+) -> (BackendName, BackendAction) {
+    loop {
+        let msg = drone_connection
+            .recv()
+            .with_timeout(15)
+            .await
+            .unwrap()
+            .unwrap();
+
+        if let MessageToDrone::Action(BackendActionMessage {
+            action_id,
+            backend_id,
+            action,
+            ..
+        }) = msg
+        {
+            drone_connection
+                .send(MessageFromDrone::AckAction { action_id })
+                .unwrap();
+            return (backend_id, action);
+        }
+    }
+}
+
+/// Tests that a connect request claims a ready backend from a matching warm pool, unless it
+/// asks for a restart policy, and that the warm pool is replenished and cleaned up when deleted.
+#[plane_test(60)]
+async fn warm_pools(env: TestEnvironment) {
+    let controller = env.controller().await;
+    let client = controller.client();
+
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&DroneName::new_random())
+        .await
+        .unwrap();
+
+    tracing::info!("Sending initial heartbeat message (mocking the drone).");
+    drone_connection
+        .send(MessageFromDrone::Heartbeat(Heartbeat {
+            local_time: LoggableTime(Utc::now()),
+            resources: None,
+        }))
+        .unwrap();
+
+    // Wait for the drone to be registered.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let warm_pool = client
+        .create_warm_pool(&CreateWarmPoolRequest {
+            cluster: Some(env.cluster.clone()),
+            pool: env.pool.clone(),
+            executable: serde_json::to_value(DockerExecutorConfig::from_image_with_defaults(
+                "alpine",
+            ))
+            .unwrap(),
+            size: 1,
+        })
+        .await
+        .unwrap();
+
+    tracing::info!("Waiting for the warm backend to be spawned.");
+    let (warm_backend, action) = next_action(&mut drone_connection).await;
+    assert!(matches!(action, BackendAction::Spawn { .. }));
+
+    drone_connection
+        .send(MessageFromDrone::BackendEvent(BackendStateMessage {
+            event_id: BackendEventId::from(1),
+            backend_id: warm_backend.clone(),
+            state: BackendState::default().to_ready(BackendAddr("127.0.0.1:8080".parse().unwrap())),
+            timestamp: LoggableTime(Utc::now()),
+        }))
+        .unwrap();
+
+    loop {
+        let warm_pools = client.list_warm_pools().await.unwrap();
+        assert_eq!(warm_pools.len(), 1);
+        if warm_pools[0].ready == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    tracing::info!("Connecting with a restart policy, which warm backends don't have.");
+    let response = client
+        .connect(&ConnectRequest {
+            key: Some(KeyConfig {
+                name: "restart-key".to_string(),
+                ..Default::default()
+            }),
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: env.pool.clone(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable: serde_json::json!({ "image": "alpine" }),
+                lifetime_limit_seconds: None,
+                max_idle_seconds: Some(60),
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::OnFailure {
+                    max_attempts: 3,
+                    backoff_seconds: 1,
+                },
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(response.spawned);
+    assert_ne!(response.backend_id, warm_backend);
+    assert_ne!(response.status, BackendStatus::Ready);
+
+    let (spawned, action) = next_action(&mut drone_connection).await;
+    assert!(matches!(action, BackendAction::Spawn { .. }));
+    assert_eq!(spawned, response.backend_id);
+
+    tracing::info!("Connecting with a spawn config that matches the warm pool.");
+    let response = client
+        .connect(&ConnectRequest {
+            key: Some(KeyConfig {
+                name: "warm-key".to_string(),
+                ..Default::default()
+            }),
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: env.pool.clone(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                // Omitted fields are filled in with their defaults before matching.
+                executable: serde_json::json!({ "image": "alpine" }),
+                lifetime_limit_seconds: None,
+                max_idle_seconds: Some(60),
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(response.spawned);
+    assert_eq!(response.backend_id, warm_backend);
+    assert_eq!(response.status, BackendStatus::Ready);
+    assert!(response.secret_token.is_some());
+
+    tracing::info!("Waiting for the warm pool to be replenished.");
+    let (replacement, action) = next_action(&mut drone_connection).await;
+    assert!(matches!(action, BackendAction::Spawn { .. }));
+    assert_ne!(replacement, warm_backend);
+
+    let deleted = client.delete_warm_pool(&warm_pool.id).await.unwrap();
+    assert!(deleted.deleted);
+
+    tracing::info!("Waiting for the unclaimed backend to be terminated.");
+    let (terminated, action) = next_action(&mut drone_connection).await;
+    assert!(matches!(action, BackendAction::Terminate { .. }));
+    assert_eq!(terminated, replacement);
+
+    assert!(client.list_warm_pools().await.unwrap().is_empty());
+}
@@ -0,0 +1,245 @@
+use axum::{body::Bytes, http::HeaderMap, http::StatusCode};
+use chrono::Utc;
+use common::{test_env::TestEnvironment, timeout::WithTimeout};
+use plane::{controller::webhooks::WebhookConfig, database::webhook::DeliveryAttempt};
+use plane_common::{
+    log_types::LoggableTime,
+    names::{DroneName, Name},
+    protocol::{Heartbeat, MessageFromDrone},
+    types::{
+        BackendStateWebhook, BackendStatus, ConnectRequest, DockerExecutorConfig, DronePoolName,
+        IdleAction, KeyConfig, LabelSelector, RestartPolicy, SpawnConfig,
+    },
+};
+use plane_test_macro::plane_test;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::mpsc;
+use url::Url;
+
+mod common;
+
+struct WebhookRequest {
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+/// Starts a webhook receiver that fails the first request, and records every request it receives.
+async fn webhook_receiver() -> (Url, mpsc::UnboundedReceiver<WebhookRequest>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let request_count = Arc::new(AtomicUsize::new(0));
+
+    let app = axum::Router::new().route(
+        "/webhook",
+        axum::routing::post(move |headers: HeaderMap, body: Bytes| async move {
+            sender.send(WebhookRequest { headers, body }).unwrap();
+            if request_count.fetch_add(1, Ordering::SeqCst) == 0 {
+                StatusCode::INTERNAL_SERVER_ERROR
+            } else {
+                StatusCode::OK
+            }
+        }),
+    );
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!(
+        "http://127.0.0.1:{}/webhook",
+        listener.local_addr().unwrap().port()
+    ))
+    .unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (url, receiver)
+}
+
+/// Tests that backend state changes are delivered to webhooks, and retried on failure.
+#[plane_test]
+async fn webhook_delivery(env: TestEnvironment) {
+    let (url, mut requests) = webhook_receiver().await;
+    let controller = env
+        .controller_with_webhook(WebhookConfig {
+            urls: vec![url],
+            secret: Some("webhook-secret".to_string()),
+        })
+        .await;
+    let client = controller.client();
+
+    let drone_id = DroneName::new_random();
+    let mut drone_connection = client
+        .drone_connection(&env.cluster, &env.pool)
+        .connect(&drone_id)
+        .await
+        .unwrap();
+
+    drone_connection
+        .send(MessageFromDrone::Heartbeat(Heartbeat {
+            local_time: LoggableTime(Utc::now()),
+            resources: None,
+        }))
+        .unwrap();
+
+    // Wait for the drone to be registered.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+
+    let executable =
+        serde_json::to_value(DockerExecutorConfig::from_image_with_defaults("alpine")).unwrap();
+    let key = KeyConfig::new_random();
+    let response = client
+        .connect(&ConnectRequest {
+            spawn_config: Some(SpawnConfig {
+                id: None,
+                cluster: Some(env.cluster.clone()),
+                pool: DronePoolName::default(),
+                fallback_pools: Vec::new(),
+                label_selector: LabelSelector::default(),
+                executable,
+                lifetime_limit_seconds: None,
+                max_idle_seconds: None,
+                idle_action: IdleAction::Terminate,
+                use_static_token: false,
+                subdomain: None,
+                queue_timeout_seconds: None,
+                restart_policy: RestartPolicy::Never,
+            }),
+            key: Some(key.clone()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let first = requests.recv().with_timeout(10).await.unwrap().unwrap();
+    let retry = requests.recv().with_timeout(10).await.unwrap().unwrap();
+
+    assert_eq!(first.body, retry.body);
+    assert_eq!(
+        first.headers.get("x-plane-delivery-id"),
+        retry.headers.get("x-plane-delivery-id")
+    );
+    assert!(first
+        .headers
+        .get("x-plane-signature")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("sha256="));
+
+    let payload: BackendStateWebhook = serde_json::from_slice(&retry.body).unwrap();
+    assert_eq!(payload.backend, response.backend_id);
+    assert_eq!(payload.cluster, env.cluster);
+    assert_eq!(payload.key, Some(key));
+    assert_eq!(payload.previous_status, None);
+    assert_eq!(payload.status, BackendStatus::Scheduled);
+    assert_eq!(payload.termination_reason, None);
+    assert_eq!(payload.exit_code, None);
+
+    drone_connection.close().await;
+}
+
+/// Tests that a delivery left pending by a controller that stopped is resumed by another
+/// controller.
+#[plane_test]
+async fn webhook_delivery_resumed(env: TestEnvironment) {
+    let (url, mut requests) = webhook_receiver().await;
+    let db = env.db().await;
+
+    // A delivery whose controller stopped after an attempt failed, so that no controller
+    // holds a claim on it.
+    let payload = r#"{"status":"scheduled"}"#;
+    let delivery_id: i32 = sqlx::query_scalar(
+        r#"
+        insert into webhook_delivery (event_id, url, payload, attempts)
+        values (0, $1, $2::jsonb, 1)
+        returning id
+        "#,
+    )
+    .bind(url.as_str())
+    .bind(payload)
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+
+    let _controller = env
+        .controller_with_webhook(WebhookConfig {
+            urls: vec![url],
+            secret: None,
+        })
+        .await;
+
+    let first = requests.recv().with_timeout(10).await.unwrap().unwrap();
+    let retry = requests.recv().with_timeout(10).await.unwrap().unwrap();
+
+    for request in [&first, &retry] {
+        assert_eq!(
+            request.headers.get("x-plane-delivery-id").unwrap(),
+            &delivery_id.to_string()
+        );
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&request.body).unwrap(),
+            serde_json::from_str::<serde_json::Value>(payload).unwrap()
+        );
+    }
+}
+
+/// Tests that only the current holder of a claim on a delivery can renew it or record attempts,
+/// and that attempts aren't recorded once the delivery has finished.
+#[plane_test]
+async fn webhook_delivery_claim_ownership(env: TestEnvironment) {
+    let db = env.db().await;
+    let url = "http://127.0.0.1:1/webhook".to_string();
+
+    let stale_claim = db
+        .webhook()
+        .create_delivery(0, &url, &serde_json::json!({ "status": "scheduled" }))
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Let the claim lapse, so that another controller takes over the delivery.
+    sqlx::query("update webhook_delivery set claimed_until = now() - interval '1 second'")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let pending = db
+        .webhook()
+        .claim_pending_deliveries(&[url], 10)
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    let claim = pending[0].claim.clone();
+    assert_eq!(claim.delivery_id, stale_claim.delivery_id);
+
+    let failed = DeliveryAttempt::Failed {
+        status_code: None,
+        error: "Connection refused.".to_string(),
+    };
+    assert!(!db.webhook().renew_claim(&stale_claim).await.unwrap());
+    assert!(!db
+        .webhook()
+        .record_attempt(&stale_claim, &failed)
+        .await
+        .unwrap());
+
+    assert!(db.webhook().renew_claim(&claim).await.unwrap());
+    let delivered = DeliveryAttempt::Delivered { status_code: 200 };
+    assert!(db
+        .webhook()
+        .record_attempt(&claim, &delivered)
+        .await
+        .unwrap());
+
+    // The delivery has finished, so its claim can no longer be used.
+    assert!(!db.webhook().renew_claim(&claim).await.unwrap());
+    assert!(!db.webhook().record_attempt(&claim, &failed).await.unwrap());
+
+    let attempts: i32 = sqlx::query_scalar("select attempts from webhook_delivery where id = $1")
+        .bind(claim.delivery_id)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(attempts, 1);
+}
@@ -91,6 +91,87 @@ COMMENT ON COLUMN public.acme_txt_entries.leased_by IS 'The proxy that last leas
 COMMENT ON COLUMN public.acme_txt_entries.txt_value IS 'The TXT value of the entry.';
 
 
+--
+-- Name: api_key; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE public.api_key (
+    id character varying(255) NOT NULL,
+    secret_hash character varying(255) NOT NULL,
+    scopes character varying(255)[] NOT NULL,
+    cluster character varying(255),
+    key_namespace character varying(255),
+    description text,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    revoked_at timestamp with time zone
+);
+
+
+ALTER TABLE public.api_key OWNER TO postgres;
+
+--
+-- Name: TABLE api_key; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON TABLE public.api_key IS 'API keys used to authenticate requests to the controller''s /ctrl routes.';
+
+
+--
+-- Name: COLUMN api_key.id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.id IS 'The public identifier of the key, which is also the first part of the key itself.';
+
+
+--
+-- Name: COLUMN api_key.secret_hash; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.secret_hash IS 'Hex-encoded SHA-256 hash of the secret part of the key. The secret itself is not stored.';
+
+
+--
+-- Name: COLUMN api_key.scopes; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.scopes IS 'The operations the key may perform.';
+
+
+--
+-- Name: COLUMN api_key.cluster; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.cluster IS 'If set, the key may only act on this cluster.';
+
+
+--
+-- Name: COLUMN api_key.key_namespace; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.key_namespace IS 'If set, the key may only act on backends whose key is in this namespace.';
+
+
+--
+-- Name: COLUMN api_key.description; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.description IS 'A human-readable description of what the key is used for.';
+
+
+--
+-- Name: COLUMN api_key.created_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.created_at IS 'The time the key was minted.';
+
+
+--
+-- Name: COLUMN api_key.revoked_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.api_key.revoked_at IS 'The time the key was revoked, if it was. Revoked keys are rejected.';
+
+
 --
 -- Name: backend; Type: TABLE; Schema: public; Owner: postgres
 --
@@ -101,14 +182,20 @@ CREATE TABLE public.backend (
     last_status character varying(255) NOT NULL,
     last_status_time timestamp with time zone NOT NULL,
     cluster_address character varying(255),
-    drone_id integer NOT NULL,
+    drone_id integer,
     expiration_time timestamp with time zone,
     last_keepalive timestamp with time zone NOT NULL,
     allowed_idle_seconds integer,
     state jsonb NOT NULL,
     static_token character varying(256),
     subdomain character varying(255),
-    last_status_number integer
+    last_status_number integer,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    key_namespace character varying(255),
+    memory_limit_bytes bigint,
+    cpu_limit_millicores bigint,
+    warm_pool_id character varying(255),
+    suspend_when_idle boolean DEFAULT false NOT NULL
 );
 
 
@@ -153,7 +240,7 @@ COMMENT ON COLUMN public.backend.cluster_address IS 'The address (IP:PORT) of th
 -- Name: COLUMN backend.drone_id; Type: COMMENT; Schema: public; Owner: postgres
 --
 
-COMMENT ON COLUMN public.backend.drone_id IS 'The drone the backend is assigned to.';
+COMMENT ON COLUMN public.backend.drone_id IS 'The drone the backend is assigned to. Null while the backend is queued.';
 
 
 --
@@ -198,6 +285,48 @@ COMMENT ON COLUMN public.backend.subdomain IS 'Optional subdomain for session ba
 COMMENT ON COLUMN public.backend.last_status_number IS 'Number representation of last_status, used for ordering.';
 
 
+--
+-- Name: COLUMN backend.created_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend.created_at IS 'The time the backend was scheduled.';
+
+
+--
+-- Name: COLUMN backend.key_namespace; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend.key_namespace IS 'The namespace of the key the backend was created with. Unlike backend_key, this is retained after the backend terminates.';
+
+
+--
+-- Name: COLUMN backend.memory_limit_bytes; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend.memory_limit_bytes IS 'The memory limit requested by the backend''s spawn config, if any.';
+
+
+--
+-- Name: COLUMN backend.cpu_limit_millicores; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend.cpu_limit_millicores IS 'The CPU limit requested by the backend''s spawn config, in thousandths of a core, if any.';
+
+
+--
+-- Name: COLUMN backend.warm_pool_id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend.warm_pool_id IS 'The warm pool the backend was spawned for, if it has not yet been claimed by a connect request. Null for claimed backends and backends spawned by a connect request.';
+
+
+--
+-- Name: COLUMN backend.suspend_when_idle; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend.suspend_when_idle IS 'If true, the backend is suspended instead of terminated once it has been idle for allowed_idle_seconds, and resumed by the next request for it.';
+
+
 --
 -- Name: backend_action; Type: TABLE; Schema: public; Owner: postgres
 --
@@ -329,6 +458,141 @@ COMMENT ON COLUMN public.backend_key.fencing_token IS 'A number that monotonical
 COMMENT ON COLUMN public.backend_key.allow_renew IS 'If false, the key cannot be renewed for this backend, forcing the backend to be terminated.';
 
 
+--
+-- Name: backend_log; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE public.backend_log (
+    id bigint NOT NULL,
+    backend_id character varying(255) NOT NULL,
+    stream character varying(255) NOT NULL,
+    logged_at timestamp with time zone NOT NULL,
+    line text NOT NULL
+);
+
+
+ALTER TABLE public.backend_log OWNER TO postgres;
+
+--
+-- Name: TABLE backend_log; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON TABLE public.backend_log IS 'The most recent lines written by each backend''s process, kept so that they can be read after the backend exits.';
+
+
+--
+-- Name: COLUMN backend_log.id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_log.id IS 'Unique and increasing id of the line, used to order lines and to resume log streams.';
+
+
+--
+-- Name: COLUMN backend_log.backend_id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_log.backend_id IS 'The backend that wrote the line.';
+
+
+--
+-- Name: COLUMN backend_log.stream; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_log.stream IS 'The output stream the line was written to (stdout or stderr).';
+
+
+--
+-- Name: COLUMN backend_log.logged_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_log.logged_at IS 'The time the line was written, according to the drone.';
+
+
+--
+-- Name: COLUMN backend_log.line; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_log.line IS 'The line, without its trailing newline.';
+
+
+--
+-- Name: backend_log_id_seq; Type: SEQUENCE; Schema: public; Owner: postgres
+--
+
+CREATE SEQUENCE public.backend_log_id_seq
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER TABLE public.backend_log_id_seq OWNER TO postgres;
+
+--
+-- Name: backend_log_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: postgres
+--
+
+ALTER SEQUENCE public.backend_log_id_seq OWNED BY public.backend_log.id;
+
+
+--
+-- Name: backend_queue; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE public.backend_queue (
+    backend_id character varying(255) NOT NULL,
+    cluster character varying(255) NOT NULL,
+    spawn_config jsonb NOT NULL,
+    expires_at timestamp with time zone NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.backend_queue OWNER TO postgres;
+
+--
+-- Name: TABLE backend_queue; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON TABLE public.backend_queue IS 'Backends that are waiting for a drone to become available, in the order they were requested.';
+
+
+--
+-- Name: COLUMN backend_queue.backend_id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_queue.backend_id IS 'The queued backend.';
+
+
+--
+-- Name: COLUMN backend_queue.cluster; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_queue.cluster IS 'The cluster the backend will be scheduled in.';
+
+
+--
+-- Name: COLUMN backend_queue.spawn_config; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_queue.spawn_config IS 'The spawn config of the connect request, used to place the backend once a drone is available.';
+
+
+--
+-- Name: COLUMN backend_queue.expires_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_queue.expires_at IS 'The time after which the backend is terminated if it has not been scheduled to a drone.';
+
+
+--
+-- Name: COLUMN backend_queue.created_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.backend_queue.created_at IS 'The time the backend was queued.';
+
+
 --
 -- Name: backend_state; Type: TABLE; Schema: public; Owner: postgres
 --
@@ -469,7 +733,10 @@ CREATE TABLE public.drone (
     draining boolean DEFAULT false NOT NULL,
     last_heartbeat timestamp with time zone,
     last_local_time timestamp with time zone,
-    pool character varying(255) DEFAULT ''::character varying NOT NULL
+    pool character varying(255) DEFAULT ''::character varying NOT NULL,
+    resources jsonb,
+    labels jsonb DEFAULT '{}'::jsonb NOT NULL,
+    max_backends integer
 );
 
 
@@ -524,6 +791,27 @@ COMMENT ON COLUMN public.drone.last_local_time IS 'The last reported local times
 COMMENT ON COLUMN public.drone.pool IS 'The pool to which the drone is assigned (default pool is an empty string).';
 
 
+--
+-- Name: COLUMN drone.resources; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.drone.resources IS 'The total, allocatable, and used memory and CPU last reported by the drone. Null if the drone does not report its resources.';
+
+
+--
+-- Name: COLUMN drone.labels; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.drone.labels IS 'Key/value labels advertised by the drone when it connects, which spawn requests can select drones by.';
+
+
+--
+-- Name: COLUMN drone.max_backends; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.drone.max_backends IS 'The maximum number of non-terminated backends the drone can run at once, as reported by the drone when it connects. Null if the drone has no limit.';
+
+
 --
 -- Name: drone_id_seq; Type: SEQUENCE; Schema: public; Owner: postgres
 --
@@ -618,6 +906,63 @@ ALTER SEQUENCE public.event_id_seq OWNER TO postgres;
 ALTER SEQUENCE public.event_id_seq OWNED BY public.event.id;
 
 
+--
+-- Name: key_spawn_config; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE public.key_spawn_config (
+    namespace character varying(255) NOT NULL,
+    key_name character varying(255) NOT NULL,
+    tag character varying(255) NOT NULL,
+    spawn_config jsonb NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.key_spawn_config OWNER TO postgres;
+
+--
+-- Name: TABLE key_spawn_config; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON TABLE public.key_spawn_config IS 'The spawn config most recently used to spawn a backend for each key, used to respawn a backend for the key when a connect request gives no spawn config.';
+
+
+--
+-- Name: COLUMN key_spawn_config.namespace; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.key_spawn_config.namespace IS 'The namespace of the key.';
+
+
+--
+-- Name: COLUMN key_spawn_config.key_name; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.key_spawn_config.key_name IS 'The name of the key.';
+
+
+--
+-- Name: COLUMN key_spawn_config.tag; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.key_spawn_config.tag IS 'The tag of the key when the backend was spawned. Only connect requests with the same tag respawn from this config.';
+
+
+--
+-- Name: COLUMN key_spawn_config.spawn_config; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.key_spawn_config.spawn_config IS 'The spawn config, with its cluster filled in.';
+
+
+--
+-- Name: COLUMN key_spawn_config.updated_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.key_spawn_config.updated_at IS 'The time the spawn config was last used to spawn a backend. Configs that have not been used for the cleanup period are deleted.';
+
+
 --
 -- Name: node; Type: TABLE; Schema: public; Owner: postgres
 --
@@ -723,44 +1068,109 @@ ALTER SEQUENCE public.node_id_seq OWNED BY public.node.id;
 
 
 --
--- Name: token; Type: TABLE; Schema: public; Owner: postgres
+-- Name: node_join_token; Type: TABLE; Schema: public; Owner: postgres
 --
 
-CREATE TABLE public.token (
-    token character varying(255) NOT NULL,
-    backend_id character varying(255) NOT NULL,
-    expiration_time timestamp with time zone NOT NULL,
-    username character varying(255),
-    auth jsonb NOT NULL,
-    secret_token character varying(255) NOT NULL
+CREATE TABLE public.node_join_token (
+    id character varying(255) NOT NULL,
+    secret_hash character varying(255) NOT NULL,
+    cluster character varying(255),
+    description text,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    revoked_at timestamp with time zone
 );
 
 
-ALTER TABLE public.token OWNER TO postgres;
+ALTER TABLE public.node_join_token OWNER TO postgres;
 
 --
--- Name: TABLE token; Type: COMMENT; Schema: public; Owner: postgres
+-- Name: TABLE node_join_token; Type: COMMENT; Schema: public; Owner: postgres
 --
 
-COMMENT ON TABLE public.token IS 'Information about tokens used to make authorized connections to backends.';
+COMMENT ON TABLE public.node_join_token IS 'Join tokens that drones, proxies, and DNS servers present when connecting to the controller.';
 
 
 --
--- Name: COLUMN token.token; Type: COMMENT; Schema: public; Owner: postgres
+-- Name: COLUMN node_join_token.id; Type: COMMENT; Schema: public; Owner: postgres
 --
 
-COMMENT ON COLUMN public.token.token IS 'The token used to make the connection.';
+COMMENT ON COLUMN public.node_join_token.id IS 'The public identifier of the token, which is also the first part of the token itself.';
 
 
 --
--- Name: COLUMN token.backend_id; Type: COMMENT; Schema: public; Owner: postgres
+-- Name: COLUMN node_join_token.secret_hash; Type: COMMENT; Schema: public; Owner: postgres
 --
 
-COMMENT ON COLUMN public.token.backend_id IS 'The backend the token is associated with.';
+COMMENT ON COLUMN public.node_join_token.secret_hash IS 'Hex-encoded SHA-256 hash of the secret part of the token. The secret itself is not stored.';
 
 
 --
--- Name: COLUMN token.expiration_time; Type: COMMENT; Schema: public; Owner: postgres
+-- Name: COLUMN node_join_token.cluster; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.node_join_token.cluster IS 'If set, the token may only be used by drones and proxies in this cluster. Otherwise, it may be used by any node.';
+
+
+--
+-- Name: COLUMN node_join_token.description; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.node_join_token.description IS 'A human-readable description of what the token is used for.';
+
+
+--
+-- Name: COLUMN node_join_token.created_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.node_join_token.created_at IS 'The time the token was minted.';
+
+
+--
+-- Name: COLUMN node_join_token.revoked_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.node_join_token.revoked_at IS 'The time the token was revoked, if it was. Revoked tokens are rejected.';
+
+
+--
+-- Name: token; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE public.token (
+    token character varying(255) NOT NULL,
+    backend_id character varying(255) NOT NULL,
+    expiration_time timestamp with time zone NOT NULL,
+    username character varying(255),
+    auth jsonb NOT NULL,
+    secret_token character varying(255) NOT NULL
+);
+
+
+ALTER TABLE public.token OWNER TO postgres;
+
+--
+-- Name: TABLE token; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON TABLE public.token IS 'Information about tokens used to make authorized connections to backends.';
+
+
+--
+-- Name: COLUMN token.token; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.token.token IS 'The token used to make the connection.';
+
+
+--
+-- Name: COLUMN token.backend_id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.token.backend_id IS 'The backend the token is associated with.';
+
+
+--
+-- Name: COLUMN token.expiration_time; Type: COMMENT; Schema: public; Owner: postgres
 --
 
 COMMENT ON COLUMN public.token.expiration_time IS 'The time the token expires.';
@@ -787,6 +1197,221 @@ COMMENT ON COLUMN public.token.auth IS 'A JSON representation of arbitrary auth
 COMMENT ON COLUMN public.token.secret_token IS 'A secret token optionally used for secondary authentication.';
 
 
+--
+-- Name: warm_pool; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE public.warm_pool (
+    id character varying(255) NOT NULL,
+    cluster character varying(255) NOT NULL,
+    pool character varying(255) DEFAULT ''::character varying NOT NULL,
+    executable jsonb NOT NULL,
+    size integer NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+
+ALTER TABLE public.warm_pool OWNER TO postgres;
+
+--
+-- Name: TABLE warm_pool; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON TABLE public.warm_pool IS 'Pools of backends that are spawned ahead of time, so that connect requests can claim a backend that is already ready.';
+
+
+--
+-- Name: COLUMN warm_pool.id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.warm_pool.id IS 'The unique identifier of the warm pool.';
+
+
+--
+-- Name: COLUMN warm_pool.cluster; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.warm_pool.cluster IS 'The cluster the warm backends are spawned in.';
+
+
+--
+-- Name: COLUMN warm_pool.pool; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.warm_pool.pool IS 'The drone pool the warm backends are spawned in.';
+
+
+--
+-- Name: COLUMN warm_pool.executable; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.warm_pool.executable IS 'The executable config of the warm backends. Connect requests with the same executable config may claim a warm backend.';
+
+
+--
+-- Name: COLUMN warm_pool.size; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.warm_pool.size IS 'The number of unclaimed backends to keep in the pool.';
+
+
+--
+-- Name: COLUMN warm_pool.created_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.warm_pool.created_at IS 'The time the warm pool was created.';
+
+
+--
+-- Name: webhook_delivery; Type: TABLE; Schema: public; Owner: postgres
+--
+
+CREATE TABLE public.webhook_delivery (
+    id integer NOT NULL,
+    event_id integer NOT NULL,
+    url text NOT NULL,
+    payload jsonb NOT NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    attempts integer DEFAULT 0 NOT NULL,
+    last_attempt_at timestamp with time zone,
+    last_status_code integer,
+    last_error text,
+    delivered_at timestamp with time zone,
+    failed_at timestamp with time zone,
+    claimed_until timestamp with time zone,
+    claim_token character varying(255)
+);
+
+
+ALTER TABLE public.webhook_delivery OWNER TO postgres;
+
+--
+-- Name: TABLE webhook_delivery; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON TABLE public.webhook_delivery IS 'Deliveries of backend state change webhooks to configured URLs.';
+
+
+--
+-- Name: COLUMN webhook_delivery.id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.id IS 'A unique identifier for the delivery, sent to the receiver so it can de-duplicate retries.';
+
+
+--
+-- Name: COLUMN webhook_delivery.event_id; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.event_id IS 'The id of the event that triggered the delivery. Not a foreign key, since events are cleaned up independently.';
+
+
+--
+-- Name: COLUMN webhook_delivery.url; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.url IS 'The URL the webhook is delivered to.';
+
+
+--
+-- Name: COLUMN webhook_delivery.payload; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.payload IS 'The JSON payload of the webhook.';
+
+
+--
+-- Name: COLUMN webhook_delivery.created_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.created_at IS 'The time the delivery was created.';
+
+
+--
+-- Name: COLUMN webhook_delivery.attempts; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.attempts IS 'The number of delivery attempts made so far.';
+
+
+--
+-- Name: COLUMN webhook_delivery.last_attempt_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.last_attempt_at IS 'The time of the most recent delivery attempt.';
+
+
+--
+-- Name: COLUMN webhook_delivery.last_status_code; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.last_status_code IS 'The HTTP status code returned by the most recent delivery attempt, if a response was received.';
+
+
+--
+-- Name: COLUMN webhook_delivery.last_error; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.last_error IS 'A description of the error of the most recent delivery attempt, if it failed.';
+
+
+--
+-- Name: COLUMN webhook_delivery.delivered_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.delivered_at IS 'The time the webhook was successfully delivered, if it was.';
+
+
+--
+-- Name: COLUMN webhook_delivery.failed_at; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.failed_at IS 'The time delivery was abandoned after exhausting retries, if it was.';
+
+
+--
+-- Name: COLUMN webhook_delivery.claimed_until; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.claimed_until IS 'The time until which a controller is responsible for delivering the webhook. Pending deliveries whose claim has lapsed are retried by another controller.';
+
+
+--
+-- Name: COLUMN webhook_delivery.claim_token; Type: COMMENT; Schema: public; Owner: postgres
+--
+
+COMMENT ON COLUMN public.webhook_delivery.claim_token IS 'A random token identifying the current claim on the delivery. Only the holder of the claim may renew it or record attempts.';
+
+
+--
+-- Name: webhook_delivery_id_seq; Type: SEQUENCE; Schema: public; Owner: postgres
+--
+
+CREATE SEQUENCE public.webhook_delivery_id_seq
+    AS integer
+    START WITH 1
+    INCREMENT BY 1
+    NO MINVALUE
+    NO MAXVALUE
+    CACHE 1;
+
+
+ALTER TABLE public.webhook_delivery_id_seq OWNER TO postgres;
+
+--
+-- Name: webhook_delivery_id_seq; Type: SEQUENCE OWNED BY; Schema: public; Owner: postgres
+--
+
+ALTER SEQUENCE public.webhook_delivery_id_seq OWNED BY public.webhook_delivery.id;
+
+
+--
+-- Name: backend_log id; Type: DEFAULT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.backend_log ALTER COLUMN id SET DEFAULT nextval('public.backend_log_id_seq'::regclass);
+
+
 --
 -- Name: backend_state id; Type: DEFAULT; Schema: public; Owner: postgres
 --
@@ -815,6 +1440,13 @@ ALTER TABLE ONLY public.event ALTER COLUMN id SET DEFAULT nextval('public.event_
 ALTER TABLE ONLY public.node ALTER COLUMN id SET DEFAULT nextval('public.node_id_seq'::regclass);
 
 
+--
+-- Name: webhook_delivery id; Type: DEFAULT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.webhook_delivery ALTER COLUMN id SET DEFAULT nextval('public.webhook_delivery_id_seq'::regclass);
+
+
 --
 -- Name: _sqlx_migrations _sqlx_migrations_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -831,6 +1463,14 @@ ALTER TABLE ONLY public.acme_txt_entries
     ADD CONSTRAINT acme_txt_entries_pkey PRIMARY KEY (cluster);
 
 
+--
+-- Name: api_key api_key_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.api_key
+    ADD CONSTRAINT api_key_pkey PRIMARY KEY (id);
+
+
 --
 -- Name: backend_action backend_action_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -847,6 +1487,14 @@ ALTER TABLE ONLY public.backend_key
     ADD CONSTRAINT backend_key_pkey PRIMARY KEY (id);
 
 
+--
+-- Name: backend_log backend_log_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.backend_log
+    ADD CONSTRAINT backend_log_pkey PRIMARY KEY (id);
+
+
 --
 -- Name: backend backend_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -855,6 +1503,14 @@ ALTER TABLE ONLY public.backend
     ADD CONSTRAINT backend_pkey PRIMARY KEY (id);
 
 
+--
+-- Name: backend_queue backend_queue_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.backend_queue
+    ADD CONSTRAINT backend_queue_pkey PRIMARY KEY (backend_id);
+
+
 --
 -- Name: backend_state backend_state_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -887,6 +1543,14 @@ ALTER TABLE ONLY public.event
     ADD CONSTRAINT event_pkey PRIMARY KEY (id);
 
 
+--
+-- Name: key_spawn_config key_spawn_config_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.key_spawn_config
+    ADD CONSTRAINT key_spawn_config_pkey PRIMARY KEY (namespace, key_name);
+
+
 --
 -- Name: node node_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -895,6 +1559,14 @@ ALTER TABLE ONLY public.node
     ADD CONSTRAINT node_pkey PRIMARY KEY (id);
 
 
+--
+-- Name: node_join_token node_join_token_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.node_join_token
+    ADD CONSTRAINT node_join_token_pkey PRIMARY KEY (id);
+
+
 --
 -- Name: token token_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -903,6 +1575,22 @@ ALTER TABLE ONLY public.token
     ADD CONSTRAINT token_pkey PRIMARY KEY (token);
 
 
+--
+-- Name: warm_pool warm_pool_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.warm_pool
+    ADD CONSTRAINT warm_pool_pkey PRIMARY KEY (id);
+
+
+--
+-- Name: webhook_delivery webhook_delivery_pkey; Type: CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.webhook_delivery
+    ADD CONSTRAINT webhook_delivery_pkey PRIMARY KEY (id);
+
+
 --
 -- Name: idx_backend_action_backend; Type: INDEX; Schema: public; Owner: postgres
 --
@@ -924,6 +1612,13 @@ CREATE INDEX idx_backend_action_created_at ON public.backend_action USING btree
 CREATE INDEX idx_backend_action_pending ON public.backend_action USING btree (drone_id, created_at) WHERE (acked_at IS NULL);
 
 
+--
+-- Name: idx_backend_created_at; Type: INDEX; Schema: public; Owner: postgres
+--
+
+CREATE INDEX idx_backend_created_at ON public.backend USING btree (created_at, id);
+
+
 --
 -- Name: idx_backend_drone_id; Type: INDEX; Schema: public; Owner: postgres
 --
@@ -931,6 +1626,20 @@ CREATE INDEX idx_backend_action_pending ON public.backend_action USING btree (dr
 CREATE INDEX idx_backend_drone_id ON public.backend USING btree (drone_id) WHERE ((last_status)::text <> 'terminated'::text);
 
 
+--
+-- Name: idx_backend_log_backend_id; Type: INDEX; Schema: public; Owner: postgres
+--
+
+CREATE INDEX idx_backend_log_backend_id ON public.backend_log USING btree (backend_id, id);
+
+
+--
+-- Name: idx_backend_queue_created_at; Type: INDEX; Schema: public; Owner: postgres
+--
+
+CREATE INDEX idx_backend_queue_created_at ON public.backend_queue USING btree (created_at);
+
+
 --
 -- Name: idx_backend_state_created_at; Type: INDEX; Schema: public; Owner: postgres
 --
@@ -945,6 +1654,13 @@ CREATE INDEX idx_backend_state_created_at ON public.backend_state USING btree (b
 CREATE INDEX idx_backend_static_token ON public.backend USING btree (static_token);
 
 
+--
+-- Name: idx_backend_warm_pool; Type: INDEX; Schema: public; Owner: postgres
+--
+
+CREATE INDEX idx_backend_warm_pool ON public.backend USING btree (warm_pool_id) WHERE (warm_pool_id IS NOT NULL);
+
+
 --
 -- Name: idx_cluster_name; Type: INDEX; Schema: public; Owner: postgres
 --
@@ -973,6 +1689,27 @@ CREATE INDEX idx_event_created_at ON public.event USING btree (created_at);
 CREATE UNIQUE INDEX idx_namespace_name ON public.backend_key USING btree (namespace, key_name);
 
 
+--
+-- Name: idx_webhook_delivery_created_at; Type: INDEX; Schema: public; Owner: postgres
+--
+
+CREATE INDEX idx_webhook_delivery_created_at ON public.webhook_delivery USING btree (created_at);
+
+
+--
+-- Name: idx_webhook_delivery_event_url; Type: INDEX; Schema: public; Owner: postgres
+--
+
+CREATE UNIQUE INDEX idx_webhook_delivery_event_url ON public.webhook_delivery USING btree (event_id, url);
+
+
+--
+-- Name: idx_webhook_delivery_pending; Type: INDEX; Schema: public; Owner: postgres
+--
+
+CREATE INDEX idx_webhook_delivery_pending ON public.webhook_delivery USING btree (id) WHERE ((delivered_at IS NULL) AND (failed_at IS NULL));
+
+
 --
 -- Name: acme_txt_entries acme_txt_entries_leased_by_fkey; Type: FK CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -1013,6 +1750,22 @@ ALTER TABLE ONLY public.backend_key
     ADD CONSTRAINT backend_key_id_fkey FOREIGN KEY (id) REFERENCES public.backend(id);
 
 
+--
+-- Name: backend_log backend_log_backend_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.backend_log
+    ADD CONSTRAINT backend_log_backend_id_fkey FOREIGN KEY (backend_id) REFERENCES public.backend(id) ON DELETE CASCADE;
+
+
+--
+-- Name: backend_queue backend_queue_backend_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.backend_queue
+    ADD CONSTRAINT backend_queue_backend_id_fkey FOREIGN KEY (backend_id) REFERENCES public.backend(id) ON DELETE CASCADE;
+
+
 --
 -- Name: backend_state backend_state_backend_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: postgres
 --
@@ -1021,6 +1774,14 @@ ALTER TABLE ONLY public.backend_state
     ADD CONSTRAINT backend_state_backend_id_fkey FOREIGN KEY (backend_id) REFERENCES public.backend(id);
 
 
+--
+-- Name: backend backend_warm_pool_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: postgres
+--
+
+ALTER TABLE ONLY public.backend
+    ADD CONSTRAINT backend_warm_pool_id_fkey FOREIGN KEY (warm_pool_id) REFERENCES public.warm_pool(id) ON DELETE SET NULL;
+
+
 --
 -- Name: drone drone_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: postgres
 --
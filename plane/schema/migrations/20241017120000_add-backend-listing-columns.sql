@@ -0,0 +1,21 @@
+-- Columns needed to filter and paginate the backend listing API.
+
+alter table backend add column created_at timestamptz not null default now();
+
+-- Backfill the creation time of existing backends from their first recorded state.
+update backend set created_at = coalesce(
+    (select min(created_at) from backend_state where backend_state.backend_id = backend.id),
+    backend.last_status_time
+);
+
+alter table backend add column key_namespace varchar(255);
+
+-- Backfill the key namespace for backends that still hold their key.
+update backend set key_namespace = backend_key.namespace
+from backend_key
+where backend_key.id = backend.id;
+
+comment on column backend.created_at is 'The time the backend was scheduled.';
+comment on column backend.key_namespace is 'The namespace of the key the backend was created with. Unlike backend_key, this is retained after the backend terminates.';
+
+create index idx_backend_created_at on backend(created_at, id);
@@ -0,0 +1,30 @@
+create table webhook_delivery (
+    id serial primary key,
+    event_id integer not null,
+    url text not null,
+    payload jsonb not null,
+    created_at timestamptz not null default now(),
+    attempts integer not null default 0,
+    last_attempt_at timestamptz,
+    last_status_code integer,
+    last_error text,
+    delivered_at timestamptz,
+    failed_at timestamptz
+);
+
+comment on table webhook_delivery is 'Deliveries of backend state change webhooks to configured URLs.';
+comment on column webhook_delivery.id is 'A unique identifier for the delivery, sent to the receiver so it can de-duplicate retries.';
+comment on column webhook_delivery.event_id is 'The id of the event that triggered the delivery. Not a foreign key, since events are cleaned up independently.';
+comment on column webhook_delivery.url is 'The URL the webhook is delivered to.';
+comment on column webhook_delivery.payload is 'The JSON payload of the webhook.';
+comment on column webhook_delivery.created_at is 'The time the delivery was created.';
+comment on column webhook_delivery.attempts is 'The number of delivery attempts made so far.';
+comment on column webhook_delivery.last_attempt_at is 'The time of the most recent delivery attempt.';
+comment on column webhook_delivery.last_status_code is 'The HTTP status code returned by the most recent delivery attempt, if a response was received.';
+comment on column webhook_delivery.last_error is 'A description of the error of the most recent delivery attempt, if it failed.';
+comment on column webhook_delivery.delivered_at is 'The time the webhook was successfully delivered, if it was.';
+comment on column webhook_delivery.failed_at is 'The time delivery was abandoned after exhausting retries, if it was.';
+
+-- Each event is delivered to each URL once, even when multiple controllers observe the event.
+create unique index idx_webhook_delivery_event_url on webhook_delivery(event_id, url);
+create index idx_webhook_delivery_created_at on webhook_delivery(created_at);
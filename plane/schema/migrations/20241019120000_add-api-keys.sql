@@ -0,0 +1,20 @@
+create table api_key (
+    id varchar(255) primary key,
+    secret_hash varchar(255) not null,
+    scopes varchar(255)[] not null,
+    cluster varchar(255),
+    key_namespace varchar(255),
+    description text,
+    created_at timestamptz not null default now(),
+    revoked_at timestamptz
+);
+
+comment on table api_key is 'API keys used to authenticate requests to the controller''s /ctrl routes.';
+comment on column api_key.id is 'The public identifier of the key, which is also the first part of the key itself.';
+comment on column api_key.secret_hash is 'Hex-encoded SHA-256 hash of the secret part of the key. The secret itself is not stored.';
+comment on column api_key.scopes is 'The operations the key may perform.';
+comment on column api_key.cluster is 'If set, the key may only act on this cluster.';
+comment on column api_key.key_namespace is 'If set, the key may only act on backends whose key is in this namespace.';
+comment on column api_key.description is 'A human-readable description of what the key is used for.';
+comment on column api_key.created_at is 'The time the key was minted.';
+comment on column api_key.revoked_at is 'The time the key was revoked, if it was. Revoked keys are rejected.';
@@ -0,0 +1,16 @@
+create table node_join_token (
+    id varchar(255) primary key,
+    secret_hash varchar(255) not null,
+    cluster varchar(255),
+    description text,
+    created_at timestamptz not null default now(),
+    revoked_at timestamptz
+);
+
+comment on table node_join_token is 'Join tokens that drones, proxies, and DNS servers present when connecting to the controller.';
+comment on column node_join_token.id is 'The public identifier of the token, which is also the first part of the token itself.';
+comment on column node_join_token.secret_hash is 'Hex-encoded SHA-256 hash of the secret part of the token. The secret itself is not stored.';
+comment on column node_join_token.cluster is 'If set, the token may only be used by drones and proxies in this cluster. Otherwise, it may be used by any node.';
+comment on column node_join_token.description is 'A human-readable description of what the token is used for.';
+comment on column node_join_token.created_at is 'The time the token was minted.';
+comment on column node_join_token.revoked_at is 'The time the token was revoked, if it was. Revoked tokens are rejected.';
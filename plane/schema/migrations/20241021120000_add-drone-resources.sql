@@ -0,0 +1,11 @@
+-- Resources reported by drones and requested by backends, used to place backends by available headroom.
+
+alter table drone add column resources jsonb;
+
+comment on column drone.resources is 'The total, allocatable, and used memory and CPU last reported by the drone. Null if the drone does not report its resources.';
+
+alter table backend add column memory_limit_bytes bigint;
+alter table backend add column cpu_limit_millicores bigint;
+
+comment on column backend.memory_limit_bytes is 'The memory limit requested by the backend''s spawn config, if any.';
+comment on column backend.cpu_limit_millicores is 'The CPU limit requested by the backend''s spawn config, in thousandths of a core, if any.';
@@ -0,0 +1,3 @@
+alter table drone add column labels jsonb not null default '{}'::jsonb;
+
+comment on column drone.labels is 'Key/value labels advertised by the drone when it connects, which spawn requests can select drones by.';
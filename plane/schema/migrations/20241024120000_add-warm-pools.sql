@@ -0,0 +1,22 @@
+create table warm_pool (
+    id varchar(255) primary key,
+    cluster varchar(255) not null,
+    pool varchar(255) not null default '',
+    executable jsonb not null,
+    size integer not null,
+    created_at timestamptz not null default now()
+);
+
+comment on table warm_pool is 'Pools of backends that are spawned ahead of time, so that connect requests can claim a backend that is already ready.';
+comment on column warm_pool.id is 'The unique identifier of the warm pool.';
+comment on column warm_pool.cluster is 'The cluster the warm backends are spawned in.';
+comment on column warm_pool.pool is 'The drone pool the warm backends are spawned in.';
+comment on column warm_pool.executable is 'The executable config of the warm backends. Connect requests with the same executable config may claim a warm backend.';
+comment on column warm_pool.size is 'The number of unclaimed backends to keep in the pool.';
+comment on column warm_pool.created_at is 'The time the warm pool was created.';
+
+alter table backend add column warm_pool_id varchar(255) references warm_pool(id) on delete set null;
+
+comment on column backend.warm_pool_id is 'The warm pool the backend was spawned for, if it has not yet been claimed by a connect request. Null for claimed backends and backends spawned by a connect request.';
+
+create index idx_backend_warm_pool on backend(warm_pool_id) where warm_pool_id is not null;
@@ -0,0 +1,20 @@
+alter table backend alter column drone_id drop not null;
+
+comment on column backend.drone_id is 'The drone the backend is assigned to. Null while the backend is queued.';
+
+create table backend_queue (
+    backend_id varchar(255) primary key references backend(id) on delete cascade,
+    cluster varchar(255) not null,
+    spawn_config jsonb not null,
+    expires_at timestamptz not null,
+    created_at timestamptz not null default now()
+);
+
+comment on table backend_queue is 'Backends that are waiting for a drone to become available, in the order they were requested.';
+comment on column backend_queue.backend_id is 'The queued backend.';
+comment on column backend_queue.cluster is 'The cluster the backend will be scheduled in.';
+comment on column backend_queue.spawn_config is 'The spawn config of the connect request, used to place the backend once a drone is available.';
+comment on column backend_queue.expires_at is 'The time after which the backend is terminated if it has not been scheduled to a drone.';
+comment on column backend_queue.created_at is 'The time the backend was queued.';
+
+create index idx_backend_queue_created_at on backend_queue(created_at);
@@ -0,0 +1,3 @@
+alter table backend add column suspend_when_idle boolean not null default false;
+
+comment on column backend.suspend_when_idle is 'If true, the backend is suspended instead of terminated once it has been idle for allowed_idle_seconds, and resumed by the next request for it.';
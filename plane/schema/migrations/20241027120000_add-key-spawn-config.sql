@@ -0,0 +1,15 @@
+create table key_spawn_config (
+    namespace varchar(255) not null,
+    key_name varchar(255) not null,
+    tag varchar(255) not null,
+    spawn_config jsonb not null,
+    updated_at timestamptz not null default now(),
+    primary key (namespace, key_name)
+);
+
+comment on table key_spawn_config is 'The spawn config most recently used to spawn a backend for each key, used to respawn a backend for the key when a connect request gives no spawn config.';
+comment on column key_spawn_config.namespace is 'The namespace of the key.';
+comment on column key_spawn_config.key_name is 'The name of the key.';
+comment on column key_spawn_config.tag is 'The tag of the key when the backend was spawned. Only connect requests with the same tag respawn from this config.';
+comment on column key_spawn_config.spawn_config is 'The spawn config, with its cluster filled in.';
+comment on column key_spawn_config.updated_at is 'The time the spawn config was last used to spawn a backend. Configs that have not been used for the cleanup period are deleted.';
@@ -0,0 +1,16 @@
+create table backend_log (
+    id bigserial primary key,
+    backend_id varchar(255) not null references backend(id) on delete cascade,
+    stream varchar(255) not null,
+    logged_at timestamptz not null,
+    line text not null
+);
+
+comment on table backend_log is 'The most recent lines written by each backend''s process, kept so that they can be read after the backend exits.';
+comment on column backend_log.id is 'Unique and increasing id of the line, used to order lines and to resume log streams.';
+comment on column backend_log.backend_id is 'The backend that wrote the line.';
+comment on column backend_log.stream is 'The output stream the line was written to (stdout or stderr).';
+comment on column backend_log.logged_at is 'The time the line was written, according to the drone.';
+comment on column backend_log.line is 'The line, without its trailing newline.';
+
+create index idx_backend_log_backend_id on backend_log(backend_id, id);
@@ -0,0 +1,5 @@
+alter table webhook_delivery add column claimed_until timestamptz;
+
+comment on column webhook_delivery.claimed_until is 'The time until which a controller is responsible for delivering the webhook. Pending deliveries whose claim has lapsed are retried by another controller.';
+
+create index idx_webhook_delivery_pending on webhook_delivery(id) where delivered_at is null and failed_at is null;
@@ -0,0 +1,3 @@
+alter table webhook_delivery add column claim_token varchar(255);
+
+comment on column webhook_delivery.claim_token is 'A random token identifying the current claim on the delivery. Only the holder of the claim may renew it or record attempts.';
@@ -1,14 +1,17 @@
+use crate::util::parse_label;
 use chrono::Duration;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
 use plane_common::{
-    names::{BackendName, DroneName, Name, ProxyName},
+    names::{ApiKeyName, BackendName, DroneName, JoinTokenName, Name, ProxyName, WarmPoolName},
     protocol::{CertManagerRequest, CertManagerResponse, MessageFromProxy, MessageToProxy},
     sse::SseStream,
     types::{
-        backend_state::BackendStatusStreamEntry, BackendStatus, ClusterName, ClusterState,
-        ConnectRequest, DockerExecutorConfig, DronePoolName, KeyConfig, Mount, NodeState,
-        SpawnConfig, Subdomain,
+        backend_state::BackendStatusStreamEntry, ApiKeyScope, BackendStatus, ClusterName,
+        ClusterState, ConnectRequest, CreateWarmPoolRequest, DockerExecutorConfig, DronePoolName,
+        IdleAction, KeyConfig, LabelSelector, MintApiKeyRequest, MintApiKeyResponse,
+        MintJoinTokenRequest, MintJoinTokenResponse, Mount, NodeState, RestartPolicy, SpawnConfig,
+        Subdomain,
     },
     version::{PLANE_GIT_HASH, PLANE_VERSION},
     PlaneClient, PlaneClientError,
@@ -87,10 +90,89 @@ pub struct AdminOpts {
     #[clap(long)]
     pub controller: Url,
 
+    /// API key to authenticate with, if the controller requires one.
+    #[clap(long)]
+    pub api_key: Option<String>,
+
+    /// Join token to present when connecting to the controller as a node (as `put-dummy-dns`
+    /// does), if the controller requires one.
+    #[clap(long)]
+    pub join_token: Option<String>,
+
     #[clap(subcommand)]
     pub command: AdminCommand,
 }
 
+#[derive(Args)]
+pub struct MintApiKeyOpts {
+    /// Operation the key may perform. May be provided multiple times.
+    #[clap(long = "scope", required = true)]
+    pub scopes: Vec<ApiKeyScope>,
+
+    /// Restrict the key to a single cluster.
+    #[clap(long)]
+    pub cluster: Option<ClusterName>,
+
+    /// Restrict the key to backends whose key is in this namespace.
+    #[clap(long)]
+    pub key_namespace: Option<String>,
+
+    /// Description of what the key is used for.
+    #[clap(long)]
+    pub description: Option<String>,
+}
+
+impl From<MintApiKeyOpts> for MintApiKeyRequest {
+    fn from(opts: MintApiKeyOpts) -> Self {
+        Self {
+            scopes: opts.scopes,
+            cluster: opts.cluster,
+            key_namespace: opts.key_namespace,
+            description: opts.description,
+        }
+    }
+}
+
+pub fn show_minted_api_key(response: &MintApiKeyResponse) {
+    println!("Minted API key: {}", response.id.to_string().bright_green());
+    println!(
+        "Key (this is not stored, and will not be shown again): {}",
+        response.api_key.bright_white()
+    );
+}
+
+#[derive(Args)]
+pub struct MintJoinTokenOpts {
+    /// Restrict the token to drones and proxies in a single cluster. Tokens without a
+    /// cluster may be used by any node, including DNS servers.
+    #[clap(long)]
+    pub cluster: Option<ClusterName>,
+
+    /// Description of what the token is used for.
+    #[clap(long)]
+    pub description: Option<String>,
+}
+
+impl From<MintJoinTokenOpts> for MintJoinTokenRequest {
+    fn from(opts: MintJoinTokenOpts) -> Self {
+        Self {
+            cluster: opts.cluster,
+            description: opts.description,
+        }
+    }
+}
+
+pub fn show_minted_join_token(response: &MintJoinTokenResponse) {
+    println!(
+        "Minted join token: {}",
+        response.id.to_string().bright_green()
+    );
+    println!(
+        "Token (this is not stored, and will not be shown again): {}",
+        response.join_token.bright_white()
+    );
+}
+
 #[derive(Subcommand)]
 pub enum AdminCommand {
     Connect {
@@ -110,6 +192,10 @@ pub enum AdminCommand {
         #[clap(long)]
         max_idle_seconds: Option<i32>,
 
+        /// Suspend the backend once it has been idle for `--max-idle-seconds`, instead of terminating it.
+        #[clap(long)]
+        suspend_when_idle: bool,
+
         /// An optional backend to assign the string (not recommended and may be removed. Omit to allow Plane to auto-assign
         /// one instead).
         #[clap(long)]
@@ -123,6 +209,18 @@ pub enum AdminCommand {
         #[clap(long, default_value_t = DronePoolName::default())]
         pool: DronePoolName,
 
+        /// Pool to try if no drone in `--pool` is available. May be repeated; pools are tried in order.
+        #[clap(long = "fallback-pool")]
+        fallback_pools: Vec<DronePoolName>,
+
+        /// Only run the backend on a drone with this label, given as `key=value`. May be repeated.
+        #[clap(long = "require-label", value_parser = parse_label)]
+        required_labels: Vec<(String, String)>,
+
+        /// Prefer drones with this label, given as `key=value`. May be repeated.
+        #[clap(long = "prefer-label", value_parser = parse_label)]
+        preferred_labels: Vec<(String, String)>,
+
         /// Optionally mount the specified directory from under the host's mount
         /// base to /plane-data in the backend. The directory will be created on
         /// the host if it doesn't exist already.
@@ -132,6 +230,14 @@ pub enum AdminCommand {
         /// Optionally specify a subdomain for this backend.
         #[clap(long)]
         subdomain: Option<Subdomain>,
+
+        /// If no drone is available, queue the backend for up to this many seconds instead of failing.
+        #[clap(long)]
+        queue_timeout_seconds: Option<i32>,
+
+        /// Restart the backend's process up to this many times if it exits with an error.
+        #[clap(long)]
+        max_restarts: Option<u32>,
     },
     Terminate {
         backend: BackendName,
@@ -160,6 +266,40 @@ pub enum AdminCommand {
     BackendStatus {
         backend: BackendName,
     },
+    /// Mint a new API key.
+    MintApiKey(MintApiKeyOpts),
+    /// Revoke an API key, so that it can no longer be used.
+    RevokeApiKey {
+        id: ApiKeyName,
+    },
+    /// Mint a new join token, which drones, proxies, and DNS servers present when connecting.
+    MintJoinToken(MintJoinTokenOpts),
+    /// Revoke a join token, so that nodes can no longer use it to connect.
+    RevokeJoinToken {
+        id: JoinTokenName,
+    },
+    /// Create a warm pool, which keeps backends of an image ready for connect requests to claim.
+    CreateWarmPool {
+        #[clap(long)]
+        cluster: Option<ClusterName>,
+
+        #[clap(long)]
+        image: String,
+
+        /// Number of unclaimed backends to keep ready.
+        #[clap(long)]
+        size: u32,
+
+        /// The drone pool to run the warm backends in.
+        #[clap(long, default_value_t = DronePoolName::default())]
+        pool: DronePoolName,
+    },
+    /// List warm pools and their unclaimed backends.
+    ListWarmPools,
+    /// Delete a warm pool, and terminate its unclaimed backends.
+    DeleteWarmPool {
+        id: WarmPoolName,
+    },
 }
 
 pub async fn run_admin_command(opts: AdminOpts) {
@@ -187,7 +327,13 @@ pub async fn print_status_stream(
 }
 
 pub async fn run_admin_command_inner(opts: AdminOpts) -> Result<(), PlaneClientError> {
-    let client = PlaneClient::new(opts.controller);
+    let mut client = PlaneClient::new(opts.controller);
+    if let Some(api_key) = &opts.api_key {
+        client = client.with_api_key(api_key);
+    }
+    if let Some(join_token) = &opts.join_token {
+        client = client.with_join_token(join_token);
+    }
 
     match opts.command {
         AdminCommand::Connect {
@@ -196,11 +342,17 @@ pub async fn run_admin_command_inner(opts: AdminOpts) -> Result<(), PlaneClientE
             key,
             immediate,
             max_idle_seconds,
+            suspend_when_idle,
             id,
             static_token,
             pool,
+            fallback_pools,
+            required_labels,
+            preferred_labels,
             mount,
             subdomain,
+            queue_timeout_seconds,
+            max_restarts,
         } => {
             let mut executor_config = DockerExecutorConfig::from_image_with_defaults(image);
             executor_config.mount = mount.map(Mount::Path);
@@ -209,12 +361,29 @@ pub async fn run_admin_command_inner(opts: AdminOpts) -> Result<(), PlaneClientE
                 id,
                 cluster: cluster.clone(),
                 pool,
+                fallback_pools,
+                label_selector: LabelSelector {
+                    required: required_labels.into_iter().collect(),
+                    preferred: preferred_labels.into_iter().collect(),
+                },
                 executable: serde_json::to_value(&executor_config)
                     .expect("Failed to serialize config"),
                 lifetime_limit_seconds: None,
                 max_idle_seconds: Some(max_idle_seconds),
+                idle_action: if suspend_when_idle {
+                    IdleAction::Suspend
+                } else {
+                    IdleAction::Terminate
+                },
                 use_static_token: static_token,
                 subdomain,
+                queue_timeout_seconds,
+                restart_policy: max_restarts
+                    .map(|max_attempts| RestartPolicy::OnFailure {
+                        max_attempts,
+                        backoff_seconds: 1,
+                    })
+                    .unwrap_or_default(),
             };
             let key_config = key.map(|name| KeyConfig {
                 name,
@@ -247,6 +416,10 @@ pub async fn run_admin_command_inner(opts: AdminOpts) -> Result<(), PlaneClientE
                 println!("Drone: {}", drone.to_string().bright_green());
             }
 
+            if let Some(pool) = response.pool.filter(|pool| !pool.is_default()) {
+                println!("Pool: {}", pool.to_string().bright_green());
+            }
+
             if !immediate {
                 let stream = client.backend_status_stream(&response.backend_id).await?;
                 print_status_stream(stream, BackendStatus::Ready).await;
@@ -369,6 +542,90 @@ pub async fn run_admin_command_inner(opts: AdminOpts) -> Result<(), PlaneClientE
             let stream = client.backend_status_stream(&backend).await?;
             print_status_stream(stream, BackendStatus::Terminated).await;
         }
+        AdminCommand::MintApiKey(mint_opts) => {
+            let response = client.mint_api_key(&mint_opts.into()).await?;
+            show_minted_api_key(&response);
+        }
+        AdminCommand::RevokeApiKey { id } => {
+            let result = client.revoke_api_key(&id).await?;
+            if result.revoked {
+                println!("Revoked API key {}.", id.to_string().bright_green());
+            } else {
+                println!(
+                    "API key {} does not exist or was already revoked.",
+                    id.to_string().bright_green()
+                );
+            }
+        }
+        AdminCommand::MintJoinToken(mint_opts) => {
+            let response = client.mint_join_token(&mint_opts.into()).await?;
+            show_minted_join_token(&response);
+        }
+        AdminCommand::RevokeJoinToken { id } => {
+            let result = client.revoke_join_token(&id).await?;
+            if result.revoked {
+                println!("Revoked join token {}.", id.to_string().bright_green());
+            } else {
+                println!(
+                    "Join token {} does not exist or was already revoked.",
+                    id.to_string().bright_green()
+                );
+            }
+        }
+        AdminCommand::CreateWarmPool {
+            cluster,
+            image,
+            size,
+            pool,
+        } => {
+            let executor_config = DockerExecutorConfig::from_image_with_defaults(image);
+            let warm_pool = client
+                .create_warm_pool(&CreateWarmPoolRequest {
+                    cluster,
+                    pool,
+                    executable: serde_json::to_value(&executor_config)
+                        .expect("Failed to serialize config"),
+                    size,
+                })
+                .await?;
+            println!(
+                "Created warm pool: {}",
+                warm_pool.id.to_string().bright_green()
+            );
+        }
+        AdminCommand::ListWarmPools => {
+            let warm_pools = client.list_warm_pools().await?;
+            for warm_pool in warm_pools {
+                let image = warm_pool
+                    .executable
+                    .get("image")
+                    .and_then(|image| image.as_str())
+                    .unwrap_or_default();
+                println!(
+                    "{} {} {} (cluster: {}, pool: {})",
+                    warm_pool.id.to_string().bright_green(),
+                    image.bright_white(),
+                    format!(
+                        "{} ready, {} starting, size {}",
+                        warm_pool.ready, warm_pool.starting, warm_pool.size
+                    )
+                    .bright_cyan(),
+                    warm_pool.cluster,
+                    warm_pool.pool,
+                );
+            }
+        }
+        AdminCommand::DeleteWarmPool { id } => {
+            let result = client.delete_warm_pool(&id).await?;
+            if result.deleted {
+                println!("Deleted warm pool {}.", id.to_string().bright_green());
+            } else {
+                println!(
+                    "Warm pool {} does not exist.",
+                    id.to_string().bright_green()
+                );
+            }
+        }
     };
 
     Ok(())
@@ -385,13 +642,31 @@ pub fn show_node_state(node: &NodeState) {
     );
 }
 
+/// The fraction of a drone's backend capacity that is in use.
+fn utilization(backend_count: u32, max_backends: u32) -> f64 {
+    if max_backends == 0 {
+        return 1.0;
+    }
+
+    backend_count as f64 / max_backends as f64
+}
+
 pub fn show_cluster_state(cluster_state: &ClusterState) {
     println!("{}", "Drones:".bright_yellow());
     for drone in &cluster_state.drones {
         show_node_state(&drone.node);
         println!("    Ready: {}", drone.ready);
         println!("    Draining: {}", drone.draining);
-        println!("    Backend count: {}", drone.backend_count);
+        if let Some(max_backends) = drone.max_backends {
+            println!(
+                "    Backend count: {} / {} ({:.0}% utilized)",
+                drone.backend_count,
+                max_backends,
+                utilization(drone.backend_count, max_backends) * 100.0
+            );
+        } else {
+            println!("    Backend count: {}", drone.backend_count);
+        }
         println!(
             "    Last heartbeat age: {}",
             friendly_duration(drone.last_heartbeat_age)
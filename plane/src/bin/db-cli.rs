@@ -2,7 +2,11 @@
 
 use clap::{Parser, Subcommand};
 use colored::{self, Colorize};
-use plane::{database::connect, init_tracing::init_tracing};
+use plane::{
+    admin::{show_minted_api_key, show_minted_join_token, MintApiKeyOpts, MintJoinTokenOpts},
+    database::connect,
+    init_tracing::init_tracing,
+};
 use plane_common::{
     names::{BackendName, DroneName},
     types::{BackendState, BackendStatus, ClusterName, TerminationReason},
@@ -61,6 +65,12 @@ enum Command {
         #[clap(long)]
         cluster: Option<ClusterName>,
     },
+    /// Mint an API key directly in the database. This can be used to create the first
+    /// `admin` key for a controller that requires API keys.
+    MintApiKey(MintApiKeyOpts),
+    /// Mint a join token directly in the database. This can be used to create the first
+    /// join token for a controller that requires join tokens.
+    MintJoinToken(MintJoinTokenOpts),
 }
 
 async fn main_inner(opts: Opts) -> anyhow::Result<()> {
@@ -133,6 +143,14 @@ async fn main_inner(opts: Opts) -> anyhow::Result<()> {
                 }
             }
         }
+        Command::MintApiKey(mint_opts) => {
+            let response = db.api_keys().mint(&mint_opts.into()).await?;
+            show_minted_api_key(&response);
+        }
+        Command::MintJoinToken(mint_opts) => {
+            let response = db.join_tokens().mint(&mint_opts.into()).await?;
+            show_minted_join_token(&response);
+        }
         Command::ListBackends => {
             let backends = db.backend().list_backends().await?;
 
@@ -143,7 +161,11 @@ async fn main_inner(opts: Opts) -> anyhow::Result<()> {
                     backend.cluster.green(),
                     backend.state.status().to_string().yellow(),
                     backend.last_status_time.to_string().white(),
-                    backend.drone_id.to_string().green(),
+                    backend
+                        .drone_id
+                        .map(|drone_id| drone_id.to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                        .green(),
                 );
             }
         }
@@ -19,6 +19,7 @@ pub async fn run_cleanup(
             )
             .await?;
         EventSubscriptionManager::clean_up_events(&db.pool, min_age_days).await?;
+        db.webhook().clean_up_deliveries(min_age_days).await?;
     }
 
     db.clean_up_tokens().await?;
@@ -0,0 +1,198 @@
+use super::{
+    core::Controller,
+    error::{err_to_response, IntoApiError},
+};
+use crate::database::{api_key::ApiKey, PlaneDatabase};
+use axum::{
+    extract::{Path, RawPathParams, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::Next,
+    response::Response,
+    Extension, Json,
+};
+use plane_common::{
+    names::{ApiKeyName, BackendName},
+    protocol::ApiErrorKind,
+    types::{ApiKeyScope, MintApiKeyRequest, MintApiKeyResponse, RevokeApiKeyResult},
+};
+
+pub fn forbidden(user_message: &str) -> Response {
+    err_to_response(
+        user_message,
+        StatusCode::FORBIDDEN,
+        user_message,
+        ApiErrorKind::Forbidden,
+    )
+}
+
+/// Requires requests to carry a valid API key as a bearer token. The key is added to the
+/// request's extensions, where `require_scope` and handlers check what it is allowed to do.
+pub async fn api_key_layer(
+    State(db): State<PlaneDatabase>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    let api_key = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let Some(api_key) = api_key else {
+        return err_to_response(
+            "Missing API key",
+            StatusCode::UNAUTHORIZED,
+            "Missing API key.",
+            ApiErrorKind::Unauthorized,
+        );
+    };
+
+    match db.api_keys().authenticate(api_key).await {
+        Ok(Some(api_key)) => {
+            req.extensions_mut().insert(api_key);
+            next.run(req).await
+        }
+        Ok(None) => err_to_response(
+            "Invalid API key",
+            StatusCode::UNAUTHORIZED,
+            "Invalid or revoked API key.",
+            ApiErrorKind::Unauthorized,
+        ),
+        Err(err) => err_to_response(
+            err,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to verify API key.",
+            ApiErrorKind::DatabaseError,
+        ),
+    }
+}
+
+/// Checks that an API key may act on the given backend, based on its cluster and key namespace.
+pub async fn authorize_backend(
+    controller: &Controller,
+    api_key: &ApiKey,
+    backend: &BackendName,
+) -> Result<(), Response> {
+    if !api_key.is_restricted() {
+        return Ok(());
+    }
+
+    let owner = controller
+        .db
+        .api_keys()
+        .backend_owner(backend)
+        .await
+        .or_internal_error("Database error")?
+        .or_not_found("Backend does not exist")?;
+
+    if !api_key.allows_cluster(&owner.cluster) {
+        return Err(forbidden(
+            "API key is not valid for this backend's cluster.",
+        ));
+    }
+
+    if !api_key.allows_key_namespace(owner.key_namespace.as_deref()) {
+        return Err(forbidden(
+            "API key is not valid for this backend's key namespace.",
+        ));
+    }
+
+    Ok(())
+}
+
+/// Requires the request's API key to have the given scope, and checks the `cluster` and
+/// `backend` path parameters (if present) against the key's restrictions.
+///
+/// Routes with a `cluster` parameter act on the whole cluster, so keys restricted to a key
+/// namespace may not use them.
+///
+/// If API key authentication is disabled, requests carry no key and are passed through.
+pub async fn require_scope(
+    State((controller, scope)): State<(Controller, ApiKeyScope)>,
+    params: Option<RawPathParams>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(api_key) = req.extensions().get::<ApiKey>().cloned() else {
+        return next.run(req).await;
+    };
+
+    if !api_key.has_scope(scope) {
+        return forbidden(&format!("API key does not have the `{}` scope.", scope));
+    }
+
+    for (name, value) in params.iter().flat_map(|params| params.iter()) {
+        match name {
+            "cluster" if api_key.key_namespace.is_some() => {
+                return forbidden("API key is restricted to a key namespace.");
+            }
+            "cluster" if !api_key.allows_cluster(value) => {
+                return forbidden("API key is not valid for this cluster.");
+            }
+            "backend" => {
+                let Ok(backend) = BackendName::try_from(value.to_string()) else {
+                    return forbidden("Invalid backend name.");
+                };
+
+                if let Err(response) = authorize_backend(&controller, &api_key, &backend).await {
+                    return response;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    next.run(req).await
+}
+
+/// Checks that an API key may manage other API keys and join tokens. Keys restricted to a
+/// cluster or key namespace may not, since they could otherwise mint credentials without
+/// those restrictions.
+pub fn authorize_admin(api_key: Option<Extension<ApiKey>>) -> Result<(), Response> {
+    match api_key {
+        Some(Extension(api_key)) if api_key.is_restricted() => {
+            Err(forbidden("Only unrestricted API keys may manage API keys."))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub async fn handle_mint_api_key(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    Json(request): Json<MintApiKeyRequest>,
+) -> Result<Json<MintApiKeyResponse>, Response> {
+    authorize_admin(api_key)?;
+
+    let response = controller
+        .db
+        .api_keys()
+        .mint(&request)
+        .await
+        .or_internal_error("Failed to mint API key")?;
+
+    tracing::info!(id = %response.id, scopes = ?request.scopes, "Minted API key.");
+
+    Ok(Json(response))
+}
+
+pub async fn handle_revoke_api_key(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    Path(id): Path<ApiKeyName>,
+) -> Result<Json<RevokeApiKeyResult>, Response> {
+    authorize_admin(api_key)?;
+
+    let revoked = controller
+        .db
+        .api_keys()
+        .revoke(&id)
+        .await
+        .or_internal_error("Failed to revoke API key")?;
+
+    if revoked {
+        tracing::info!(%id, "Revoked API key.");
+    }
+
+    Ok(Json(RevokeApiKeyResult { revoked }))
+}
@@ -0,0 +1,112 @@
+use super::{
+    api_key::forbidden,
+    core::Controller,
+    error::{err_to_response, IntoApiError},
+};
+use crate::database::{api_key::ApiKey, backend::BackendListCursor};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Response,
+    Extension, Json,
+};
+use plane_common::{
+    names::BackendName,
+    protocol::ApiErrorKind,
+    types::{BackendLimitsRequest, BackendLimitsResponse, BackendListQuery, BackendListResponse},
+};
+
+/// Number of backends returned per page if the request does not specify a limit.
+const DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Maximum number of backends returned per page, regardless of the requested limit.
+const MAX_PAGE_SIZE: u32 = 1_000;
+
+/// Narrows a listing query to the cluster and key namespace an API key is restricted to.
+fn restrict_query(api_key: &ApiKey, query: &mut BackendListQuery) -> Result<(), Response> {
+    if let Some(cluster) = &api_key.cluster {
+        match &query.cluster {
+            Some(requested) if requested != cluster => {
+                return Err(forbidden("API key is not valid for this cluster."));
+            }
+            _ => query.cluster = Some(cluster.clone()),
+        }
+    }
+
+    if let Some(key_namespace) = &api_key.key_namespace {
+        match &query.key_namespace {
+            Some(requested) if requested != key_namespace => {
+                return Err(forbidden("API key is not valid for this key namespace."));
+            }
+            _ => query.key_namespace = Some(key_namespace.clone()),
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn handle_list_backends(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    Query(mut query): Query<BackendListQuery>,
+) -> Result<Json<BackendListResponse>, Response> {
+    if let Some(Extension(api_key)) = &api_key {
+        restrict_query(api_key, &mut query)?;
+    }
+
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(BackendListCursor::decode)
+        .map(|cursor| {
+            cursor.or_status(
+                StatusCode::BAD_REQUEST,
+                "Invalid cursor",
+                ApiErrorKind::Other,
+            )
+        })
+        .transpose()?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let (backends, next_cursor) = controller
+        .db
+        .backend()
+        .search_backends(&query, cursor.as_ref(), limit as i64)
+        .await
+        .or_internal_error("Database error")?;
+
+    Ok(Json(BackendListResponse {
+        backends,
+        next_cursor: next_cursor.map(|cursor| cursor.encode()),
+    }))
+}
+
+pub async fn handle_update_limits(
+    State(controller): State<Controller>,
+    Path(backend_id): Path<BackendName>,
+    Json(request): Json<BackendLimitsRequest>,
+) -> Result<Json<BackendLimitsResponse>, Response> {
+    let limits = [request.lifetime_limit_seconds, request.max_idle_seconds];
+    if limits.into_iter().flatten().any(|limit| limit < 0) {
+        return Err(err_to_response(
+            "Negative backend limit.",
+            StatusCode::BAD_REQUEST,
+            "Limits must not be negative.",
+            ApiErrorKind::Other,
+        ));
+    }
+
+    let limits = controller
+        .db
+        .backend()
+        .update_limits(&backend_id, &request)
+        .await
+        .or_internal_error("Database error")?
+        .or_not_found("Backend does not exist or has terminated")?;
+
+    Ok(Json(limits))
+}
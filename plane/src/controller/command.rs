@@ -1,4 +1,4 @@
-use super::ControllerConfig;
+use super::{webhooks::WebhookConfig, ControllerConfig};
 use anyhow::Result;
 use clap::Parser;
 use plane_common::{
@@ -34,6 +34,26 @@ pub struct ControllerOpts {
     /// (after stripping `/ctrl` and everything before it).
     #[clap(long)]
     forward_auth: Option<Url>,
+
+    /// Require requests to /ctrl/ routes to carry an API key (minted with `plane admin mint-api-key`)
+    /// as a bearer token.
+    #[clap(long)]
+    require_api_key: bool,
+
+    /// Require drones, proxies, and DNS servers to present a join token (minted with
+    /// `plane admin mint-join-token`) when connecting.
+    #[clap(long)]
+    require_join_token: bool,
+
+    /// URL to POST a JSON payload to whenever a backend changes state. May be
+    /// provided multiple times to deliver to multiple URLs.
+    #[clap(long = "webhook-url")]
+    webhook_urls: Vec<Url>,
+
+    /// Secret used to sign webhook payloads with HMAC-SHA256. The signature is sent
+    /// in the `X-Plane-Signature` header.
+    #[clap(long)]
+    webhook_secret: Option<String>,
 }
 
 impl ControllerOpts {
@@ -47,6 +67,15 @@ impl ControllerOpts {
 
         let addr = (self.host, self.port).into();
 
+        let webhook = if self.webhook_urls.is_empty() {
+            None
+        } else {
+            Some(WebhookConfig {
+                urls: self.webhook_urls,
+                secret: self.webhook_secret,
+            })
+        };
+
         Ok(ControllerConfig {
             db_url: self.db,
             bind_addr: addr,
@@ -56,6 +85,9 @@ impl ControllerOpts {
             cleanup_min_age_days: self.cleanup_min_age_days,
             cleanup_batch_size: None,
             forward_auth: self.forward_auth,
+            require_api_key: self.require_api_key,
+            require_join_token: self.require_join_token,
+            webhook,
         })
     }
 }
@@ -1,65 +1,82 @@
+use super::api_key::{authorize_backend, forbidden};
 use super::error::err_to_response;
+use super::metrics::CONTROLLER_METRICS;
 use super::Controller;
 use crate::controller::error::IntoApiError;
-use crate::database::connect::ConnectError;
-use axum::{extract::State, http::StatusCode, response::Response, Json};
+use crate::database::{api_key::ApiKey, connect::ConnectError};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Extension, Json,
+};
 use plane_common::{
+    names::BackendName,
     protocol::ApiErrorKind,
-    types::{ConnectRequest, ConnectResponse, RevokeRequest},
+    types::{
+        ConnectRequest, ConnectResponse, RevokeRequest, TokenRefreshRequest, TokenRefreshResponse,
+    },
 };
 
-fn connect_error_to_response(connect_error: &ConnectError) -> Response {
+/// Returns the status code, user-facing message, and error kind for a connect error.
+fn connect_error_details(connect_error: &ConnectError) -> (StatusCode, &'static str, ApiErrorKind) {
     match connect_error {
-        ConnectError::FailedToAcquireKey => err_to_response(
-            connect_error,
+        ConnectError::FailedToAcquireKey => (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Failed to acquire lock.",
             ApiErrorKind::FailedToAcquireKey,
         ),
-        ConnectError::KeyUnheldNoSpawnConfig => err_to_response(
-            connect_error,
+        ConnectError::KeyUnheldNoSpawnConfig => (
             StatusCode::CONFLICT,
             "Lock is unheld but no spawn config was provided.",
             ApiErrorKind::KeyUnheldNoSpawnConfig,
         ),
-        ConnectError::KeyHeldUnhealthy => err_to_response(
-            connect_error,
+        ConnectError::KeyHeldUnhealthy => (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Lock is held but unhealthy.",
             ApiErrorKind::KeyHeldUnhealthy,
         ),
-        ConnectError::KeyHeld { .. } => err_to_response(
-            connect_error,
+        ConnectError::KeyHeld { .. } => (
             StatusCode::CONFLICT,
             "Lock is held but tag does not match.",
             ApiErrorKind::KeyHeld,
         ),
-        ConnectError::NoDroneAvailable => err_to_response(
-            connect_error,
+        ConnectError::NoDroneAvailable => (
             StatusCode::INTERNAL_SERVER_ERROR,
             "No active drone available.",
             ApiErrorKind::NoDroneAvailable,
         ),
-        ConnectError::FailedToRemoveKey => err_to_response(
-            connect_error,
+        ConnectError::ClusterAtCapacity | ConnectError::DroneAtCapacity => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "All drones are at capacity.",
+            ApiErrorKind::ClusterAtCapacity,
+        ),
+        ConnectError::FailedToRemoveKey => (
             StatusCode::CONFLICT,
             "Failed to remove lock.",
             ApiErrorKind::FailedToRemoveKey,
         ),
-        ConnectError::DatabaseError(_) | ConnectError::Serialization(_) => err_to_response(
-            connect_error,
+        ConnectError::DatabaseError(_) | ConnectError::Serialization(_) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Internal error.",
             ApiErrorKind::Other,
         ),
-        ConnectError::NoClusterProvided => err_to_response(
-            connect_error,
+        ConnectError::NoClusterProvided => (
             StatusCode::BAD_REQUEST,
             "No cluster provided, and no default cluster for this controller.",
             ApiErrorKind::NoClusterProvided,
         ),
-        ConnectError::Other(_) => err_to_response(
-            connect_error,
+        ConnectError::InvalidTokenLifetime(_) => (
+            StatusCode::BAD_REQUEST,
+            "Invalid token lifetime.",
+            ApiErrorKind::Other,
+        ),
+        ConnectError::ClusterNotAllowed(_) => (
+            StatusCode::FORBIDDEN,
+            "API key is not valid for this cluster.",
+            ApiErrorKind::Forbidden,
+        ),
+        ConnectError::Other(_) => (
             StatusCode::INTERNAL_SERVER_ERROR,
             "Internal error.",
             ApiErrorKind::Other,
@@ -67,26 +84,65 @@ fn connect_error_to_response(connect_error: &ConnectError) -> Response {
     }
 }
 
+fn connect_error_to_response(connect_error: &ConnectError) -> Response {
+    let (status, user_message, kind) = connect_error_details(connect_error);
+    err_to_response(connect_error, status, user_message, kind)
+}
+
+/// Checks the key namespace of a connect request against an API key's restrictions. The
+/// cluster is checked during the connect, once it is known, before a token is issued.
+fn authorize_connect(api_key: &ApiKey, request: &ConnectRequest) -> Result<(), Response> {
+    let key_namespace = request.key.as_ref().map(|key| key.namespace.as_str());
+    if api_key.key_namespace.is_some() && !api_key.allows_key_namespace(key_namespace) {
+        return Err(forbidden("API key is not valid for this key namespace."));
+    }
+
+    Ok(())
+}
+
 pub async fn handle_connect(
     State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
     Json(request): Json<ConnectRequest>,
 ) -> Result<Json<ConnectResponse>, Response> {
-    let response = controller
-        .connect(&request)
-        .await
-        .map_err(|e| connect_error_to_response(&e))?;
-    Ok(Json(response))
+    if let Some(Extension(api_key)) = &api_key {
+        authorize_connect(api_key, &request)?;
+    }
+
+    let allowed_cluster = api_key
+        .as_ref()
+        .and_then(|Extension(api_key)| api_key.cluster.as_ref());
+
+    match controller.connect(&request, allowed_cluster).await {
+        Ok(response) => {
+            CONTROLLER_METRICS
+                .connect_requests
+                .with_label_values(&["Success"])
+                .inc();
+            Ok(Json(response))
+        }
+        Err(err) => {
+            let (status, user_message, kind) = connect_error_details(&err);
+            CONTROLLER_METRICS
+                .connect_requests
+                .with_label_values(&[&format!("{:?}", kind)])
+                .inc();
+            Err(err_to_response(&err, status, user_message, kind))
+        }
+    }
 }
 
-// TODO: Make proxies aware when a token is revoked, because they cache the
-// token->backend mapping. This will probably require a larger re-thinking of
-// how data is synchronized between the controller and proxies. Eventually we
-// could even have it propagate to the proxies all the way so that it even
-// interrupts existing connections!
+/// Revokes a user's tokens for a backend. Proxies in the backend's cluster are
+/// notified, so that they stop routing the tokens and close connections made with them.
 pub async fn handle_revoke(
     State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
     Json(request): Json<RevokeRequest>,
 ) -> Result<Json<&'static str>, Response> {
+    if let Some(Extension(api_key)) = &api_key {
+        authorize_backend(&controller, api_key, &request.backend_id).await?;
+    }
+
     controller
         .db
         .revoke(&request)
@@ -94,3 +150,21 @@ pub async fn handle_revoke(
         .or_internal_error("Failed to revoke token")?;
     Ok(Json("Token revoked successfully"))
 }
+
+pub async fn handle_refresh_token(
+    State(controller): State<Controller>,
+    Path(backend_id): Path<BackendName>,
+    Json(request): Json<TokenRefreshRequest>,
+) -> Result<Json<TokenRefreshResponse>, Response> {
+    let expiration_time = controller
+        .db
+        .refresh_token(&backend_id, &request)
+        .await
+        .map_err(|e| connect_error_to_response(&e))?
+        .or_not_found("Token not found, expired, or backend terminated")?;
+
+    Ok(Json(TokenRefreshResponse {
+        token: request.token,
+        expiration_time,
+    }))
+}
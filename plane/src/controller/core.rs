@@ -15,6 +15,9 @@ pub struct Controller {
     pub id: ControllerName,
     pub client: PlaneClient,
     pub default_cluster: Option<ClusterName>,
+
+    /// Whether drones, proxies, and DNS servers must present a join token to connect.
+    pub require_join_token: bool,
 }
 
 pub struct NodeHandle {
@@ -77,6 +80,7 @@ impl Controller {
         id: ControllerName,
         controller_url: Url,
         default_cluster: Option<ClusterName>,
+        require_join_token: bool,
     ) -> Self {
         let client = PlaneClient::new(controller_url);
 
@@ -85,16 +89,25 @@ impl Controller {
             id,
             client,
             default_cluster,
+            require_join_token,
         }
     }
 
+    /// Connects to or spawns a backend. If `allowed_cluster` is provided, the request
+    /// fails if the backend is in another cluster.
     pub async fn connect(
         &self,
         connect_request: &ConnectRequest,
+        allowed_cluster: Option<&ClusterName>,
     ) -> Result<ConnectResponse, ConnectError> {
         let response = self
             .db
-            .connect(self.default_cluster.as_ref(), connect_request, &self.client)
+            .connect(
+                self.default_cluster.as_ref(),
+                allowed_cluster,
+                connect_request,
+                &self.client,
+            )
             .await?;
 
         Ok(response)
@@ -1,7 +1,10 @@
-use super::Controller;
+use super::{api_key::forbidden, join_token::verify_join_token, Controller};
+use crate::database::api_key::ApiKey;
 use axum::{
     extract::{ws::WebSocket, ConnectInfo, State, WebSocketUpgrade},
-    response::IntoResponse,
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Extension,
 };
 use plane_common::{
     protocol::{MessageFromDns, MessageToDns},
@@ -61,9 +64,23 @@ pub async fn dns_socket(ws: WebSocket, controller: Controller, ip: IpAddr) {
 
 pub async fn handle_dns_socket(
     State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    headers: HeaderMap,
     connect_info: ConnectInfo<SocketAddr>,
     ws: WebSocketUpgrade,
-) -> impl IntoResponse {
+) -> Result<impl IntoResponse, Response> {
+    // DNS servers serve every cluster and key namespace, so a restricted key may not connect one.
+    if let Some(Extension(api_key)) = api_key {
+        if api_key.is_restricted() {
+            return Err(forbidden(
+                "API key is restricted to a cluster or key namespace.",
+            ));
+        }
+    }
+
+    // Likewise, DNS servers may only use join tokens that are not restricted to a cluster.
+    verify_join_token(&controller, &headers, None).await?;
+
     let ip = connect_info.ip();
-    ws.on_upgrade(move |socket| dns_socket(socket, controller, ip))
+    Ok(ws.on_upgrade(move |socket| dns_socket(socket, controller, ip)))
 }
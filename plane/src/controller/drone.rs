@@ -1,13 +1,13 @@
 use axum::{
     extract::{ws::WebSocket, ConnectInfo, Path, Query, State, WebSocketUpgrade},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
 use plane_common::{
     log_types::LoggableTime,
     protocol::{
-        ApiErrorKind, BackendAction, BackendActionMessage, Heartbeat, KeyDeadlines,
-        MessageFromDrone, MessageToDrone, RenewKeyResponse,
+        ApiErrorKind, BackendAction, BackendActionMessage, BackendLogMessage, Heartbeat,
+        KeyDeadlines, MessageFromDrone, MessageToDrone, RenewKeyResponse,
     },
     typed_socket::{server::new_server, TypedSocket},
     types::{
@@ -19,6 +19,7 @@ use std::{
     net::{IpAddr, SocketAddr},
     time::Duration,
 };
+use tokio::sync::mpsc;
 use valuable::Valuable;
 
 use crate::database::{
@@ -28,8 +29,17 @@ use crate::database::{
     subscribe::Subscription,
     PlaneDatabase,
 };
+use crate::util::GuardHandle;
 
-use super::{core::Controller, error::IntoApiError};
+use super::{
+    core::Controller, error::IntoApiError, join_token::verify_join_token,
+    metrics::CONTROLLER_METRICS,
+};
+
+/// Number of batches of log lines from a drone that can wait to be written to the database.
+/// Batches that arrive while the queue is full are dropped, so that log lines do not hold up
+/// the drone's other messages.
+const LOG_QUEUE_BATCHES: usize = 32;
 
 #[derive(Deserialize)]
 pub struct DroneSocketQuery {
@@ -41,16 +51,26 @@ pub async fn handle_message_from_drone(
     drone_id: NodeId,
     controller: &Controller,
     sender: &mut TypedSocket<MessageToDrone>,
+    log_queue: &mpsc::Sender<Vec<BackendLogMessage>>,
 ) -> anyhow::Result<()> {
     match msg {
         MessageFromDrone::BackendMetrics(metrics_msg) => {
             controller.db.backend().publish_metrics(metrics_msg).await?;
         }
-        MessageFromDrone::Heartbeat(Heartbeat { local_time }) => {
+        MessageFromDrone::BackendLogs(log_msgs) => {
+            if let Err(err) = log_queue.try_send(log_msgs) {
+                tracing::warn!(%err, "Dropping log lines from drone.");
+                CONTROLLER_METRICS.dropped_log_batches.inc();
+            }
+        }
+        MessageFromDrone::Heartbeat(Heartbeat {
+            local_time,
+            resources,
+        }) => {
             controller
                 .db
                 .drone()
-                .heartbeat(drone_id, local_time.0)
+                .heartbeat(drone_id, local_time.0, resources.as_ref())
                 .await?;
         }
         MessageFromDrone::BackendEvent(backend_event) => {
@@ -115,6 +135,28 @@ pub async fn sweep_loop(db: PlaneDatabase, drone_id: NodeId) {
         };
 
         for candidate in candidates {
+            if candidate.suspend {
+                tracing::info!(
+                    backend_id = candidate.backend_id.as_value(),
+                    drone_id = drone_id.as_i32(),
+                    allowed_idle_seconds = ?candidate.allowed_idle_seconds,
+                    as_of = ?candidate.as_of,
+                    last_keepalive = ?candidate.last_keepalive,
+                    "Suspending idle backend"
+                );
+
+                if let Err(err) = db
+                    .backend_actions()
+                    .create_pending_action(&candidate.backend_id, drone_id, &BackendAction::Suspend)
+                    .await
+                {
+                    tracing::error!(?err, "Error suspending backend");
+                } else {
+                    CONTROLLER_METRICS.suspended_backends.inc();
+                }
+                continue;
+            }
+
             tracing::info!(
                 backend_id = candidate.backend_id.as_value(),
                 drone_id = drone_id.as_i32(),
@@ -138,11 +180,26 @@ pub async fn sweep_loop(db: PlaneDatabase, drone_id: NodeId) {
                 .await
             {
                 tracing::error!(?err, "Error terminating backend");
+            } else {
+                CONTROLLER_METRICS.swept_backends.inc();
             }
         }
     }
 }
 
+/// Writes batches of log lines received from a drone to the database, in the order they
+/// were received.
+async fn append_logs_loop(
+    db: PlaneDatabase,
+    mut log_queue: mpsc::Receiver<Vec<BackendLogMessage>>,
+) {
+    while let Some(log_msgs) = log_queue.recv().await {
+        if let Err(err) = db.backend_logs().append(&log_msgs).await {
+            tracing::error!(?err, "Error appending log lines");
+        }
+    }
+}
+
 pub async fn process_pending_actions(
     db: &PlaneDatabase,
     socket: &mut TypedSocket<MessageToDrone>,
@@ -190,10 +247,20 @@ pub async fn drone_socket_inner(
 
     let sweep_loop_handle = tokio::spawn(sweep_loop(controller.db.clone(), drone_id));
 
+    let (log_queue, log_queue_receiver) = mpsc::channel(LOG_QUEUE_BATCHES);
+    let _append_logs_handle =
+        GuardHandle::new(append_logs_loop(controller.db.clone(), log_queue_receiver));
+
     controller
         .db
         .drone()
-        .register_drone(drone_id, true, pool)
+        .register_drone(
+            drone_id,
+            true,
+            pool,
+            &socket.remote_handshake.labels,
+            socket.remote_handshake.max_backends,
+        )
         .await?;
 
     let mut backend_actions: Subscription<BackendActionMessage> =
@@ -226,7 +293,7 @@ pub async fn drone_socket_inner(
             message_from_drone_result = socket.recv() => {
                 match message_from_drone_result {
                     Some(message_from_drone) => {
-                        if let Err(err) = handle_message_from_drone(message_from_drone, drone_id, &controller, &mut socket).await {
+                        if let Err(err) = handle_message_from_drone(message_from_drone, drone_id, &controller, &mut socket, &log_queue).await {
                             tracing::error!(?err, "Error handling message from drone");
                         }
                     }
@@ -260,6 +327,7 @@ pub async fn handle_drone_socket(
     Path(cluster): Path<String>,
     Query(query): Query<DroneSocketQuery>,
     State(controller): State<Controller>,
+    headers: HeaderMap,
     connect_info: ConnectInfo<SocketAddr>,
     ws: WebSocketUpgrade,
 ) -> Result<impl IntoResponse, Response> {
@@ -268,6 +336,7 @@ pub async fn handle_drone_socket(
         "Invalid cluster name",
         ApiErrorKind::InvalidClusterName,
     )?;
+    verify_join_token(&controller, &headers, Some(&cluster)).await?;
     let pool = query.pool.unwrap_or_default();
     let ip = connect_info.0.ip();
     Ok(ws.on_upgrade(move |socket| drone_socket(cluster, socket, controller, ip, pool)))
@@ -0,0 +1,116 @@
+use super::core::Controller;
+use crate::database::subscribe::{Notification, NotificationPayload, Subscription};
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive},
+        Sse,
+    },
+};
+use futures_util::Stream;
+use plane_common::types::{ClusterEvent, ClusterEventStreamEntry, ClusterName};
+use serde_json::Value;
+use std::convert::Infallible;
+
+fn to_sse_event(notification: Notification<ClusterEvent>) -> Event {
+    let entry = ClusterEventStreamEntry {
+        timestamp: notification.timestamp,
+        event: notification.payload,
+    };
+
+    let event = Event::default()
+        .json_data(&entry)
+        .expect("always serializable");
+
+    match notification.id {
+        Some(id) => event.id(id.to_string()),
+        None => event,
+    }
+}
+
+/// Converts a past event from the event table into a cluster event, if it is
+/// a cluster event for the given cluster.
+fn as_cluster_event(
+    notification: Notification<Value>,
+    cluster: &str,
+) -> Option<Notification<ClusterEvent>> {
+    if notification.kind != ClusterEvent::kind() || notification.key.as_deref() != Some(cluster) {
+        return None;
+    }
+
+    let payload = match serde_json::from_value(notification.payload) {
+        Ok(payload) => payload,
+        Err(err) => {
+            tracing::warn!(?err, id = notification.id, "Failed to parse cluster event.");
+            return None;
+        }
+    };
+
+    Some(Notification {
+        id: notification.id,
+        timestamp: notification.timestamp,
+        kind: notification.kind,
+        key: notification.key,
+        payload,
+    })
+}
+
+pub async fn handle_cluster_events(
+    Path(cluster): Path<ClusterName>,
+    State(controller): State<Controller>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let last_event_id: Option<i32> = headers
+        .get("Last-Event-ID")
+        .and_then(|id| id.to_str().ok())
+        .and_then(|id| id.parse().ok());
+
+    let cluster = cluster.to_string();
+
+    // We subscribe before replaying past events, so that no events are missed in between.
+    let mut subscription: Subscription<ClusterEvent> = controller.db.subscribe_with_key(&cluster);
+
+    let stream = async_stream::stream! {
+        let mut last_event_id = last_event_id;
+
+        if let Some(mut since) = last_event_id {
+            loop {
+                let events = match controller.db.get_events_since(since).await {
+                    Ok(events) => events,
+                    Err(err) => {
+                        // Ending the stream lets the client reconnect and resume from the last event it received.
+                        tracing::error!(?err, "Error fetching past cluster events.");
+                        return;
+                    }
+                };
+
+                let Some(last_id) = events.last().and_then(|event| event.id) else {
+                    break;
+                };
+                since = last_id;
+
+                for event in events {
+                    if let Some(event) = as_cluster_event(event, &cluster) {
+                        yield Ok(to_sse_event(event));
+                    }
+                }
+            }
+
+            last_event_id = Some(since);
+        }
+
+        while let Some(notification) = subscription.next().await {
+            if let (Some(id), Some(last_event_id)) = (notification.id, last_event_id) {
+                if id <= last_event_id {
+                    // Already sent while replaying past events.
+                    continue;
+                }
+            }
+
+            yield Ok(to_sse_event(notification));
+        }
+    };
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
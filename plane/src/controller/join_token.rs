@@ -0,0 +1,111 @@
+use super::{
+    api_key::authorize_admin,
+    core::Controller,
+    error::{err_to_response, IntoApiError},
+};
+use crate::database::api_key::ApiKey;
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    Extension, Json,
+};
+use plane_common::{
+    names::JoinTokenName,
+    protocol::ApiErrorKind,
+    typed_socket::client::JOIN_TOKEN_HEADER,
+    types::{ClusterName, MintJoinTokenRequest, MintJoinTokenResponse, RevokeJoinTokenResult},
+};
+
+/// Checks the join token presented by a node before it is registered. `cluster` is the
+/// cluster the node is joining, or `None` for DNS servers.
+///
+/// If join tokens are not required by the controller, all nodes are accepted.
+pub async fn verify_join_token(
+    controller: &Controller,
+    headers: &HeaderMap,
+    cluster: Option<&ClusterName>,
+) -> Result<(), Response> {
+    if !controller.require_join_token {
+        return Ok(());
+    }
+
+    let join_token = headers
+        .get(JOIN_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .or_status(
+            StatusCode::UNAUTHORIZED,
+            "Missing join token.",
+            ApiErrorKind::Unauthorized,
+        )?;
+
+    let join_token = controller
+        .db
+        .join_tokens()
+        .authenticate(join_token)
+        .await
+        .or_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Failed to verify join token.",
+            ApiErrorKind::DatabaseError,
+        )?
+        .or_status(
+            StatusCode::UNAUTHORIZED,
+            "Invalid or revoked join token.",
+            ApiErrorKind::Unauthorized,
+        )?;
+
+    if !join_token.allows_cluster(cluster) {
+        return Err(err_to_response(
+            format!(
+                "Join token {} is not valid for {:?}",
+                join_token.id, cluster
+            ),
+            StatusCode::UNAUTHORIZED,
+            "Join token is not valid for this cluster.",
+            ApiErrorKind::Unauthorized,
+        ));
+    }
+
+    Ok(())
+}
+
+pub async fn handle_mint_join_token(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    Json(request): Json<MintJoinTokenRequest>,
+) -> Result<Json<MintJoinTokenResponse>, Response> {
+    authorize_admin(api_key)?;
+
+    let response = controller
+        .db
+        .join_tokens()
+        .mint(&request)
+        .await
+        .or_internal_error("Failed to mint join token")?;
+
+    tracing::info!(id = %response.id, cluster = ?request.cluster, "Minted join token.");
+
+    Ok(Json(response))
+}
+
+pub async fn handle_revoke_join_token(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    Path(id): Path<JoinTokenName>,
+) -> Result<Json<RevokeJoinTokenResult>, Response> {
+    authorize_admin(api_key)?;
+
+    let revoked = controller
+        .db
+        .join_tokens()
+        .revoke(&id)
+        .await
+        .or_internal_error("Failed to revoke join token")?;
+
+    if revoked {
+        tracing::info!(%id, "Revoked join token.");
+    }
+
+    Ok(Json(RevokeJoinTokenResult { revoked }))
+}
@@ -0,0 +1,81 @@
+use super::{core::Controller, error::IntoApiError};
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive},
+        IntoResponse, Response, Sse,
+    },
+    Json,
+};
+use futures_util::StreamExt;
+use plane_common::{
+    names::BackendName,
+    types::{BackendLogEntry, BackendLogsQuery},
+};
+use std::convert::Infallible;
+
+/// Returns a backend's buffered log lines, or with `follow`, an event stream of them that
+/// continues with new lines. Lines stay available after the backend terminates, until the
+/// backend is cleaned up.
+pub async fn handle_backend_logs(
+    State(controller): State<Controller>,
+    Path(backend_id): Path<BackendName>,
+    Query(query): Query<BackendLogsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Response> {
+    controller
+        .db
+        .backend()
+        .backend(&backend_id)
+        .await
+        .or_internal_error("Database error")?
+        .or_not_found("Backend does not exist")?;
+
+    if !query.follow {
+        let records = controller
+            .db
+            .backend_logs()
+            .recent(&backend_id, query.tail, None)
+            .await
+            .or_internal_error("Database error")?;
+
+        let entries: Vec<BackendLogEntry> =
+            records.into_iter().map(|record| record.entry).collect();
+        return Ok(Json(entries).into_response());
+    }
+
+    let last_event_id: Option<i64> = headers
+        .get("Last-Event-ID")
+        .and_then(|id| id.to_str().ok())
+        .and_then(|id| id.parse().ok());
+
+    // A client resuming a stream is sent every buffered line it missed, rather than the tail.
+    let tail = match last_event_id {
+        Some(_) => None,
+        None => query.tail,
+    };
+
+    let mut records = Box::pin(
+        controller
+            .db
+            .backend_logs()
+            .follow(&backend_id, tail, last_event_id)
+            .await
+            .or_internal_error("Database error")?,
+    );
+
+    let stream = async_stream::stream! {
+        while let Some(record) = records.next().await {
+            let event = Event::default()
+                .json_data(&record.entry)
+                .expect("always serializable")
+                .id(record.id.to_string());
+            yield Ok::<_, Infallible>(event);
+        }
+    };
+
+    Ok(Sse::new(stream)
+        .keep_alive(KeepAlive::default())
+        .into_response())
+}
@@ -0,0 +1,208 @@
+use super::{api_key::forbidden, core::Controller, error::IntoApiError};
+use crate::{
+    database::{api_key::ApiKey, metrics::DATABASE_METRICS},
+    metrics::metrics_response,
+};
+use axum::{extract::State, response::Response, Extension};
+use prometheus::{opts, IntCounter, IntCounterVec, IntGaugeVec, Registry};
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+/// Minimum time between reads of the cluster-wide gauges from the database. Scrapes within
+/// this interval of the last read report the gauges from that read.
+const GAUGE_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Metrics reported by the controller on `/ctrl/metrics`.
+///
+/// Counters and histograms are recorded by this controller process, while gauges are
+/// cluster-wide counts read from the database when metrics are scraped, at most once every
+/// `GAUGE_REFRESH_INTERVAL`. Metrics recorded by database operations are in
+/// `DATABASE_METRICS`, and are reported alongside these.
+pub struct ControllerMetrics {
+    registry: Registry,
+
+    /// Connect requests, labeled by `result`, which is either `Success` or an `ApiErrorKind`.
+    pub connect_requests: IntCounterVec,
+
+    /// Non-terminated backends, labeled by cluster and status.
+    pub backends: IntGaugeVec,
+
+    /// Drones and proxies connected to a live controller, labeled by cluster and node kind.
+    pub connected_nodes: IntGaugeVec,
+
+    /// Backend actions not yet acknowledged by their drone, labeled by cluster.
+    pub pending_backend_actions: IntGaugeVec,
+
+    /// Backends that the sweep loop has asked to terminate for being expired or idle.
+    pub swept_backends: IntCounter,
+
+    /// Backends that the sweep loop has asked to suspend for being idle.
+    pub suspended_backends: IntCounter,
+
+    /// Batches of backend log lines dropped because the controller could not store them
+    /// as fast as they arrived.
+    pub dropped_log_batches: IntCounter,
+
+    /// When the gauges were last read from the database.
+    gauges_refreshed_at: Mutex<Option<Instant>>,
+}
+
+pub static CONTROLLER_METRICS: LazyLock<ControllerMetrics> = LazyLock::new(ControllerMetrics::new);
+
+impl ControllerMetrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let connect_requests = IntCounterVec::new(
+            opts!(
+                "plane_connect_requests_total",
+                "Connect requests by result."
+            ),
+            &["result"],
+        )
+        .expect("Metric options are valid.");
+
+        let backends = IntGaugeVec::new(
+            opts!(
+                "plane_backends",
+                "Non-terminated backends by cluster and status."
+            ),
+            &["cluster", "status"],
+        )
+        .expect("Metric options are valid.");
+
+        let connected_nodes = IntGaugeVec::new(
+            opts!(
+                "plane_connected_nodes",
+                "Nodes connected to a live controller by cluster and kind."
+            ),
+            &["cluster", "kind"],
+        )
+        .expect("Metric options are valid.");
+
+        let pending_backend_actions = IntGaugeVec::new(
+            opts!(
+                "plane_pending_backend_actions",
+                "Backend actions not yet acknowledged by their drone, by cluster."
+            ),
+            &["cluster"],
+        )
+        .expect("Metric options are valid.");
+
+        let swept_backends = IntCounter::new(
+            "plane_swept_backends_total",
+            "Backends the sweep loop asked to terminate for being expired or idle.",
+        )
+        .expect("Metric options are valid.");
+
+        let suspended_backends = IntCounter::new(
+            "plane_suspended_backends_total",
+            "Backends the sweep loop asked to suspend for being idle.",
+        )
+        .expect("Metric options are valid.");
+
+        let dropped_log_batches = IntCounter::new(
+            "plane_dropped_log_batches_total",
+            "Batches of backend log lines dropped because they could not be stored in time.",
+        )
+        .expect("Metric options are valid.");
+
+        for collector in [
+            Box::new(connect_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(backends.clone()),
+            Box::new(connected_nodes.clone()),
+            Box::new(pending_backend_actions.clone()),
+            Box::new(swept_backends.clone()),
+            Box::new(suspended_backends.clone()),
+            Box::new(dropped_log_batches.clone()),
+        ]
+        .into_iter()
+        .chain(DATABASE_METRICS.collectors())
+        {
+            registry
+                .register(collector)
+                .expect("Metric names are unique.");
+        }
+
+        Self {
+            registry,
+            connect_requests,
+            backends,
+            connected_nodes,
+            pending_backend_actions,
+            swept_backends,
+            suspended_backends,
+            dropped_log_batches,
+            gauges_refreshed_at: Mutex::new(None),
+        }
+    }
+
+    /// Refreshes the cluster-wide gauges from the database, unless they were refreshed within
+    /// the last `GAUGE_REFRESH_INTERVAL`.
+    async fn refresh_gauges(&self, controller: &Controller) -> sqlx::Result<()> {
+        // Holding the lock while refreshing also keeps concurrent scrapes from each querying
+        // the database.
+        let mut refreshed_at = self.gauges_refreshed_at.lock().await;
+        if let Some(refreshed_at) = *refreshed_at {
+            if refreshed_at.elapsed() < GAUGE_REFRESH_INTERVAL {
+                return Ok(());
+            }
+        }
+
+        let metrics_db = controller.db.metrics();
+
+        let backend_counts = metrics_db.backend_counts().await?;
+        let node_counts = metrics_db.connected_node_counts().await?;
+        let pending_action_counts = metrics_db.pending_action_counts().await?;
+
+        // Reset the gauges, so that label sets that no longer have any rows are not reported.
+        self.backends.reset();
+        for row in backend_counts {
+            self.backends
+                .with_label_values(&[&row.cluster, &row.status])
+                .set(row.count);
+        }
+
+        self.connected_nodes.reset();
+        for row in node_counts {
+            self.connected_nodes
+                .with_label_values(&[&row.cluster, &row.kind])
+                .set(row.count);
+        }
+
+        self.pending_backend_actions.reset();
+        for row in pending_action_counts {
+            self.pending_backend_actions
+                .with_label_values(&[&row.cluster])
+                .set(row.count);
+        }
+
+        *refreshed_at = Some(Instant::now());
+
+        Ok(())
+    }
+}
+
+pub async fn handle_metrics(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+) -> Result<Response, Response> {
+    // Metrics cover every cluster, so a restricted key may not read them.
+    if let Some(Extension(api_key)) = api_key {
+        if api_key.is_restricted() {
+            return Err(forbidden(
+                "API key is restricted to a cluster or key namespace.",
+            ));
+        }
+    }
+
+    CONTROLLER_METRICS
+        .refresh_gauges(&controller)
+        .await
+        .or_internal_error("Failed to read metrics from database")?;
+
+    Ok(metrics_response(&CONTROLLER_METRICS.registry))
+}
@@ -1,11 +1,23 @@
 use self::{
+    api_key::{api_key_layer, handle_mint_api_key, handle_revoke_api_key, require_scope},
     backend_state::{handle_backend_status, handle_backend_status_stream},
+    backends::{handle_list_backends, handle_update_limits},
     cluster_state::handle_cluster_state,
-    connect::handle_revoke,
+    connect::{handle_refresh_token, handle_revoke},
     dns::handle_dns_socket,
     drain::handle_drain,
     error::IntoApiError,
+    events::handle_cluster_events,
+    join_token::{handle_mint_join_token, handle_revoke_join_token},
+    logs::handle_backend_logs,
+    metrics::handle_metrics,
     proxy::handle_proxy_socket,
+    spawn_queue::run_spawn_queue_loop,
+    warm_pool::{
+        handle_create_warm_pool, handle_delete_warm_pool, handle_list_warm_pools,
+        run_warm_pool_loop,
+    },
+    webhooks::{run_webhook_loop, WebhookConfig},
 };
 use crate::{
     cleanup,
@@ -28,7 +40,7 @@ use futures_util::never::Never;
 use plane_common::{
     names::ControllerName,
     protocol::StatusResponse,
-    types::ClusterName,
+    types::{ApiKeyScope, ClusterName},
     version::{PLANE_GIT_HASH, PLANE_VERSION},
     PlaneClient,
 };
@@ -48,7 +60,9 @@ use tower_http::{
 use tracing::Level;
 use url::Url;
 
+mod api_key;
 mod backend_state;
+mod backends;
 mod cluster_state;
 pub mod command;
 mod connect;
@@ -57,9 +71,16 @@ mod dns;
 mod drain;
 mod drone;
 pub mod error;
+mod events;
 mod forward_auth;
+mod join_token;
+mod logs;
+pub mod metrics;
 mod proxy;
+mod spawn_queue;
 mod terminate;
+mod warm_pool;
+pub mod webhooks;
 
 /// How long to wait for the server to terminate gracefully before forcing it to shut down.
 /// We want to keep this just high enough to serve short requests. Long-lived requests
@@ -149,6 +170,9 @@ pub struct ControllerServer {
     // when gracefully terminating.
     server_handle: Option<JoinHandle<Result<(), std::io::Error>>>,
     _cleanup_handle: GuardHandle,
+    _spawn_queue_handle: GuardHandle,
+    _warm_pool_handle: GuardHandle,
+    _webhook_handle: Option<GuardHandle>,
 }
 
 impl ControllerServer {
@@ -172,6 +196,9 @@ impl ControllerServer {
             config.cleanup_min_age_days,
             config.cleanup_batch_size,
             config.forward_auth,
+            config.require_api_key,
+            config.require_join_token,
+            config.webhook,
         )
         .await
     }
@@ -186,7 +213,18 @@ impl ControllerServer {
         cleanup_min_age_days: Option<i32>,
         cleanup_batch_size: Option<i32>,
         forward_auth: Option<Url>,
+        require_api_key: bool,
+        require_join_token: bool,
+        webhook: Option<WebhookConfig>,
     ) -> Result<Self> {
+        // Join tokens are minted and revoked through control routes, so requiring them
+        // without authenticating those routes would let anyone mint one.
+        if require_join_token && !require_api_key && forward_auth.is_none() {
+            anyhow::bail!(
+                "Requiring join tokens also requires API keys or forward auth, to protect the join token routes."
+            );
+        }
+
         let bind_addr = listener.local_addr()?;
 
         let cleanup_handle = {
@@ -197,11 +235,30 @@ impl ControllerServer {
             })
         };
 
+        let spawn_queue_handle = GuardHandle::new(run_spawn_queue_loop(db.clone()));
+
+        let warm_pool_handle = GuardHandle::new(run_warm_pool_loop(db.clone()));
+
+        let webhook_handle = webhook.map(|webhook| {
+            tracing::info!(urls = ?webhook.urls, "Webhooks enabled");
+            GuardHandle::new(run_webhook_loop(db.clone(), webhook))
+        });
+
         let (graceful_terminate_sender, graceful_terminate_receiver) =
             tokio::sync::oneshot::channel::<()>();
 
-        let controller =
-            Controller::new(db.clone(), id.clone(), controller_url, default_cluster).await;
+        if require_join_token {
+            tracing::info!("Join tokens required for node connections");
+        }
+
+        let controller = Controller::new(
+            db.clone(),
+            id.clone(),
+            controller_url,
+            default_cluster,
+            require_join_token,
+        )
+        .await;
 
         let trace_layer = TraceLayer::new_for_http()
             .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
@@ -215,28 +272,110 @@ impl ControllerServer {
         // or that otherwise expose non-public system information.
         //
         // These routes should not be exposed on the open internet without an authorization
-        // barrier (such as a reverse proxy) in front.
+        // barrier (such as API keys, or a reverse proxy) in front.
+        //
+        // Each route requires an API key with the given scope, when API keys are required.
+        let scope = |scope: ApiKeyScope| {
+            axum::middleware::from_fn_with_state((controller.clone(), scope), require_scope)
+        };
         let mut control_routes = Router::new()
             .route("/status", get(status))
-            .route("/c/:cluster/state", get(handle_cluster_state))
-            .route("/c/:cluster/drone-socket", get(handle_drone_socket))
-            .route("/c/:cluster/proxy-socket", get(handle_proxy_socket))
-            .route("/dns-socket", get(handle_dns_socket))
-            .route("/connect", post(handle_connect))
-            .route("/c/:cluster/d/:drone/drain", post(handle_drain))
+            .route(
+                "/metrics",
+                get(handle_metrics).route_layer(scope(ApiKeyScope::ReadState)),
+            )
+            .route(
+                "/c/:cluster/state",
+                get(handle_cluster_state).route_layer(scope(ApiKeyScope::ReadState)),
+            )
+            .route(
+                "/c/:cluster/events",
+                get(handle_cluster_events).route_layer(scope(ApiKeyScope::ReadState)),
+            )
+            .route(
+                "/backends",
+                get(handle_list_backends).route_layer(scope(ApiKeyScope::ReadState)),
+            )
+            .route(
+                "/c/:cluster/drone-socket",
+                get(handle_drone_socket).route_layer(scope(ApiKeyScope::NodeSocket)),
+            )
+            .route(
+                "/c/:cluster/proxy-socket",
+                get(handle_proxy_socket).route_layer(scope(ApiKeyScope::NodeSocket)),
+            )
+            .route(
+                "/dns-socket",
+                get(handle_dns_socket).route_layer(scope(ApiKeyScope::NodeSocket)),
+            )
+            .route(
+                "/connect",
+                post(handle_connect).route_layer(scope(ApiKeyScope::Connect)),
+            )
+            .route(
+                "/c/:cluster/d/:drone/drain",
+                post(handle_drain).route_layer(scope(ApiKeyScope::Drain)),
+            )
             .route(
                 "/b/:backend/soft-terminate",
-                post(terminate::handle_soft_terminate),
+                post(terminate::handle_soft_terminate).route_layer(scope(ApiKeyScope::Terminate)),
             )
             .route(
                 "/b/:backend/hard-terminate",
-                post(terminate::handle_hard_terminate),
+                post(terminate::handle_hard_terminate).route_layer(scope(ApiKeyScope::Terminate)),
+            )
+            .route(
+                "/b/:backend/tokens/refresh",
+                post(handle_refresh_token).route_layer(scope(ApiKeyScope::Connect)),
+            )
+            .route(
+                "/b/:backend/limits",
+                post(handle_update_limits).route_layer(scope(ApiKeyScope::Connect)),
+            )
+            .route(
+                "/b/:backend/logs",
+                get(handle_backend_logs).route_layer(scope(ApiKeyScope::ReadState)),
             )
             .route(
                 "/b/revoke",
-                post(handle_revoke), // (TODO) does not notify proxies, see handler function for details
+                post(handle_revoke).route_layer(scope(ApiKeyScope::Terminate)),
+            )
+            .route(
+                "/api-keys",
+                post(handle_mint_api_key).route_layer(scope(ApiKeyScope::Admin)),
+            )
+            .route(
+                "/api-keys/:api_key/revoke",
+                post(handle_revoke_api_key).route_layer(scope(ApiKeyScope::Admin)),
+            )
+            .route(
+                "/join-tokens",
+                post(handle_mint_join_token).route_layer(scope(ApiKeyScope::Admin)),
+            )
+            .route(
+                "/join-tokens/:join_token/revoke",
+                post(handle_revoke_join_token).route_layer(scope(ApiKeyScope::Admin)),
+            )
+            .route(
+                "/warm-pools",
+                post(handle_create_warm_pool)
+                    .get(handle_list_warm_pools)
+                    .route_layer(scope(ApiKeyScope::Admin)),
+            )
+            .route(
+                "/warm-pools/:warm_pool/delete",
+                post(handle_delete_warm_pool).route_layer(scope(ApiKeyScope::Admin)),
             );
 
+        if require_api_key {
+            tracing::info!("API key authentication enabled");
+
+            control_routes = control_routes.layer(axum::middleware::from_fn_with_state(
+                db.clone(),
+                api_key_layer,
+            ));
+        }
+
         if let Some(forward_auth_url) = forward_auth {
             tracing::info!(?forward_auth_url, "Forward auth enabled");
             let forward_url = forward_auth_url.clone();
@@ -288,6 +427,9 @@ impl ControllerServer {
             controller_id: id,
             bind_addr,
             _cleanup_handle: cleanup_handle,
+            _spawn_queue_handle: spawn_queue_handle,
+            _warm_pool_handle: warm_pool_handle,
+            _webhook_handle: webhook_handle,
         })
     }
 
@@ -353,6 +495,16 @@ pub struct ControllerConfig {
     pub cleanup_min_age_days: Option<i32>,
     pub cleanup_batch_size: Option<i32>,
     pub forward_auth: Option<Url>,
+
+    /// If true, requests to `/ctrl` routes must be authenticated with an API key.
+    #[serde(default)]
+    pub require_api_key: bool,
+
+    /// If true, drones, proxies, and DNS servers must present a valid join token to connect.
+    #[serde(default)]
+    pub require_join_token: bool,
+
+    pub webhook: Option<WebhookConfig>,
 }
 
 pub async fn run_controller(config: ControllerConfig) -> Result<()> {
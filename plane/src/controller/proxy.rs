@@ -1,21 +1,21 @@
-use super::{core::Controller, error::IntoApiError};
+use super::{core::Controller, error::IntoApiError, join_token::verify_join_token};
 use crate::database::{
     backend::RouteInfoResult,
     subscribe::{Notification, Subscription},
 };
 use axum::{
     extract::{ws::WebSocket, ConnectInfo, Path, State, WebSocketUpgrade},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Response},
 };
 use plane_common::{
     names::{BackendName, Name},
     protocol::{
         ApiErrorKind, CertManagerRequest, CertManagerResponse, MessageFromProxy, MessageToProxy,
-        RouteInfoRequest, RouteInfoResponse,
+        RouteInfoRequest, RouteInfoResponse, TokensRevoked,
     },
     typed_socket::{server::new_server, TypedSocket},
-    types::{BackendState, BearerToken, ClusterName, NodeId},
+    types::{BackendState, BackendStatus, BearerToken, ClusterName, NodeId},
 };
 use std::net::{IpAddr, SocketAddr};
 use tokio::select;
@@ -45,6 +45,13 @@ pub async fn handle_route_info_request(
             let mut sub: Subscription<BackendState> =
                 controller.db.subscribe_with_key(backend_id.as_str());
 
+            // If the backend is suspended, a request for it is what resumes it.
+            if partial_route_info.status == BackendStatus::Suspended
+                && controller.db.backend().resume(&backend_id).await?
+            {
+                tracing::info!(%backend_id, "Resuming suspended backend.");
+            }
+
             // There is a race condition if the status updated between when our last query hit and when we started the
             // subscription. It's a bit hacky, but for now we will just issue the query again.
             // We can't start the subscription first to avoid repeating the query, because we need to know the backend
@@ -101,8 +108,13 @@ pub async fn handle_route_info_request(
                     let Notification { payload, .. } = result;
 
                     match payload {
-                        BackendState::Ready { address } => {
-                            let route_info = partial_route_info.set_address(address);
+                        BackendState::Ready {
+                            address,
+                            named_addresses,
+                            ..
+                        } => {
+                            let route_info =
+                                partial_route_info.set_address(address, named_addresses);
                             let response = RouteInfoResponse {
                                 token,
                                 route_info: Some(route_info),
@@ -263,6 +275,8 @@ pub async fn proxy_socket_inner(
         .await?;
 
     let mut event_subscription: Subscription<BackendState> = controller.db.subscribe();
+    let mut revocation_subscription: Subscription<TokensRevoked> =
+        controller.db.subscribe_with_key(&cluster.to_string());
 
     loop {
         select! {
@@ -291,6 +305,36 @@ pub async fn proxy_socket_inner(
                         };
                         socket.send(MessageToProxy::BackendRemoved { backend: backend_id })?;
                     },
+                    Some(Notification {
+                        key: Some(backend_id),
+                        payload: BackendState::Suspended { .. },
+                        ..
+                    }) => {
+                        let backend_id = match BackendName::try_from(backend_id) {
+                            Ok(backend_id) => backend_id,
+                            Err(err) => {
+                                tracing::error!(?err, "Error parsing backend ID from notification");
+                                continue;
+                            }
+                        };
+                        socket.send(MessageToProxy::BackendSuspended { backend: backend_id })?;
+                    },
+                    // A restarting backend's old address is gone, and a waiting backend may
+                    // have a new one, so cached routes to it are stale.
+                    Some(Notification {
+                        key: Some(backend_id),
+                        payload: BackendState::Restarting { .. } | BackendState::Waiting { .. },
+                        ..
+                    }) => {
+                        let backend_id = match BackendName::try_from(backend_id) {
+                            Ok(backend_id) => backend_id,
+                            Err(err) => {
+                                tracing::error!(?err, "Error parsing backend ID from notification");
+                                continue;
+                            }
+                        };
+                        socket.send(MessageToProxy::InvalidateRoutes { backend: backend_id })?;
+                    },
                     Some(_) => (),
                     None => {
                         // We treat this as an error, because it should never happen - the
@@ -299,6 +343,16 @@ pub async fn proxy_socket_inner(
                     }
                 }
             }
+            revocation = revocation_subscription.next() => {
+                match revocation {
+                    Some(Notification { payload, .. }) => {
+                        socket.send(MessageToProxy::TokensRevoked(payload))?;
+                    }
+                    None => {
+                        tracing::error!("Revocation subscription closed!");
+                    }
+                }
+            }
         }
     }
 
@@ -314,6 +368,7 @@ pub async fn proxy_socket(cluster: ClusterName, ws: WebSocket, controller: Contr
 pub async fn handle_proxy_socket(
     Path(cluster): Path<String>,
     State(controller): State<Controller>,
+    headers: HeaderMap,
     connect_info: ConnectInfo<SocketAddr>,
     ws: WebSocketUpgrade,
 ) -> Result<impl IntoResponse, Response> {
@@ -322,6 +377,7 @@ pub async fn handle_proxy_socket(
         "Invalid cluster name",
         ApiErrorKind::InvalidClusterName,
     )?;
+    verify_join_token(&controller, &headers, Some(&cluster)).await?;
     let ip = connect_info.ip();
     Ok(ws.on_upgrade(move |socket| proxy_socket(cluster, socket, controller, ip)))
 }
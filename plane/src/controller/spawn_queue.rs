@@ -0,0 +1,22 @@
+use crate::database::PlaneDatabase;
+use std::time::Duration;
+
+/// How often each controller attempts to schedule queued backends.
+const PLACE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Schedules queued backends to drones as capacity becomes available, and terminates those
+/// whose queue timeout has passed. Every controller runs this loop; a backend being scheduled
+/// by one controller is skipped by the others.
+pub async fn run_spawn_queue_loop(db: PlaneDatabase) {
+    loop {
+        if let Err(err) = db.spawn_queue().expire_queued_backends().await {
+            tracing::error!(?err, "Failed to expire queued backends.");
+        }
+
+        if let Err(err) = db.spawn_queue().place_queued_backends().await {
+            tracing::error!(?err, "Failed to schedule queued backends.");
+        }
+
+        tokio::time::sleep(PLACE_INTERVAL).await;
+    }
+}
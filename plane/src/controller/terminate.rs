@@ -23,6 +23,50 @@ async fn terminate(
         .or_internal_error("Database error")?
         .or_not_found("Backend does not exist")?;
 
+    let drone_id = match backend.drone_id {
+        Some(drone_id) => drone_id,
+        None => {
+            // The backend is queued. If it is removed from the queue, it will never be
+            // scheduled, so it can be terminated directly.
+            let dequeued = controller
+                .db
+                .spawn_queue()
+                .dequeue(backend_id)
+                .await
+                .or_internal_error("Database error")?;
+
+            if dequeued {
+                controller
+                    .db
+                    .backend()
+                    .update_state(
+                        backend_id,
+                        backend
+                            .state
+                            .to_terminating(TerminationReason::External)
+                            .to_terminated(None),
+                    )
+                    .await
+                    .or_internal_error("Database error")?;
+                return Ok(());
+            }
+
+            // The backend was scheduled (or terminated) since we read it.
+            let backend = controller
+                .db
+                .backend()
+                .backend(backend_id)
+                .await
+                .or_internal_error("Database error")?
+                .or_not_found("Backend does not exist")?;
+
+            let Some(drone_id) = backend.drone_id else {
+                return Ok(());
+            };
+            drone_id
+        }
+    };
+
     let kind = if hard {
         TerminationKind::Hard
     } else {
@@ -33,7 +77,7 @@ async fn terminate(
         .backend_actions()
         .create_pending_action(
             backend_id,
-            backend.drone_id,
+            drone_id,
             &BackendAction::Terminate {
                 kind,
                 reason: TerminationReason::External,
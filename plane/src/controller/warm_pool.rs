@@ -0,0 +1,124 @@
+use super::{
+    api_key::authorize_admin,
+    core::Controller,
+    error::{err_to_response, IntoApiError},
+};
+use crate::database::{api_key::ApiKey, PlaneDatabase};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::Response,
+    Extension, Json,
+};
+use plane_common::{
+    names::WarmPoolName,
+    protocol::ApiErrorKind,
+    types::{CreateWarmPoolRequest, DeleteWarmPoolResult, WarmPool},
+};
+use serde_json::Value;
+use std::time::Duration;
+
+/// How often each controller checks whether warm pools need to be replenished.
+const REPLENISH_INTERVAL: Duration = Duration::from_secs(5);
+
+pub async fn handle_create_warm_pool(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    Json(request): Json<CreateWarmPoolRequest>,
+) -> Result<Json<WarmPool>, Response> {
+    authorize_admin(api_key)?;
+
+    let cluster = request
+        .cluster
+        .as_ref()
+        .or(controller.default_cluster.as_ref())
+        .or_status(
+            StatusCode::BAD_REQUEST,
+            "No cluster provided, and no default cluster for this controller.",
+            ApiErrorKind::NoClusterProvided,
+        )?;
+
+    // Mounts named after the backend's key would be named after the placeholder key the
+    // backend holds before it is claimed.
+    if request.executable.get("mount") == Some(&Value::Bool(true)) {
+        return Err(err_to_response(
+            "Warm pool executable uses a key-named mount.",
+            StatusCode::BAD_REQUEST,
+            "Warm pool backends can't mount a directory named after their key.",
+            ApiErrorKind::Other,
+        ));
+    }
+
+    let warm_pool = controller
+        .db
+        .warm_pools()
+        .create(cluster, &request)
+        .await
+        .or_internal_error("Failed to create warm pool")?;
+
+    tracing::info!(id = %warm_pool.id, size = warm_pool.size, "Created warm pool.");
+
+    Ok(Json(warm_pool))
+}
+
+pub async fn handle_list_warm_pools(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+) -> Result<Json<Vec<WarmPool>>, Response> {
+    authorize_admin(api_key)?;
+
+    let warm_pools = controller
+        .db
+        .warm_pools()
+        .list()
+        .await
+        .or_internal_error("Failed to list warm pools")?;
+
+    Ok(Json(warm_pools))
+}
+
+pub async fn handle_delete_warm_pool(
+    State(controller): State<Controller>,
+    api_key: Option<Extension<ApiKey>>,
+    Path(id): Path<WarmPoolName>,
+) -> Result<Json<DeleteWarmPoolResult>, Response> {
+    authorize_admin(api_key)?;
+
+    let deleted = controller
+        .db
+        .warm_pools()
+        .delete(&id)
+        .await
+        .or_internal_error("Failed to delete warm pool")?;
+
+    if deleted {
+        tracing::info!(%id, "Deleted warm pool.");
+    }
+
+    Ok(Json(DeleteWarmPoolResult { deleted }))
+}
+
+/// Keeps each warm pool topped up with unclaimed backends. Every controller runs this loop;
+/// a warm pool being replenished by one controller is skipped by the others.
+pub async fn run_warm_pool_loop(db: PlaneDatabase) {
+    loop {
+        match db.warm_pools().list().await {
+            Ok(warm_pools) => {
+                for warm_pool in warm_pools {
+                    if warm_pool.ready + warm_pool.starting >= warm_pool.size {
+                        continue;
+                    }
+
+                    if let Err(err) = db.warm_pools().replenish(&warm_pool.id).await {
+                        tracing::warn!(?err, id = %warm_pool.id, "Failed to replenish warm pool.");
+                    }
+                }
+            }
+            Err(err) => {
+                tracing::error!(?err, "Failed to list warm pools.");
+            }
+        }
+
+        tokio::time::sleep(REPLENISH_INTERVAL).await;
+    }
+}
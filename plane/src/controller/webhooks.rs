@@ -0,0 +1,365 @@
+use crate::{
+    database::{
+        subscribe::{Notification, Subscription},
+        webhook::{DeliveryAttempt, DeliveryClaim, DELIVERY_CLAIM_DURATION},
+        PlaneDatabase,
+    },
+    util::GuardHandle,
+};
+use anyhow::Result;
+use data_encoding::HEXLOWER;
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+use plane_common::{
+    exponential_backoff::ExponentialBackoff,
+    types::{BackendState, BackendStateWebhook, ClusterEvent, ClusterName},
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, time::Duration};
+use url::Url;
+
+/// Number of times delivery of a webhook is attempted before it is abandoned.
+const MAX_DELIVERY_ATTEMPTS: u32 = 10;
+
+/// How long to wait for a webhook receiver to respond before treating the attempt as failed.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often to look for pending deliveries that no controller is delivering.
+const RESUME_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum number of pending deliveries claimed at a time.
+const RESUME_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// URLs that backend state change webhooks are POSTed to.
+    pub urls: Vec<Url>,
+
+    /// Secret used to sign webhook bodies. If not set, webhooks are not signed.
+    pub secret: Option<String>,
+}
+
+/// Computes the hex-encoded HMAC-SHA256 signature of a webhook body.
+fn sign(secret: &str, body: &[u8]) -> Result<String> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(body)?;
+    Ok(HEXLOWER.encode(&signer.sign_to_vec()?))
+}
+
+fn webhook_payload(notification: Notification<ClusterEvent>) -> Option<BackendStateWebhook> {
+    let ClusterEvent::BackendStateChange {
+        backend,
+        key,
+        previous_status,
+        state,
+    } = notification.payload
+    else {
+        return None;
+    };
+
+    let cluster = match notification.key.as_deref().map(ClusterName::from_str) {
+        Some(Ok(cluster)) => cluster,
+        _ => {
+            tracing::warn!(
+                key = notification.key,
+                "Cluster event has invalid cluster key."
+            );
+            return None;
+        }
+    };
+
+    let (termination_reason, exit_code) = match &state {
+        BackendState::Terminated {
+            reason, exit_code, ..
+        } => (*reason, *exit_code),
+        _ => (None, None),
+    };
+
+    Some(BackendStateWebhook {
+        timestamp: notification.timestamp,
+        cluster,
+        backend,
+        key,
+        previous_status,
+        status: state.status(),
+        termination_reason,
+        exit_code,
+    })
+}
+
+async fn attempt_delivery(
+    client: &reqwest::Client,
+    url: &Url,
+    delivery_id: i32,
+    body: &[u8],
+    signature: Option<&str>,
+) -> std::result::Result<u16, (Option<u16>, String)> {
+    let mut request = client
+        .post(url.clone())
+        .header("content-type", "application/json")
+        .header("x-plane-delivery-id", delivery_id.to_string())
+        .body(body.to_vec());
+
+    if let Some(signature) = signature {
+        request = request.header("x-plane-signature", format!("sha256={}", signature));
+    }
+
+    match request.send().await {
+        Ok(response) if response.status().is_success() => Ok(response.status().as_u16()),
+        Ok(response) => Err((
+            Some(response.status().as_u16()),
+            format!("Receiver returned status {}.", response.status()),
+        )),
+        Err(err) => Err((None, err.to_string())),
+    }
+}
+
+/// Keeps this controller's claim on a delivery until the task is dropped, or the claim
+/// is lost.
+async fn renew_claim_loop(db: PlaneDatabase, claim: DeliveryClaim) {
+    let delivery_id = claim.delivery_id;
+    loop {
+        tokio::time::sleep(DELIVERY_CLAIM_DURATION / 3).await;
+
+        match db.webhook().renew_claim(&claim).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tracing::warn!(delivery_id, "Lost claim on webhook delivery.");
+                return;
+            }
+            Err(err) => {
+                tracing::error!(?err, delivery_id, "Failed to renew webhook delivery claim.");
+            }
+        }
+    }
+}
+
+async fn deliver(
+    db: PlaneDatabase,
+    client: reqwest::Client,
+    url: Url,
+    claim: DeliveryClaim,
+    previous_attempts: u32,
+    body: Vec<u8>,
+    signature: Option<String>,
+) {
+    let delivery_id = claim.delivery_id;
+    let _claim_handle = GuardHandle::new(renew_claim_loop(db.clone(), claim.clone()));
+
+    let mut backoff = ExponentialBackoff::new(
+        Duration::from_secs(1),
+        Duration::from_secs(5 * 60),
+        2.0,
+        Duration::from_secs(60),
+    );
+
+    let first_attempt = (previous_attempts + 1).min(MAX_DELIVERY_ATTEMPTS);
+    for attempt in first_attempt..=MAX_DELIVERY_ATTEMPTS {
+        let result =
+            attempt_delivery(&client, &url, delivery_id, &body, signature.as_deref()).await;
+
+        let outcome = match result {
+            Ok(status_code) => DeliveryAttempt::Delivered { status_code },
+            Err((status_code, error)) if attempt == MAX_DELIVERY_ATTEMPTS => {
+                tracing::error!(%url, delivery_id, error, "Abandoning webhook delivery.");
+                DeliveryAttempt::Abandoned { status_code, error }
+            }
+            Err((status_code, error)) => {
+                tracing::warn!(%url, delivery_id, attempt, error, "Webhook delivery failed.");
+                DeliveryAttempt::Failed { status_code, error }
+            }
+        };
+
+        match db.webhook().record_attempt(&claim, &outcome).await {
+            Ok(true) => {}
+            Ok(false) => {
+                // Another controller has claimed the delivery, and will finish it.
+                tracing::warn!(delivery_id, "Lost claim on webhook delivery.");
+                return;
+            }
+            Err(err) => {
+                tracing::error!(
+                    ?err,
+                    delivery_id,
+                    "Failed to record webhook delivery attempt."
+                );
+            }
+        }
+
+        if matches!(outcome, DeliveryAttempt::Failed { .. }) {
+            backoff.wait().await;
+        } else {
+            return;
+        }
+    }
+}
+
+/// Serializes a webhook payload, and signs it if a secret is configured.
+fn prepare_body<T: Serialize>(
+    config: &WebhookConfig,
+    payload: &T,
+) -> Result<(Vec<u8>, Option<String>)> {
+    let body = serde_json::to_vec(payload)?;
+    let signature = match config.secret.as_deref() {
+        Some(secret) => Some(sign(secret, &body)?),
+        None => None,
+    };
+
+    Ok((body, signature))
+}
+
+/// Tracks a delivery task, so that it is stopped with the webhook loop, and forgets tasks
+/// that have finished.
+fn track_delivery(deliveries: &mut Vec<GuardHandle>, delivery: GuardHandle) {
+    deliveries.retain(|delivery| !delivery.is_finished());
+    deliveries.push(delivery);
+}
+
+/// Claims and resumes pending deliveries that no controller is delivering, such as those of
+/// a controller that stopped before finishing them.
+async fn resume_pending_deliveries(
+    db: &PlaneDatabase,
+    client: &reqwest::Client,
+    config: &WebhookConfig,
+    deliveries: &mut Vec<GuardHandle>,
+) {
+    let urls: Vec<String> = config.urls.iter().map(|url| url.to_string()).collect();
+    let pending = match db
+        .webhook()
+        .claim_pending_deliveries(&urls, RESUME_BATCH_SIZE)
+        .await
+    {
+        Ok(pending) => pending,
+        Err(err) => {
+            tracing::error!(?err, "Failed to claim pending webhook deliveries.");
+            return;
+        }
+    };
+
+    for delivery in pending {
+        let delivery_id = delivery.claim.delivery_id;
+        let url = match Url::parse(&delivery.url) {
+            Ok(url) => url,
+            Err(err) => {
+                tracing::error!(?err, delivery_id, "Invalid webhook URL.");
+                continue;
+            }
+        };
+        let (body, signature) = match prepare_body(config, &delivery.payload) {
+            Ok(prepared) => prepared,
+            Err(err) => {
+                tracing::error!(?err, delivery_id, "Failed to sign webhook.");
+                continue;
+            }
+        };
+
+        tracing::info!(%url, delivery_id, "Resuming webhook delivery.");
+        track_delivery(
+            deliveries,
+            GuardHandle::new(deliver(
+                db.clone(),
+                client.clone(),
+                url,
+                delivery.claim,
+                delivery.attempts.max(0) as u32,
+                body,
+                signature,
+            )),
+        );
+    }
+}
+
+/// Delivers a webhook to every configured URL for each backend state change. Deliveries
+/// left pending by controllers that stopped are resumed at startup and periodically.
+pub async fn run_webhook_loop(db: PlaneDatabase, config: WebhookConfig) {
+    let client = match reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            tracing::error!(?err, "Failed to construct webhook client.");
+            return;
+        }
+    };
+
+    let mut subscription: Subscription<ClusterEvent> = db.subscribe();
+    let mut deliveries: Vec<GuardHandle> = Vec::new();
+
+    // The first tick completes immediately, so pending deliveries are resumed at startup.
+    let mut resume_interval = tokio::time::interval(RESUME_INTERVAL);
+    resume_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+    loop {
+        let notification = tokio::select! {
+            _ = resume_interval.tick() => {
+                resume_pending_deliveries(&db, &client, &config, &mut deliveries).await;
+                continue;
+            }
+            notification = subscription.next() => notification,
+        };
+
+        let Some(notification) = notification else {
+            break;
+        };
+
+        let Some(event_id) = notification.id else {
+            continue;
+        };
+
+        let Some(payload) = webhook_payload(notification) else {
+            continue;
+        };
+
+        let (body, signature) = match prepare_body(&config, &payload) {
+            Ok(prepared) => prepared,
+            Err(err) => {
+                tracing::error!(?err, "Failed to sign webhook.");
+                continue;
+            }
+        };
+        let payload_value =
+            serde_json::to_value(&payload).expect("Webhook payload is always serializable.");
+
+        for url in &config.urls {
+            let claim = match db
+                .webhook()
+                .create_delivery(event_id, url.as_str(), &payload_value)
+                .await
+            {
+                Ok(Some(claim)) => claim,
+                // Another controller has already taken responsibility for this delivery.
+                Ok(None) => continue,
+                Err(err) => {
+                    tracing::error!(?err, %url, event_id, "Failed to create webhook delivery.");
+                    continue;
+                }
+            };
+
+            track_delivery(
+                &mut deliveries,
+                GuardHandle::new(deliver(
+                    db.clone(),
+                    client.clone(),
+                    url.clone(),
+                    claim,
+                    0,
+                    body.clone(),
+                    signature.clone(),
+                )),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        // Test vector from RFC 4231, test case 2.
+        let signature = sign("Jefe", b"what do ya want for nothing?").unwrap();
+        assert_eq!(
+            signature,
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
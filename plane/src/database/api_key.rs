@@ -0,0 +1,172 @@
+use super::util::hash_secret;
+use plane_common::{
+    names::{ApiKeyName, BackendName, Name},
+    types::{ApiKeyScope, ClusterName, MintApiKeyRequest, MintApiKeyResponse},
+    util::random_token,
+};
+use sqlx::PgPool;
+
+pub struct ApiKeyDatabase<'a> {
+    pool: &'a PgPool,
+}
+
+/// An API key that has been presented with a request and verified.
+#[derive(Clone, Debug)]
+pub struct ApiKey {
+    pub id: ApiKeyName,
+    pub scopes: Vec<ApiKeyScope>,
+    pub cluster: Option<ClusterName>,
+    pub key_namespace: Option<String>,
+}
+
+impl ApiKey {
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    pub fn allows_cluster(&self, cluster: &str) -> bool {
+        self.cluster
+            .as_ref()
+            .is_none_or(|allowed| allowed.as_str() == cluster)
+    }
+
+    pub fn allows_key_namespace(&self, namespace: Option<&str>) -> bool {
+        self.key_namespace
+            .as_deref()
+            .is_none_or(|allowed| Some(allowed) == namespace)
+    }
+
+    /// Whether the key is restricted to a cluster or key namespace.
+    pub fn is_restricted(&self) -> bool {
+        self.cluster.is_some() || self.key_namespace.is_some()
+    }
+}
+
+/// The cluster and key namespace of a backend, which API keys may be restricted to.
+pub struct BackendOwner {
+    pub cluster: String,
+    pub key_namespace: Option<String>,
+}
+
+impl<'a> ApiKeyDatabase<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates a new API key. The returned key is not stored, only a hash of its secret part.
+    pub async fn mint(&self, request: &MintApiKeyRequest) -> sqlx::Result<MintApiKeyResponse> {
+        let id = ApiKeyName::new_random();
+        let secret = random_token();
+
+        let scopes: Vec<String> = request.scopes.iter().map(|s| s.to_string()).collect();
+
+        sqlx::query!(
+            r#"
+            insert into api_key (id, secret_hash, scopes, cluster, key_namespace, description)
+            values ($1, $2, $3, $4, $5, $6)
+            "#,
+            id.to_string(),
+            hash_secret(&secret),
+            &scopes,
+            request.cluster.as_ref().map(|c| c.to_string()),
+            request.key_namespace,
+            request.description,
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(MintApiKeyResponse {
+            api_key: format!("{}.{}", id, secret),
+            id,
+        })
+    }
+
+    /// Returns the API key if it exists, has not been revoked, and its secret matches.
+    pub async fn authenticate(&self, api_key: &str) -> sqlx::Result<Option<ApiKey>> {
+        let Some((id, secret)) = api_key.split_once('.') else {
+            return Ok(None);
+        };
+
+        let Ok(id) = ApiKeyName::try_from(id.to_string()) else {
+            return Ok(None);
+        };
+
+        let result = sqlx::query!(
+            r#"
+            select secret_hash, scopes, cluster, key_namespace
+            from api_key
+            where id = $1 and revoked_at is null
+            "#,
+            id.to_string(),
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        let Some(result) = result else {
+            return Ok(None);
+        };
+
+        if !openssl::memcmp::eq(
+            hash_secret(secret).as_bytes(),
+            result.secret_hash.as_bytes(),
+        ) {
+            return Ok(None);
+        }
+
+        let scopes = result
+            .scopes
+            .iter()
+            .map(|scope| scope.parse::<ApiKeyScope>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| sqlx::Error::Decode(Box::new(err)))?;
+
+        let cluster = result
+            .cluster
+            .map(|cluster| cluster.parse::<ClusterName>())
+            .transpose()
+            .map_err(|err| sqlx::Error::Decode(err.into()))?;
+
+        Ok(Some(ApiKey {
+            id,
+            scopes,
+            cluster,
+            key_namespace: result.key_namespace,
+        }))
+    }
+
+    /// Revokes an API key. Returns false if the key does not exist or was already revoked.
+    pub async fn revoke(&self, id: &ApiKeyName) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            update api_key
+            set revoked_at = now()
+            where id = $1 and revoked_at is null
+            "#,
+            id.to_string(),
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns the cluster and key namespace of a backend, so that they can be checked
+    /// against the restrictions of an API key.
+    pub async fn backend_owner(&self, backend: &BackendName) -> sqlx::Result<Option<BackendOwner>> {
+        let result = sqlx::query!(
+            r#"
+            select cluster, key_namespace
+            from backend
+            where id = $1
+            "#,
+            backend.to_string(),
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result.map(|row| BackendOwner {
+            cluster: row.cluster,
+            key_namespace: row.key_namespace,
+        }))
+    }
+}
@@ -1,20 +1,23 @@
+use super::metrics::DATABASE_METRICS;
 use super::{
+    backend_actions::create_pending_action,
     subscribe::{emit_backend_metrics, emit_with_key},
     PlaneDatabase,
 };
 use chrono::{DateTime, Utc};
 use futures_util::Stream;
 use plane_common::{
-    log_types::BackendAddr,
+    log_types::{BackendAddr, NamedBackendAddrs},
     names::{BackendName, DroneName},
-    protocol::{BackendActionMessage, BackendMetricsMessage, RouteInfo},
+    protocol::{BackendAction, BackendActionMessage, BackendMetricsMessage, RouteInfo},
     types::{
-        backend_state::BackendStatusStreamEntry, BackendState, BackendStatus, BearerToken,
-        ClusterName, NodeId, SecretToken, Subdomain,
+        backend_state::BackendStatusStreamEntry, BackendLimitsRequest, BackendLimitsResponse,
+        BackendListEntry, BackendListQuery, BackendState, BackendStatus, BearerToken, ClusterEvent,
+        ClusterName, DronePoolName, KeyConfig, NodeId, SecretToken, Subdomain,
     },
 };
-use sqlx::PgConnection;
-use std::{fmt::Debug, net::SocketAddr, str::FromStr};
+use sqlx::{postgres::types::PgInterval, PgConnection};
+use std::{fmt::Debug, net::SocketAddr, str::FromStr, time::Duration};
 use valuable::Valuable;
 
 pub struct BackendDatabase<'a> {
@@ -33,6 +36,12 @@ impl super::subscribe::NotificationPayload for BackendMetricsMessage {
     }
 }
 
+impl super::subscribe::NotificationPayload for ClusterEvent {
+    fn kind() -> &'static str {
+        "cluster_event"
+    }
+}
+
 impl super::subscribe::NotificationPayload for BackendState {
     fn kind() -> &'static str {
         "backend_state"
@@ -69,12 +78,14 @@ impl<'a> BackendDatabase<'a> {
 
         let stream = async_stream::stream! {
             let mut last_status = None;
+            let mut last_restart_count = 0;
             for row in result {
                 let state: Result<BackendState, _> = serde_json::from_value(row.state);
                 match state {
                     Ok(state) => {
                         yield BackendStatusStreamEntry::from_state(state.clone(), row.created_at);
                         last_status = Some(state.status());
+                        last_restart_count = last_restart_count.max(state.restart_count());
                     }
                     Err(e) => {
                         tracing::warn!(?e, "Invalid backend status");
@@ -87,9 +98,16 @@ impl<'a> BackendDatabase<'a> {
                 // In order to missing events that occur when we read the DB and when we subscribe to updates,
                 // we subscribe to updates before we read from the DB. But this means we might get duplicate
                 // events, so we keep track of the last status we saw and ignore events that have a status
-                // less than or equal to it.
+                // less than or equal to it. A restarted backend goes back to an earlier status, so
+                // those events are recognized by their higher restart count instead, and a resumed
+                // backend goes back from suspended to ready.
                 if let Some(last_status) = last_status {
-                    if state.status() <= last_status {
+                    let resumed = last_status == BackendStatus::Suspended
+                        && state.status() == BackendStatus::Ready;
+                    if state.status() <= last_status
+                        && state.restart_count() <= last_restart_count
+                        && !resumed
+                    {
                         continue;
                     }
                 }
@@ -98,6 +116,7 @@ impl<'a> BackendDatabase<'a> {
                 let item = BackendStatusStreamEntry::from_state(state.clone(), time);
 
                 last_status = Some(state.status());
+                last_restart_count = last_restart_count.max(state.restart_count());
 
                 yield item;
             }
@@ -140,7 +159,7 @@ impl<'a> BackendDatabase<'a> {
             last_keepalive: result.last_keepalive,
             state: serde_json::from_value(result.state)
                 .map_err(|_| sqlx::Error::Decode("Failed to decode backend state.".into()))?,
-            drone_id: NodeId::from(result.drone_id),
+            drone_id: result.drone_id.map(NodeId::from),
             expiration_time: result.expiration_time,
             allowed_idle_seconds: result.allowed_idle_seconds,
             as_of: result.as_of,
@@ -152,11 +171,16 @@ impl<'a> BackendDatabase<'a> {
         backend: &BackendName,
         new_state: BackendState,
     ) -> sqlx::Result<bool> {
+        let _timer = DATABASE_METRICS.query_timer("backend.update_state");
         let mut txn = self.db.pool.begin().await?;
 
         let new_status = new_state.status();
         let new_status_number = new_status.as_int();
 
+        // We read the previous status and key in the same statement, so that the state change
+        // event reflects exactly the transition made by this update.
+        // Statuses only move forward, except when the backend's process has been restarted,
+        // which starts it over from `waiting`, and when a suspended backend is resumed.
         let result = sqlx::query!(
             r#"
             update backend
@@ -166,8 +190,29 @@ impl<'a> BackendDatabase<'a> {
                 last_status_number = $3,
                 cluster_address = $4,
                 state = $5
-            where id = $1
-            and (last_status_number < $3 or last_status_number is null)
+            from (
+                select backend.id, backend.last_status, backend.created_at, drone.pool, backend_key.key_name, backend_key.namespace, backend_key.tag
+                from backend
+                left join backend_key on backend_key.id = backend.id
+                left join drone on drone.id = backend.drone_id
+                where backend.id = $1
+                for update of backend
+            ) as previous
+            where backend.id = previous.id
+            and (
+                backend.last_status_number < $3
+                or backend.last_status_number is null
+                or $6 > coalesce((backend.state->'restart'->>'restart_count')::integer, 0)
+                or (backend.last_status = $7 and $2 = $8)
+            )
+            returning
+                backend.cluster,
+                previous.last_status as "previous_status!",
+                previous.created_at as "created_at!",
+                previous.pool as "pool?",
+                previous.key_name as "key_name?",
+                previous.namespace as "namespace?",
+                previous.tag as "tag?"
             "#,
             backend.to_string(),
             new_status.to_string(),
@@ -175,11 +220,14 @@ impl<'a> BackendDatabase<'a> {
             new_state.address().map(|d| d.0.to_string()),
             serde_json::to_value(&new_state)
                 .expect("BackendState should always be JSON-serializable."),
+            new_state.restart_count() as i32,
+            BackendStatus::Suspended.to_string(),
+            BackendStatus::Ready.to_string(),
         )
-        .execute(&mut *txn)
+        .fetch_optional(&mut *txn)
         .await?;
 
-        if result.rows_affected() == 0 {
+        let Some(updated) = result else {
             let result = sqlx::query!(
                 r#"
                 select last_status
@@ -195,7 +243,7 @@ impl<'a> BackendDatabase<'a> {
 
             tracing::warn!(last_status, new_status=%new_status, backend=backend.as_value(), "Not updating backend status");
             return Ok(false);
-        }
+        };
 
         // If the backend is terminated, we can delete its associated key.
         if matches!(new_state, BackendState::Terminated { .. }) {
@@ -210,10 +258,38 @@ impl<'a> BackendDatabase<'a> {
             .await?;
         }
 
-        emit_state_change(&mut txn, backend, &new_state).await?;
+        let cluster = ClusterName::from_str(&updated.cluster)
+            .map_err(|_| sqlx::Error::Decode("Failed to decode cluster name.".into()))?;
+        let previous_status = BackendStatus::try_from(updated.previous_status)
+            .map_err(|_| sqlx::Error::Decode("Failed to decode backend status.".into()))?;
+        let key = updated.key_name.map(|name| KeyConfig {
+            name,
+            namespace: updated.namespace.unwrap_or_default(),
+            tag: updated.tag.unwrap_or_default(),
+        });
+        emit_state_change(
+            &mut txn,
+            backend,
+            &cluster,
+            key.as_ref(),
+            Some(previous_status),
+            &new_state,
+        )
+        .await?;
 
         txn.commit().await?;
 
+        // Status numbers only increase, so this is the first time the backend has become ready.
+        if new_status == BackendStatus::Ready {
+            let spawn_to_ready = (Utc::now() - updated.created_at)
+                .to_std()
+                .unwrap_or_default();
+            DATABASE_METRICS
+                .spawn_to_ready_seconds
+                .with_label_values(&[updated.pool.as_deref().unwrap_or_default()])
+                .observe(spawn_to_ready.as_secs_f64());
+        }
+
         Ok(true)
     }
 
@@ -248,7 +324,7 @@ impl<'a> BackendDatabase<'a> {
                 state: serde_json::from_value(row.state)
                     .map_err(|_| sqlx::Error::Decode("Failed to decode backend state.".into()))?,
                 last_keepalive: row.last_keepalive,
-                drone_id: NodeId::from(row.drone_id),
+                drone_id: row.drone_id.map(NodeId::from),
                 expiration_time: row.expiration_time,
                 allowed_idle_seconds: row.allowed_idle_seconds,
                 as_of: row.as_of,
@@ -258,6 +334,99 @@ impl<'a> BackendDatabase<'a> {
         Ok(result)
     }
 
+    /// Returns one page of backends matching the given filters, ordered by creation time.
+    /// Returns up to `limit` backends, and a cursor for the next page if there are more.
+    pub async fn search_backends(
+        &self,
+        query: &BackendListQuery,
+        cursor: Option<&BackendListCursor>,
+        limit: i64,
+    ) -> sqlx::Result<(Vec<BackendListEntry>, Option<BackendListCursor>)> {
+        let query_result = sqlx::query!(
+            r#"
+            select
+                backend.id,
+                backend.cluster,
+                backend.state,
+                backend.created_at,
+                backend.last_status_time,
+                backend.last_keepalive,
+                backend.expiration_time,
+                backend.key_namespace,
+                node.name as "drone_name?",
+                drone.pool as "pool?"
+            from backend
+            left join node on node.id = backend.drone_id
+            left join drone on drone.id = backend.drone_id
+            where
+                ($1::varchar is null or backend.cluster = $1)
+                and ($2::varchar is null or node.name = $2)
+                and ($3::varchar is null or drone.pool = $3)
+                and ($4::integer is null or backend.last_status_number >= $4)
+                and ($5::integer is null or backend.last_status_number <= $5)
+                and ($6::varchar is null or backend.key_namespace = $6)
+                and ($7::timestamptz is null or backend.created_at >= $7)
+                and ($8::timestamptz is null or backend.created_at < $8)
+                and ($9::timestamptz is null or (backend.created_at, backend.id) > ($9, $10::varchar))
+            order by backend.created_at, backend.id
+            limit $11
+            "#,
+            query.cluster.as_ref().map(|c| c.to_string()),
+            query.drone.as_ref().map(|d| d.to_string()),
+            query.pool.as_ref().map(|p| p.to_string()),
+            query.min_status.map(|s| s.as_int()),
+            query.max_status.map(|s| s.as_int()),
+            query.key_namespace,
+            query.created_after,
+            query.created_before,
+            cursor.map(|c| c.created_at),
+            cursor.map(|c| c.id.to_string()),
+            // Fetch one extra row to determine whether there is another page.
+            limit + 1,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let has_more = query_result.len() as i64 > limit;
+
+        let mut result = Vec::new();
+        for row in query_result.into_iter().take(limit as usize) {
+            let state: BackendState = serde_json::from_value(row.state)
+                .map_err(|_| sqlx::Error::Decode("Failed to decode backend state.".into()))?;
+
+            result.push(BackendListEntry {
+                id: BackendName::try_from(row.id)
+                    .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?,
+                cluster: ClusterName::from_str(&row.cluster)
+                    .map_err(|_| sqlx::Error::Decode("Failed to decode cluster name.".into()))?,
+                drone: row
+                    .drone_name
+                    .map(DroneName::try_from)
+                    .transpose()
+                    .map_err(|_| sqlx::Error::Decode("Failed to decode drone name.".into()))?,
+                pool: row.pool.map(DronePoolName::from),
+                key_namespace: row.key_namespace,
+                status: state.status(),
+                state,
+                created_at: row.created_at,
+                last_status_time: row.last_status_time,
+                last_keepalive: row.last_keepalive,
+                expiration_time: row.expiration_time,
+            });
+        }
+
+        let next_cursor = if has_more {
+            result.last().map(|entry| BackendListCursor {
+                created_at: entry.created_at,
+                id: entry.id.clone(),
+            })
+        } else {
+            None
+        };
+
+        Ok((result, next_cursor))
+    }
+
     pub async fn list_alive_backends_for_drone(
         &self,
         cluster: &ClusterName,
@@ -298,7 +467,7 @@ impl<'a> BackendDatabase<'a> {
                 state: serde_json::from_value(row.state)
                     .map_err(|_| sqlx::Error::Decode("Failed to decode backend state.".into()))?,
                 last_keepalive: row.last_keepalive,
-                drone_id: NodeId::from(row.drone_id),
+                drone_id: row.drone_id.map(NodeId::from),
                 expiration_time: row.expiration_time,
                 allowed_idle_seconds: row.allowed_idle_seconds,
                 as_of: row.as_of,
@@ -319,7 +488,8 @@ impl<'a> BackendDatabase<'a> {
                 cluster,
                 last_status,
                 cluster_address,
-                subdomain
+                subdomain,
+                state
             from backend
             where backend.static_token = $1
             limit 1
@@ -343,14 +513,18 @@ impl<'a> BackendDatabase<'a> {
 
         let backend_id = BackendName::try_from(result.id)
             .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?;
+        let status = BackendStatus::try_from(result.last_status)
+            .map_err(|_| sqlx::Error::Decode("Failed to decode backend status.".into()))?;
 
         let partial = PartialRouteInfo {
             backend_id: backend_id.clone(),
+            status,
             secret_token: SecretToken::from("".to_string()),
             cluster: ClusterName::from_str(&result.cluster)
                 .map_err(|_| sqlx::Error::Decode("Failed to decode cluster name.".into()))?,
             user: None,
             user_data: None,
+            expiration_time: None,
             subdomain: result
                 .subdomain
                 .map(Subdomain::try_from)
@@ -372,12 +546,17 @@ impl<'a> BackendDatabase<'a> {
             return Ok(RouteInfoResult::NotFound);
         };
 
-        Ok(RouteInfoResult::Available(
-            partial.set_address(BackendAddr(address)),
-        ))
+        let state: BackendState = serde_json::from_value(result.state)
+            .map_err(|_| sqlx::Error::Decode("Failed to decode backend state.".into()))?;
+
+        Ok(RouteInfoResult::Available(partial.set_address(
+            BackendAddr(address),
+            state.named_addresses(),
+        )))
     }
 
     pub async fn route_info_for_token(&self, token: &BearerToken) -> sqlx::Result<RouteInfoResult> {
+        let _timer = DATABASE_METRICS.query_timer("backend.route_info_for_token");
         if token.is_static() {
             return self.route_info_for_static_token(token).await;
         }
@@ -392,11 +571,14 @@ impl<'a> BackendDatabase<'a> {
                 last_status,
                 cluster_address,
                 secret_token,
-                subdomain
+                subdomain,
+                backend.state,
+                token.expiration_time
             from token
             inner join backend
             on backend.id = token.backend_id
             where token = $1
+            and token.expiration_time > now()
             limit 1
             "#,
             token.to_string(),
@@ -418,13 +600,18 @@ impl<'a> BackendDatabase<'a> {
 
         let backend_id = BackendName::try_from(result.backend_id)
             .map_err(|_| sqlx::Error::Decode("Failed to decode backend name.".into()))?;
+        let status = BackendStatus::try_from(result.last_status)
+            .map_err(|_| sqlx::Error::Decode("Failed to decode backend status.".into()))?;
+
         let partial = PartialRouteInfo {
             backend_id: backend_id.clone(),
+            status,
             secret_token: SecretToken::from(result.secret_token),
             cluster: ClusterName::from_str(&result.cluster)
                 .map_err(|_| sqlx::Error::Decode("Failed to decode cluster name.".into()))?,
             user: result.username,
             user_data: Some(result.auth),
+            expiration_time: Some(result.expiration_time),
             subdomain: result
                 .subdomain
                 .map(Subdomain::try_from)
@@ -446,9 +633,13 @@ impl<'a> BackendDatabase<'a> {
             return Ok(RouteInfoResult::NotFound);
         };
 
-        Ok(RouteInfoResult::Available(
-            partial.set_address(BackendAddr(address)),
-        ))
+        let state: BackendState = serde_json::from_value(result.state)
+            .map_err(|_| sqlx::Error::Decode("Failed to decode backend state.".into()))?;
+
+        Ok(RouteInfoResult::Available(partial.set_address(
+            BackendAddr(address),
+            state.named_addresses(),
+        )))
     }
 
     pub async fn update_keepalive(&self, backend_id: &BackendName) -> sqlx::Result<bool> {
@@ -471,7 +662,85 @@ impl<'a> BackendDatabase<'a> {
         Ok(true)
     }
 
+    /// Replaces the lifetime and idle limits of a backend that has not terminated. The new
+    /// lifetime limit counts from now. Returns None if the backend does not exist or has
+    /// terminated.
+    ///
+    /// The limits are enforced by `termination_candidates`, which reads them from the
+    /// backend row on each sweep, so no message to the drone is needed.
+    pub async fn update_limits(
+        &self,
+        backend_id: &BackendName,
+        request: &BackendLimitsRequest,
+    ) -> sqlx::Result<Option<BackendLimitsResponse>> {
+        let _timer = DATABASE_METRICS.query_timer("backend.update_limits");
+        let result = sqlx::query!(
+            r#"
+            update backend
+            set
+                expiration_time = coalesce(now() + $2, expiration_time),
+                allowed_idle_seconds = coalesce($3, allowed_idle_seconds)
+            where id = $1 and last_status != $4
+            returning expiration_time, allowed_idle_seconds
+            "#,
+            backend_id.to_string(),
+            request
+                .lifetime_limit_seconds
+                .map(
+                    |limit| PgInterval::try_from(Duration::from_secs(limit as _))
+                        .expect("valid interval")
+                ),
+            request.max_idle_seconds,
+            BackendStatus::Terminated.to_string(),
+        )
+        .fetch_optional(&self.db.pool)
+        .await?;
+
+        Ok(result.map(|row| BackendLimitsResponse {
+            expiration_time: row.expiration_time,
+            max_idle_seconds: row.allowed_idle_seconds,
+        }))
+    }
+
+    /// Sends a resume action to the drone of a suspended backend, and resets its idle timer
+    /// so that it is not suspended again right away. Returns false if the backend is not
+    /// suspended.
+    pub async fn resume(&self, backend_id: &BackendName) -> sqlx::Result<bool> {
+        let _timer = DATABASE_METRICS.query_timer("backend.resume");
+        let mut txn = self.db.pool.begin().await?;
+
+        let drone_id = sqlx::query_scalar!(
+            r#"
+            update backend
+            set last_keepalive = now()
+            where id = $1 and last_status = $2
+            returning drone_id
+            "#,
+            backend_id.to_string(),
+            BackendStatus::Suspended.to_string(),
+        )
+        .fetch_optional(&mut *txn)
+        .await?;
+
+        let Some(Some(drone_id)) = drone_id else {
+            return Ok(false);
+        };
+
+        create_pending_action(
+            &mut txn,
+            backend_id,
+            NodeId::from(drone_id),
+            &BackendAction::Resume,
+        )
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(true)
+    }
+
     pub async fn publish_metrics(&self, metrics: BackendMetricsMessage) -> sqlx::Result<()> {
+        let _timer = DATABASE_METRICS.query_timer("backend.publish_metrics");
         let mut txn = self.db.pool.begin().await?;
         emit_backend_metrics(&mut txn, &metrics.backend_id.to_string(), &metrics).await?;
         txn.commit().await?;
@@ -482,6 +751,7 @@ impl<'a> BackendDatabase<'a> {
         &self,
         drone_id: NodeId,
     ) -> sqlx::Result<Vec<TerminationCandidate>> {
+        let _timer = DATABASE_METRICS.query_timer("backend.termination_candidates");
         let result = sqlx::query!(
             r#"
             select
@@ -489,19 +759,29 @@ impl<'a> BackendDatabase<'a> {
                 expiration_time,
                 allowed_idle_seconds,
                 last_keepalive,
+                (
+                    suspend_when_idle
+                    and last_status = $4
+                    and (expiration_time is null or now() <= expiration_time)
+                ) as "suspend!",
                 now() as "as_of!"
             from backend
             where
                 drone_id = $1
                 and last_status not in ($2, $3)
                 and (
-                    now() - last_keepalive > make_interval(secs => allowed_idle_seconds)
+                    (
+                        now() - last_keepalive > make_interval(secs => allowed_idle_seconds)
+                        and not (suspend_when_idle and last_status = $5)
+                    )
                     or now() > expiration_time
                 )
             "#,
             drone_id.as_i32(),
             BackendStatus::Scheduled.to_string(),
             BackendStatus::Terminated.to_string(),
+            BackendStatus::Ready.to_string(),
+            BackendStatus::Suspended.to_string(),
         )
         .fetch_all(&self.db.pool)
         .await?;
@@ -514,6 +794,7 @@ impl<'a> BackendDatabase<'a> {
                 expiration_time: row.expiration_time,
                 last_keepalive: row.last_keepalive,
                 allowed_idle_seconds: row.allowed_idle_seconds,
+                suspend: row.suspend,
                 as_of: row.as_of,
             });
         }
@@ -587,6 +868,17 @@ impl<'a> BackendDatabase<'a> {
 
         let backend_state_deleted = backend_state_result.rows_affected();
 
+        let backend_log_result = sqlx::query(
+            r#"
+            delete from backend_log
+            where backend_log.backend_id in (select id from deleted_backend);
+            "#,
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        let backend_log_deleted = backend_log_result.rows_affected();
+
         let backend_result = sqlx::query(
             r#"
             delete from backend
@@ -598,14 +890,47 @@ impl<'a> BackendDatabase<'a> {
 
         let backend_deleted = backend_result.rows_affected();
 
+        // Spawn configs of keys that have not been spawned for as long as terminated backends
+        // are kept, and are not held by a backend.
+        let key_spawn_config_result = sqlx::query(
+            r#"
+            delete from key_spawn_config
+            where now() - updated_at > make_interval(days => $1)
+            and not exists (
+                select 1 from backend_key
+                where backend_key.namespace = key_spawn_config.namespace
+                and backend_key.key_name = key_spawn_config.key_name
+            );
+            "#,
+        )
+        .bind(min_age_days)
+        .execute(&mut *txn)
+        .await?;
+
+        let key_spawn_config_deleted = key_spawn_config_result.rows_affected();
+
         txn.commit().await?;
 
+        for (table, count) in [
+            ("token", token_deleted),
+            ("backend_action", backend_action_deleted),
+            ("backend_key", backend_key_deleted),
+            ("backend_state", backend_state_deleted),
+            ("backend_log", backend_log_deleted),
+            ("backend", backend_deleted),
+            ("key_spawn_config", key_spawn_config_deleted),
+        ] {
+            DATABASE_METRICS.record_rows_deleted(table, count);
+        }
+
         tracing::info!(
             token_deleted,
             backend_action_deleted,
             backend_state_deleted,
+            backend_log_deleted,
             backend_deleted,
             backend_key_deleted,
+            key_spawn_config_deleted,
             "Finished cleanup."
         );
 
@@ -619,16 +944,41 @@ pub struct TerminationCandidate {
     pub expiration_time: Option<DateTime<Utc>>,
     pub last_keepalive: DateTime<Utc>,
     pub allowed_idle_seconds: Option<i32>,
+    /// True if the backend is idle and should be suspended rather than terminated.
+    pub suspend: bool,
     pub as_of: DateTime<Utc>,
 }
 
+/// Position of the last backend returned in a page of backend listing results.
+/// Encoded for clients as an opaque string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendListCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: BackendName,
+}
+
+impl BackendListCursor {
+    pub fn encode(&self) -> String {
+        format!("{}.{}", self.created_at.timestamp_micros(), self.id)
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let (created_at, id) = cursor.split_once('.')?;
+        let created_at = DateTime::from_timestamp_micros(created_at.parse().ok()?)?;
+        let id = BackendName::try_from(id.to_string()).ok()?;
+
+        Some(Self { created_at, id })
+    }
+}
+
 pub struct BackendRow {
     pub id: BackendName,
     pub cluster: String,
     pub last_status_time: DateTime<Utc>,
     pub state: BackendState,
     pub last_keepalive: DateTime<Utc>,
-    pub drone_id: NodeId,
+    /// The drone the backend is assigned to, or None while the backend is queued.
+    pub drone_id: Option<NodeId>,
     pub expiration_time: Option<DateTime<Utc>>,
     pub allowed_idle_seconds: Option<i32>,
     pub as_of: DateTime<Utc>,
@@ -645,6 +995,9 @@ impl BackendRow {
 pub async fn emit_state_change(
     txn: &mut PgConnection,
     backend: &BackendName,
+    cluster: &ClusterName,
+    key: Option<&KeyConfig>,
+    previous_status: Option<BackendStatus>,
     new_state: &BackendState,
 ) -> sqlx::Result<()> {
     sqlx::query!(
@@ -659,6 +1012,17 @@ pub async fn emit_state_change(
     .await?;
 
     emit_with_key(txn, &backend.to_string(), new_state).await?;
+    emit_with_key(
+        txn,
+        &cluster.to_string(),
+        &ClusterEvent::BackendStateChange {
+            backend: backend.clone(),
+            key: key.cloned(),
+            previous_status,
+            state: new_state.clone(),
+        },
+    )
+    .await?;
 
     Ok(())
 }
@@ -677,23 +1041,33 @@ pub enum RouteInfoResult {
 #[derive(Debug)]
 pub struct PartialRouteInfo {
     pub backend_id: BackendName,
+    /// The backend's last status, which is one of the starting statuses, `suspended`, or
+    /// `restarting` if the route is pending.
+    pub status: BackendStatus,
     secret_token: SecretToken,
     cluster: ClusterName,
     user: Option<String>,
     user_data: Option<serde_json::Value>,
+    expiration_time: Option<DateTime<Utc>>,
     subdomain: Option<Subdomain>,
 }
 
 impl PartialRouteInfo {
-    pub fn set_address(self, address: BackendAddr) -> RouteInfo {
+    pub fn set_address(
+        self,
+        address: BackendAddr,
+        named_addresses: NamedBackendAddrs,
+    ) -> RouteInfo {
         RouteInfo {
             backend_id: self.backend_id,
             address,
+            named_addresses,
             secret_token: self.secret_token,
             user: self.user,
             user_data: self.user_data,
             cluster: self.cluster,
             subdomain: self.subdomain,
+            expiration_time: self.expiration_time,
         }
     }
 }
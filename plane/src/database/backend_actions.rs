@@ -1,3 +1,4 @@
+use super::metrics::DATABASE_METRICS;
 use super::{connect::ConnectError, subscribe::emit_with_key};
 use plane_common::{
     names::{BackendActionName, BackendName, Name},
@@ -21,6 +22,7 @@ impl BackendActionDatabase {
         notification_id: &BackendActionName,
         drone_id: NodeId,
     ) -> anyhow::Result<()> {
+        let _timer = DATABASE_METRICS.query_timer("backend_action.ack_pending_action");
         sqlx::query!(
             r#"
             update "backend_action"
@@ -42,6 +44,7 @@ impl BackendActionDatabase {
         drone: NodeId,
         limit: i64,
     ) -> anyhow::Result<Vec<BackendActionMessage>> {
+        let _timer = DATABASE_METRICS.query_timer("backend_action.pending_actions");
         let rows = sqlx::query!(
             r#"
             select "action"
@@ -73,6 +76,7 @@ impl BackendActionDatabase {
         drone_id: NodeId,
         action: &BackendAction,
     ) -> Result<(), ConnectError> {
+        let _timer = DATABASE_METRICS.query_timer("backend_action.create_pending_action");
         let mut txn = self.pool.begin().await?;
         create_pending_action(&mut txn, backend_id, drone_id, action).await?;
         txn.commit().await?;
@@ -85,7 +89,7 @@ pub async fn create_pending_action(
     backend_id: &BackendName,
     drone_id: NodeId,
     action: &BackendAction,
-) -> Result<(), ConnectError> {
+) -> sqlx::Result<()> {
     let action_id = BackendActionName::new_random();
 
     let backend_action = BackendActionMessage {
@@ -7,10 +7,11 @@
 //! assert!(KEY_LEASE_EXPIRATION > KEY_LEASE_HARD_TERMINATE_AFTER);
 //! ```
 
+use super::metrics::DATABASE_METRICS;
 use chrono::{DateTime, Utc};
 use plane_common::{
     names::BackendName,
-    types::{BackendStatus, BearerToken, ClusterName, KeyConfig, Subdomain},
+    types::{BackendStatus, BearerToken, ClusterName, KeyConfig, SpawnConfig, Subdomain},
 };
 use sqlx::{postgres::types::PgInterval, PgPool};
 use std::{str::FromStr, time::Duration};
@@ -64,6 +65,7 @@ impl<'a> KeysDatabase<'a> {
     }
 
     pub async fn renew_key(&self, id: &BackendName) -> Result<(), sqlx::Error> {
+        let _timer = DATABASE_METRICS.query_timer("backend_key.renew_key");
         let result = sqlx::query!(
             r#"
             update backend_key
@@ -85,6 +87,55 @@ impl<'a> KeysDatabase<'a> {
         Ok(())
     }
 
+    /// Saves the spawn config used to spawn a backend for the key, so that a later connect
+    /// request for the key can respawn a backend without one.
+    pub async fn save_spawn_config(
+        &self,
+        key: &KeyConfig,
+        spawn_config: &SpawnConfig,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            r#"
+            insert into key_spawn_config (namespace, key_name, tag, spawn_config)
+            values ($1, $2, $3, $4)
+            on conflict (namespace, key_name) do update set
+                tag = excluded.tag,
+                spawn_config = excluded.spawn_config,
+                updated_at = now()
+            "#,
+            key.namespace,
+            key.name,
+            key.tag,
+            serde_json::to_value(spawn_config).expect("Spawn config is always serializable"),
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the spawn config last used to spawn a backend for the key, if the key's tag
+    /// matches.
+    pub async fn spawn_config(&self, key: &KeyConfig) -> Result<Option<SpawnConfig>, sqlx::Error> {
+        let result = sqlx::query!(
+            r#"
+            select spawn_config
+            from key_spawn_config
+            where namespace = $1 and key_name = $2 and tag = $3
+            "#,
+            key.namespace,
+            key.name,
+            key.tag,
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        result
+            .map(|row| serde_json::from_value(row.spawn_config))
+            .transpose()
+            .map_err(|e| sqlx::Error::Decode(e.into()))
+    }
+
     /// Checks if the key is held.
     pub async fn check_key(
         &self,
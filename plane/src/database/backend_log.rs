@@ -0,0 +1,228 @@
+use super::metrics::DATABASE_METRICS;
+use super::{
+    subscribe::{emit_ephemeral_with_key, NotificationPayload},
+    PlaneDatabase,
+};
+use futures_util::Stream;
+use plane_common::{
+    names::BackendName,
+    protocol::BackendLogMessage,
+    types::{BackendLogEntry, LogStream},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Number of lines kept for each backend. Older lines are deleted as new lines arrive.
+pub const BACKEND_LOG_BUFFER_LINES: u32 = 1_000;
+
+/// A buffered log line, with the id that orders it among the backend's lines.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackendLogRecord {
+    pub id: i64,
+    pub entry: BackendLogEntry,
+}
+
+/// Sent to followers of a backend's logs when lines are added to its buffer. Followers read
+/// the new lines from the buffer, so that the notification stays small however long the
+/// lines are.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackendLogsAppended {
+    /// The id of the last line added.
+    pub last_id: i64,
+}
+
+impl NotificationPayload for BackendLogsAppended {
+    fn kind() -> &'static str {
+        "backend_logs_appended"
+    }
+}
+
+pub struct BackendLogDatabase<'a> {
+    db: &'a PlaneDatabase,
+}
+
+impl<'a> BackendLogDatabase<'a> {
+    pub fn new(db: &'a PlaneDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Adds lines to their backends' buffers, dropping each buffer's oldest lines if it is
+    /// full, and notifies anyone following the backends' logs once per backend.
+    pub async fn append(&self, messages: &[BackendLogMessage]) -> sqlx::Result<()> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let _timer = DATABASE_METRICS.query_timer("backend_log.append");
+        let mut backend_ids = Vec::with_capacity(messages.len());
+        let mut streams = Vec::with_capacity(messages.len());
+        let mut timestamps = Vec::with_capacity(messages.len());
+        let mut lines = Vec::with_capacity(messages.len());
+        for message in messages {
+            backend_ids.push(message.backend_id.to_string());
+            streams.push(message.entry.stream.to_string());
+            timestamps.push(message.entry.timestamp);
+            lines.push(message.entry.text.clone());
+        }
+
+        let mut txn = self.db.pool.begin().await?;
+
+        // Lines are inserted in the order they were written, so that their ids order them.
+        // Lines of backends that no longer exist, because they were cleaned up after
+        // terminating, are skipped rather than failing the whole batch.
+        let rows = sqlx::query!(
+            r#"
+            insert into backend_log (backend_id, stream, logged_at, line)
+            select backend_id, stream, logged_at, line
+            from unnest($1::varchar[], $2::varchar[], $3::timestamptz[], $4::text[])
+                with ordinality as lines(backend_id, stream, logged_at, line, position)
+            where exists (select 1 from backend where backend.id = lines.backend_id)
+            order by position
+            returning backend_id, id
+            "#,
+            &backend_ids,
+            &streams,
+            &timestamps,
+            &lines,
+        )
+        .fetch_all(&mut *txn)
+        .await?;
+
+        let mut last_ids: BTreeMap<String, i64> = BTreeMap::new();
+        for row in rows {
+            let last_id = last_ids.entry(row.backend_id).or_insert(row.id);
+            *last_id = (*last_id).max(row.id);
+        }
+        let appended_backend_ids: Vec<String> = last_ids.keys().cloned().collect();
+
+        sqlx::query!(
+            r#"
+            delete from backend_log
+            using (
+                select
+                    backend_id,
+                    (
+                        select id from backend_log
+                        where backend_log.backend_id = appended.backend_id
+                        order by id desc
+                        offset $2
+                        limit 1
+                    ) as cutoff
+                from unnest($1::varchar[]) as appended(backend_id)
+            ) as trim
+            where backend_log.backend_id = trim.backend_id
+            and backend_log.id <= trim.cutoff
+            "#,
+            &appended_backend_ids,
+            BACKEND_LOG_BUFFER_LINES as i64,
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        for (backend_id, last_id) in last_ids {
+            emit_ephemeral_with_key(&mut txn, &backend_id, &BackendLogsAppended { last_id })
+                .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+
+    /// Returns the backend's most recent buffered lines, oldest first. If `tail` is provided,
+    /// at most that many lines are returned. If `after` is provided, only lines with a greater
+    /// id are returned.
+    pub async fn recent(
+        &self,
+        backend_id: &BackendName,
+        tail: Option<u32>,
+        after: Option<i64>,
+    ) -> sqlx::Result<Vec<BackendLogRecord>> {
+        let limit = tail
+            .unwrap_or(BACKEND_LOG_BUFFER_LINES)
+            .min(BACKEND_LOG_BUFFER_LINES);
+
+        let rows = sqlx::query!(
+            r#"
+            select id, stream, logged_at, line
+            from (
+                select id, stream, logged_at, line
+                from backend_log
+                where backend_id = $1 and id > $2
+                order by id desc
+                limit $3
+            ) as recent
+            order by id asc
+            "#,
+            backend_id.to_string(),
+            after.unwrap_or(0),
+            limit as i64,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let stream = LogStream::from_str(&row.stream)
+                    .map_err(|err| sqlx::Error::Decode(err.into()))?;
+                Ok(BackendLogRecord {
+                    id: row.id,
+                    entry: BackendLogEntry {
+                        timestamp: row.logged_at,
+                        stream,
+                        text: row.line,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Like `recent`, but continues with new lines as they are written.
+    pub async fn follow(
+        &self,
+        backend_id: &BackendName,
+        tail: Option<u32>,
+        after: Option<i64>,
+    ) -> sqlx::Result<impl Stream<Item = BackendLogRecord>> {
+        // We subscribe before reading the buffer, so that no lines are missed in between.
+        let mut sub = self
+            .db
+            .subscribe_with_key::<BackendLogsAppended>(&backend_id.to_string());
+
+        let recent = self.recent(backend_id, tail, after).await?;
+        let db = self.db.clone();
+        let backend_id = backend_id.clone();
+
+        let stream = async_stream::stream! {
+            let mut last_id = after;
+            for record in recent {
+                last_id = Some(record.id);
+                yield record;
+            }
+
+            while let Some(item) = sub.next().await {
+                if let Some(last_id) = last_id {
+                    if item.payload.last_id <= last_id {
+                        // Already sent.
+                        continue;
+                    }
+                }
+
+                let records = match db.backend_logs().recent(&backend_id, None, last_id).await {
+                    Ok(records) => records,
+                    Err(err) => {
+                        tracing::error!(?err, "Error reading backend log lines.");
+                        break;
+                    }
+                };
+
+                for record in records {
+                    last_id = Some(record.id);
+                    yield record;
+                }
+            }
+        };
+
+        Ok(stream)
+    }
+}
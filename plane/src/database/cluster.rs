@@ -26,6 +26,7 @@ impl<'a> ClusterDatabase<'a> {
                 drone.ready as "ready?",
                 drone.draining as "draining?",
                 drone.last_heartbeat as "last_drone_heartbeat",
+                drone.max_backends as "max_backends?",
                 controller.last_heartbeat as "last_controller_heartbeat!",
                 now() as "as_of!",
                 (
@@ -75,6 +76,7 @@ impl<'a> ClusterDatabase<'a> {
                         backend_count: node.backend_count.ok_or_else(|| {
                             sqlx::Error::Decode("Drone should have backend_count column.".into())
                         })? as u32,
+                        max_backends: node.max_backends.map(|max_backends| max_backends as u32),
                         last_heartbeat_age: node.as_of
                             - node.last_drone_heartbeat.ok_or_else(|| {
                                 sqlx::Error::Decode(
@@ -1,3 +1,4 @@
+use super::metrics::DATABASE_METRICS;
 use crate::database::{
     backend_key::{KeysDatabase, KEY_LEASE_EXPIRATION},
     drone::DroneDatabase,
@@ -7,15 +8,19 @@ use super::{
     backend::emit_state_change,
     backend_actions::create_pending_action,
     backend_key::{KEY_LEASE_RENEW_AFTER, KEY_LEASE_SOFT_TERMINATE_AFTER},
-    drone::DroneForSpawn,
+    drone::{DroneForSpawn, DronePick},
+    subscribe::{emit_with_key, NotificationPayload},
+    util::MapSqlxError,
 };
+use chrono::{DateTime, Utc};
 use plane_common::{
     log_types::LoggableTime,
-    names::{BackendName, OrRandom},
-    protocol::{AcquiredKey, BackendAction, KeyDeadlines},
+    names::{BackendName, DroneName, OrRandom, WarmPoolName},
+    protocol::{AcquiredKey, BackendAction, KeyDeadlines, TokensRevoked},
     types::{
         BackendState, BackendStatus, BearerToken, ClusterName, ConnectRequest, ConnectResponse,
-        KeyConfig, RevokeRequest, SecretToken, SpawnConfig,
+        DronePoolName, IdleAction, KeyConfig, LabelSelector, RestartPolicy, RevokeRequest,
+        SecretToken, SpawnConfig, TokenRefreshRequest,
     },
     util::random_token,
     PlaneClient,
@@ -27,6 +32,13 @@ use valuable::Valuable;
 
 const TOKEN_LIFETIME_SECONDS: u64 = 3600;
 
+/// Longest lifetime a token can be given when it is created or refreshed (30 days).
+const MAX_TOKEN_LIFETIME_SECONDS: u64 = 30 * 24 * 3600;
+
+/// Key namespace of warm pool backends that have not yet been claimed. Each one holds a
+/// random key in this namespace until a connect request claims it.
+const WARM_POOL_KEY_NAMESPACE: &str = "plane.warm-pool";
+
 /// Unique violation error code in Postgres.
 /// NOTE: typically we should use "on conflict do nothing", but that only
 /// works with insert queries, not update queries.
@@ -48,6 +60,14 @@ pub enum ConnectError {
     #[error("No active drone available.")]
     NoDroneAvailable,
 
+    #[error("Every drone that could run the backend is at capacity.")]
+    ClusterAtCapacity,
+
+    #[error(
+        "The drone picked for the backend reached its capacity before the backend was created."
+    )]
+    DroneAtCapacity,
+
     #[error("Key held and tag does not match. {request_tag:?} != {key_tag:?}")]
     KeyHeld {
         request_tag: String,
@@ -75,6 +95,12 @@ pub enum ConnectError {
     #[error("No cluster provided, and no default cluster for this controller.")]
     NoClusterProvided,
 
+    #[error("Invalid token lifetime: {0} seconds.")]
+    InvalidTokenLifetime(u64),
+
+    #[error("The backend would be in cluster {0}, which the request may not act on.")]
+    ClusterNotAllowed(ClusterName),
+
     #[error("Other internal error. {0}")]
     Other(String),
 }
@@ -84,14 +110,128 @@ impl ConnectError {
     fn retryable(&self) -> bool {
         matches!(
             self,
-            ConnectError::FailedToRemoveKey | ConnectError::FailedToAcquireKey
+            ConnectError::FailedToRemoveKey
+                | ConnectError::FailedToAcquireKey
+                | ConnectError::DroneAtCapacity
         )
     }
 }
 
+/// Locks the drone's row, so that concurrent spawns on the same drone are serialized, and
+/// returns Err(ConnectError::DroneAtCapacity) if the drone is already running as many
+/// backends as it allows.
+pub(super) async fn check_drone_capacity(
+    txn: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    drone_for_spawn: &DroneForSpawn,
+) -> Result<()> {
+    let max_backends = sqlx::query_scalar!(
+        r#"
+        select max_backends
+        from drone
+        where id = $1
+        for update
+        "#,
+        drone_for_spawn.id.as_i32(),
+    )
+    .fetch_one(&mut **txn)
+    .await?;
+
+    if let Some(max_backends) = max_backends {
+        // This is a separate statement from the lock above, so that it sees backends created
+        // by any transaction we waited on for the lock.
+        let backend_count = sqlx::query_scalar!(
+            r#"
+            select count(1) as "count!"
+            from backend
+            where drone_id = $1
+            and last_status != $2
+            "#,
+            drone_for_spawn.id.as_i32(),
+            BackendStatus::Terminated.to_string(),
+        )
+        .fetch_one(&mut **txn)
+        .await?;
+
+        if backend_count >= max_backends as i64 {
+            return Err(ConnectError::DroneAtCapacity);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the action that tells a drone to spawn a backend that holds the given key.
+pub(super) fn spawn_action(
+    key: &KeyConfig,
+    fencing_token: i64,
+    drone_for_spawn: &DroneForSpawn,
+    spawn_config: &SpawnConfig,
+    static_token: Option<&BearerToken>,
+) -> BackendAction {
+    let acquired_key = AcquiredKey {
+        key: key.clone(),
+        deadlines: KeyDeadlines {
+            renew_at: LoggableTime(drone_for_spawn.last_local_time + KEY_LEASE_RENEW_AFTER),
+            soft_terminate_at: LoggableTime(
+                drone_for_spawn.last_local_time + KEY_LEASE_SOFT_TERMINATE_AFTER,
+            ),
+            hard_terminate_at: LoggableTime(
+                drone_for_spawn.last_local_time + KEY_LEASE_SOFT_TERMINATE_AFTER,
+            ),
+        },
+        token: fencing_token,
+    };
+
+    BackendAction::Spawn {
+        executable: spawn_config.executable.clone(),
+        key: acquired_key,
+        static_token: static_token.cloned(),
+        restart_policy: spawn_config.restart_policy.clone(),
+    }
+}
+
+/// Picks a drone for the backend, trying the spawn config's pools in order. Returns the pool
+/// and drone picked, if any, and whether any pool had drones that were all at capacity.
+pub(super) async fn pick_placement<'a>(
+    pool: &PgPool,
+    cluster: &ClusterName,
+    spawn_config: &'a SpawnConfig,
+) -> Result<(Option<(&'a DronePoolName, DroneForSpawn)>, bool)> {
+    let mut placement = None;
+    let mut at_capacity = false;
+    for drone_pool in spawn_config.pools() {
+        let pick = DroneDatabase::new(pool)
+            .pick_drone_for_spawn(
+                cluster,
+                drone_pool,
+                &spawn_config.label_selector,
+                &spawn_config.resource_limits(),
+            )
+            .await?;
+
+        match pick {
+            DronePick::Drone(drone) => {
+                placement = Some((drone_pool, drone));
+                break;
+            }
+            DronePick::AtCapacity => {
+                tracing::info!(pool = %drone_pool, "Every drone in pool is at capacity.");
+                at_capacity = true;
+            }
+            DronePick::NoDrone => {
+                tracing::info!(pool = %drone_pool, "No drone available in pool.");
+            }
+        }
+    }
+
+    Ok((placement, at_capacity))
+}
+
 /// Attempts to create a new backend that owns the given key. If the key is already held, returns
 /// Err(ConnectError::FailedToAcquireKey). If the key is not held, creates a new backend and
 /// returns Ok(backend_id).
+///
+/// If `warm_pool` is provided, the backend is created as an unclaimed member of that warm pool.
 async fn create_backend_with_key(
     pool: &PgPool,
     key: &KeyConfig,
@@ -99,12 +239,16 @@ async fn create_backend_with_key(
     cluster: &ClusterName,
     drone_for_spawn: &DroneForSpawn,
     static_token: Option<&BearerToken>,
+    warm_pool: Option<&WarmPoolName>,
 ) -> Result<BackendName> {
     let backend_id = spawn_config.id.clone().or_random();
     let mut txn = pool.begin().await?;
 
+    check_drone_capacity(&mut txn, drone_for_spawn).await?;
+
     let initial_status = BackendStatus::Scheduled;
     let initial_state = BackendState::Scheduled;
+    let resource_limits = spawn_config.resource_limits();
 
     let result = sqlx::query!(
         r#"
@@ -121,9 +265,14 @@ async fn create_backend_with_key(
                 last_keepalive,
                 state,
                 static_token,
-                subdomain
+                subdomain,
+                key_namespace,
+                memory_limit_bytes,
+                cpu_limit_millicores,
+                warm_pool_id,
+                suspend_when_idle
             )
-            values ($1, $2, $3, now(), $14, $4, now() + $5, $6, now(), $11, $12, $13)
+            values ($1, $2, $3, now(), $14, $4, now() + $5, $6, now(), $11, $12, $13, $8, $15, $16, $17, $18)
             returning id
         )
         insert into backend_key (id, key_name, namespace, tag, expires_at, fencing_token)
@@ -149,6 +298,10 @@ async fn create_backend_with_key(
         static_token.map(|t| t.to_string()),
         spawn_config.subdomain.as_ref().map(|s| s.to_string()),
         initial_status.as_int(),
+        resource_limits.memory_limit_bytes,
+        resource_limits.cpu_millicores().map(|cpu| cpu as i64),
+        warm_pool.map(|id| id.to_string()),
+        spawn_config.idle_action == IdleAction::Suspend,
     )
     .fetch_one(&mut *txn)
     .await;
@@ -163,27 +316,23 @@ async fn create_backend_with_key(
         }
     };
 
-    emit_state_change(&mut txn, &backend_id, &initial_state).await?;
-
-    let acquired_key = AcquiredKey {
-        key: key.clone(),
-        deadlines: KeyDeadlines {
-            renew_at: LoggableTime(drone_for_spawn.last_local_time + KEY_LEASE_RENEW_AFTER),
-            soft_terminate_at: LoggableTime(
-                drone_for_spawn.last_local_time + KEY_LEASE_SOFT_TERMINATE_AFTER,
-            ),
-            hard_terminate_at: LoggableTime(
-                drone_for_spawn.last_local_time + KEY_LEASE_SOFT_TERMINATE_AFTER,
-            ),
-        },
-        token: result.fencing_token,
-    };
+    emit_state_change(
+        &mut txn,
+        &backend_id,
+        cluster,
+        Some(key),
+        None,
+        &initial_state,
+    )
+    .await?;
 
-    let pending_action = BackendAction::Spawn {
-        executable: spawn_config.executable.clone(),
-        key: acquired_key,
-        static_token: static_token.cloned(),
-    };
+    let pending_action = spawn_action(
+        key,
+        result.fencing_token,
+        drone_for_spawn,
+        spawn_config,
+        static_token,
+    );
 
     // Create an action to spawn the backend. If we succeed in acquiring the key,
     // this will cause the backend to spawn. If we fail to acquire the key, this
@@ -197,34 +346,374 @@ async fn create_backend_with_key(
     Ok(backend_id)
 }
 
+/// Creates a backend that owns the given key but is not yet assigned to a drone, and adds it
+/// to the queue of backends waiting for a drone. If the key is already held, returns
+/// Err(ConnectError::FailedToAcquireKey).
+async fn queue_backend_with_key(
+    pool: &PgPool,
+    key: &KeyConfig,
+    spawn_config: &SpawnConfig,
+    cluster: &ClusterName,
+    static_token: Option<&BearerToken>,
+    queue_timeout_seconds: i32,
+) -> Result<BackendName> {
+    let backend_id = spawn_config.id.clone().or_random();
+    let mut txn = pool.begin().await?;
+
+    let initial_status = BackendStatus::Queued;
+    let initial_state = BackendState::Queued;
+    let resource_limits = spawn_config.resource_limits();
+    let queue_timeout =
+        PgInterval::try_from(Duration::from_secs(queue_timeout_seconds.max(0) as _))
+            .expect("valid interval");
+
+    // The key is held for as long as the backend may be queued. Once the backend is scheduled,
+    // the drone renews it as usual.
+    let result = sqlx::query!(
+        r#"
+        with backend_insert as (
+            insert into backend (
+                id,
+                cluster,
+                last_status,
+                last_status_time,
+                last_status_number,
+                allowed_idle_seconds,
+                last_keepalive,
+                state,
+                static_token,
+                subdomain,
+                key_namespace,
+                memory_limit_bytes,
+                cpu_limit_millicores,
+                suspend_when_idle
+            )
+            values ($1, $2, $3, now(), $4, $5, now(), $6, $7, $8, $10, $13, $14, $16)
+            returning id
+        )
+        insert into backend_key (id, key_name, namespace, tag, expires_at, fencing_token)
+        select $1, $9, $10, $11, now() + $12 + $15, extract(epoch from now()) * 1000 from backend_insert
+        "#,
+        backend_id.to_string(),
+        cluster.to_string(),
+        initial_status.to_string(),
+        initial_status.as_int(),
+        spawn_config.max_idle_seconds,
+        serde_json::to_value(&initial_state).expect("state is always serializable"),
+        static_token.map(|t| t.to_string()),
+        spawn_config.subdomain.as_ref().map(|s| s.to_string()),
+        key.name,
+        key.namespace,
+        key.tag,
+        queue_timeout,
+        resource_limits.memory_limit_bytes,
+        resource_limits.cpu_millicores().map(|cpu| cpu as i64),
+        PgInterval::try_from(KEY_LEASE_EXPIRATION).expect("valid constant interval"),
+        spawn_config.idle_action == IdleAction::Suspend,
+    )
+    .execute(&mut *txn)
+    .await;
+
+    if let Err(err) = result {
+        if violates_uniqueness(&err) {
+            return Err(ConnectError::FailedToAcquireKey);
+        }
+        return Err(err.into());
+    }
+
+    sqlx::query!(
+        r#"
+        insert into backend_queue (backend_id, cluster, spawn_config, expires_at)
+        values ($1, $2, $3, now() + $4)
+        "#,
+        backend_id.to_string(),
+        cluster.to_string(),
+        serde_json::to_value(spawn_config)?,
+        queue_timeout,
+    )
+    .execute(&mut *txn)
+    .await?;
+
+    emit_state_change(
+        &mut txn,
+        &backend_id,
+        cluster,
+        Some(key),
+        None,
+        &initial_state,
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(backend_id)
+}
+
+/// Spawns an unclaimed backend for a warm pool, on a drone picked the same way as for a
+/// connect request.
+pub async fn spawn_warm_backend(
+    pool: &PgPool,
+    warm_pool: &WarmPoolName,
+    cluster: &ClusterName,
+    drone_pool: &DronePoolName,
+    executable: &Value,
+) -> Result<BackendName> {
+    let spawn_config = SpawnConfig {
+        id: None,
+        cluster: Some(cluster.clone()),
+        pool: drone_pool.clone(),
+        fallback_pools: Vec::new(),
+        label_selector: LabelSelector::default(),
+        executable: executable.clone(),
+        lifetime_limit_seconds: None,
+        max_idle_seconds: None,
+        idle_action: IdleAction::Terminate,
+        use_static_token: false,
+        subdomain: None,
+        queue_timeout_seconds: None,
+        restart_policy: RestartPolicy::Never,
+    };
+
+    let pick = DroneDatabase::new(pool)
+        .pick_drone_for_spawn(
+            cluster,
+            drone_pool,
+            &spawn_config.label_selector,
+            &spawn_config.resource_limits(),
+        )
+        .await?;
+    let drone = match pick {
+        DronePick::Drone(drone) => drone,
+        DronePick::AtCapacity => return Err(ConnectError::ClusterAtCapacity),
+        DronePick::NoDrone => return Err(ConnectError::NoDroneAvailable),
+    };
+
+    let key = KeyConfig {
+        namespace: WARM_POOL_KEY_NAMESPACE.to_string(),
+        ..KeyConfig::new_random()
+    };
+
+    create_backend_with_key(
+        pool,
+        &key,
+        &spawn_config,
+        cluster,
+        &drone,
+        None,
+        Some(warm_pool),
+    )
+    .await
+}
+
+/// A warm pool backend that was claimed by a connect request.
+struct ClaimedBackend {
+    backend_id: BackendName,
+    drone: DroneName,
+    pool: DronePoolName,
+}
+
+/// Claims a ready backend from a warm pool whose executable config matches the spawn config,
+/// and gives it the requested key, lifetime, and subdomain. Returns None if no such backend
+/// is available.
+async fn claim_warm_backend(
+    pool: &PgPool,
+    key: &KeyConfig,
+    spawn_config: &SpawnConfig,
+    cluster: &ClusterName,
+) -> Result<Option<ClaimedBackend>> {
+    // Warm backends are spawned with an assigned ID, dynamic tokens, and no restart policy, so
+    // they can't satisfy spawn configs that ask for any of these.
+    if spawn_config.id.is_some()
+        || spawn_config.use_static_token
+        || !spawn_config.restart_policy.is_never()
+    {
+        return Ok(None);
+    }
+
+    let pools: Vec<String> = spawn_config.pools().map(|pool| pool.to_string()).collect();
+    let mut txn = pool.begin().await?;
+
+    let claimed = sqlx::query!(
+        r#"
+        select
+            backend.id,
+            node.name as drone,
+            warm_pool.pool
+        from backend
+        inner join warm_pool on warm_pool.id = backend.warm_pool_id
+        inner join drone on drone.id = backend.drone_id
+        inner join node on node.id = backend.drone_id
+        where
+            warm_pool.cluster = $1
+            and warm_pool.pool = any($2)
+            and warm_pool.executable = $3
+            and backend.last_status = $4
+            and drone.draining = false
+            and drone.labels @> $5
+        order by array_position($2, warm_pool.pool), backend.last_status_time
+        limit 1
+        for update of backend skip locked
+        "#,
+        cluster.to_string(),
+        &pools,
+        super::warm_pool::normalize_executable(&spawn_config.executable),
+        BackendStatus::Ready.to_string(),
+        serde_json::to_value(&spawn_config.label_selector.required).map_sqlx_error()?,
+    )
+    .fetch_optional(&mut *txn)
+    .await?;
+
+    let Some(claimed) = claimed else {
+        return Ok(None);
+    };
+
+    sqlx::query!(
+        r#"
+        update backend
+        set
+            warm_pool_id = null,
+            expiration_time = now() + $2,
+            allowed_idle_seconds = $3,
+            last_keepalive = now(),
+            subdomain = $4,
+            key_namespace = $5,
+            suspend_when_idle = $6
+        where id = $1
+        "#,
+        claimed.id,
+        spawn_config
+            .lifetime_limit_seconds
+            .map(
+                |limit| PgInterval::try_from(Duration::from_secs(limit as _))
+                    .expect("valid interval")
+            ),
+        spawn_config.max_idle_seconds,
+        spawn_config.subdomain.as_ref().map(|s| s.to_string()),
+        key.namespace,
+        spawn_config.idle_action == IdleAction::Suspend,
+    )
+    .execute(&mut *txn)
+    .await?;
+
+    let result = sqlx::query!(
+        r#"
+        update backend_key
+        set key_name = $2, namespace = $3, tag = $4
+        where id = $1
+        "#,
+        claimed.id,
+        key.name,
+        key.namespace,
+        key.tag,
+    )
+    .execute(&mut *txn)
+    .await;
+
+    if let Err(err) = result {
+        if violates_uniqueness(&err) {
+            return Err(ConnectError::FailedToAcquireKey);
+        }
+        return Err(err.into());
+    }
+
+    txn.commit().await?;
+
+    Ok(Some(ClaimedBackend {
+        backend_id: BackendName::try_from(claimed.id)
+            .map_err(|_| ConnectError::Other("Invalid backend name.".to_string()))?,
+        drone: DroneName::try_from(claimed.drone)
+            .map_err(|_| ConnectError::Other("Invalid drone name.".to_string()))?,
+        pool: DronePoolName::from(claimed.pool),
+    }))
+}
+
 async fn create_token(
     pool: &PgPool,
     backend: &BackendName,
     user: Option<&str>,
     auth: Map<String, Value>,
-) -> Result<(BearerToken, SecretToken)> {
+    lifetime: &PgInterval,
+) -> Result<(BearerToken, SecretToken, DateTime<Utc>)> {
     let token = random_token();
     let secret_token = random_token();
 
-    sqlx::query!(
+    let expiration_time = sqlx::query_scalar!(
         r#"
         insert into token (token, backend_id, username, auth, secret_token, expiration_time)
         values ($1, $2, $3, $4, $5, now() + $6)
+        returning expiration_time
         "#,
         token,
         backend.to_string(),
         user,
         serde_json::to_value(auth).expect("json map is always serializable"),
         secret_token,
-        PgInterval::try_from(Duration::from_secs(TOKEN_LIFETIME_SECONDS)).expect("valid interval"),
+        lifetime,
     )
-    .execute(pool)
+    .fetch_one(pool)
     .await?;
 
-    Ok((BearerToken::from(token), SecretToken::from(secret_token)))
+    Ok((
+        BearerToken::from(token),
+        SecretToken::from(secret_token),
+        expiration_time,
+    ))
+}
+
+/// Validates a requested token lifetime, which must be between one second and
+/// `MAX_TOKEN_LIFETIME_SECONDS`. Callers validate it before making any changes, so that
+/// an invalid lifetime doesn't fail a request after a backend has been spawned or claimed.
+fn token_lifetime(lifetime_seconds: Option<u64>) -> Result<PgInterval> {
+    let lifetime_seconds = lifetime_seconds.unwrap_or(TOKEN_LIFETIME_SECONDS);
+    if lifetime_seconds == 0 || lifetime_seconds > MAX_TOKEN_LIFETIME_SECONDS {
+        return Err(ConnectError::InvalidTokenLifetime(lifetime_seconds));
+    }
+    PgInterval::try_from(Duration::from_secs(lifetime_seconds))
+        .map_err(|_| ConnectError::InvalidTokenLifetime(lifetime_seconds))
+}
+
+/// Extends the lifetime of a token that has not yet expired. Returns the token's new
+/// expiration time, or None if the token does not exist (or has expired) or its
+/// backend has terminated.
+pub async fn refresh_token(
+    pool: &PgPool,
+    backend: &BackendName,
+    request: &TokenRefreshRequest,
+) -> Result<Option<DateTime<Utc>>> {
+    let lifetime = token_lifetime(request.lifetime_seconds)?;
+
+    let expiration_time = sqlx::query_scalar!(
+        r#"
+        update token
+        set expiration_time = now() + $3
+        from backend
+        where
+            token.token = $1
+            and token.backend_id = $2
+            and token.expiration_time > now()
+            and backend.id = token.backend_id
+            and backend.last_status != 'terminated'
+        returning token.expiration_time
+        "#,
+        request.token.to_string(),
+        backend.to_string(),
+        lifetime,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(expiration_time)
+}
+
+impl NotificationPayload for TokensRevoked {
+    fn kind() -> &'static str {
+        "tokens_revoked"
+    }
 }
 
 pub async fn revoke(pool: &PgPool, request: &RevokeRequest) -> Result<()> {
+    let mut txn = pool.begin().await?;
+
     sqlx::query!(
         r#"
         delete from token
@@ -233,17 +722,87 @@ pub async fn revoke(pool: &PgPool, request: &RevokeRequest) -> Result<()> {
         request.backend_id.to_string(),
         request.user,
     )
-    .execute(pool)
+    .execute(&mut *txn)
     .await?;
+
+    let cluster = sqlx::query!(
+        r#"
+        select cluster
+        from backend
+        where id = $1
+        "#,
+        request.backend_id.to_string(),
+    )
+    .fetch_optional(&mut *txn)
+    .await?;
+
+    // Proxies may have routes for the revoked tokens cached, or connections
+    // established with them, so we notify the proxies in the backend's cluster.
+    if let Some(cluster) = cluster {
+        emit_with_key(
+            &mut txn,
+            &cluster.cluster,
+            &TokensRevoked {
+                backend: request.backend_id.clone(),
+                user: request.user.clone(),
+            },
+        )
+        .await?;
+    }
+
+    txn.commit().await?;
+    Ok(())
+}
+
+/// Saves the spawn config a backend was spawned with for a connect request that gave a key, so
+/// that the key's backend can be respawned by a later request without a spawn config.
+async fn save_spawn_config(
+    pool: &PgPool,
+    request: &ConnectRequest,
+    key: &KeyConfig,
+    spawn_config: &SpawnConfig,
+    cluster: &ClusterName,
+) -> Result<()> {
+    // Keys generated for requests without one can't be connected to again.
+    if request.key.is_none() {
+        return Ok(());
+    }
+
+    // The backend ID can't be reused, and the cluster may have come from the controller's
+    // default, which could change.
+    let spawn_config = SpawnConfig {
+        id: None,
+        cluster: Some(cluster.clone()),
+        ..spawn_config.clone()
+    };
+    KeysDatabase::new(pool)
+        .save_spawn_config(key, &spawn_config)
+        .await?;
+
     Ok(())
 }
 
+fn check_cluster_allowed(
+    allowed_cluster: Option<&ClusterName>,
+    cluster: &ClusterName,
+) -> Result<()> {
+    match allowed_cluster {
+        Some(allowed) if allowed != cluster => {
+            Err(ConnectError::ClusterNotAllowed(cluster.clone()))
+        }
+        _ => Ok(()),
+    }
+}
+
 async fn attempt_connect(
     pool: &PgPool,
     default_cluster: Option<&ClusterName>,
+    allowed_cluster: Option<&ClusterName>,
     request: &ConnectRequest,
     client: &PlaneClient,
 ) -> Result<ConnectResponse> {
+    let token_lifetime = token_lifetime(request.token_lifetime_seconds)?;
+
     let key = if let Some(key) = &request.key {
         // Request includes a key, so we need to check if it is held.
         let key_result = KeysDatabase::new(pool).check_key(key).await?;
@@ -259,20 +818,23 @@ async fn attempt_connect(
                     });
                 }
 
-                let (token, secret_token) = if let Some(token) = key_result.static_connection_token
-                {
-                    (token, None)
-                } else {
-                    let (token, secret_token) = create_token(
-                        pool,
-                        &key_result.id,
-                        request.user.as_deref(),
-                        request.auth.clone(),
-                    )
-                    .await?;
-
-                    (token, Some(secret_token))
-                };
+                check_cluster_allowed(allowed_cluster, &key_result.cluster)?;
+
+                let (token, secret_token, token_expiration_time) =
+                    if let Some(token) = key_result.static_connection_token {
+                        (token, None, None)
+                    } else {
+                        let (token, secret_token, expiration_time) = create_token(
+                            pool,
+                            &key_result.id,
+                            request.user.as_deref(),
+                            request.auth.clone(),
+                            &token_lifetime,
+                        )
+                        .await?;
+
+                        (token, Some(secret_token), Some(expiration_time))
+                    };
 
                 let connect_response = ConnectResponse::new(
                     key_result.id,
@@ -284,6 +846,8 @@ async fn attempt_connect(
                     key_result.subdomain,
                     client,
                     None,
+                    None,
+                    token_expiration_time,
                 );
 
                 return Ok(connect_response);
@@ -306,7 +870,18 @@ async fn attempt_connect(
         KeyConfig::new_random()
     };
 
-    let Some(spawn_config) = &request.spawn_config else {
+    // A request with a key but no spawn config respawns the key's backend from the spawn
+    // config it was last spawned with.
+    let stored_spawn_config = match (&request.spawn_config, &request.key) {
+        (None, Some(key)) => KeysDatabase::new(pool).spawn_config(key).await?,
+        _ => None,
+    };
+
+    let Some(spawn_config) = request
+        .spawn_config
+        .as_ref()
+        .or(stored_spawn_config.as_ref())
+    else {
         return Err(ConnectError::KeyUnheldNoSpawnConfig);
     };
 
@@ -315,11 +890,43 @@ async fn attempt_connect(
         .as_ref()
         .or(default_cluster)
         .ok_or(ConnectError::NoClusterProvided)?;
+    check_cluster_allowed(allowed_cluster, cluster)?;
+
+    if let Some(claimed) = claim_warm_backend(pool, &key, spawn_config, cluster).await? {
+        tracing::info!(
+            backend_id = claimed.backend_id.as_value(),
+            "Claimed warm backend"
+        );
+
+        save_spawn_config(pool, request, &key, spawn_config, cluster).await?;
+
+        let (token, secret_token, expiration_time) = create_token(
+            pool,
+            &claimed.backend_id,
+            request.user.as_deref(),
+            request.auth.clone(),
+            &token_lifetime,
+        )
+        .await?;
+
+        let connect_response = ConnectResponse::new(
+            claimed.backend_id,
+            cluster,
+            true,
+            BackendStatus::Ready,
+            token,
+            Some(secret_token),
+            spawn_config.subdomain.clone(),
+            client,
+            Some(claimed.drone),
+            Some(claimed.pool),
+            Some(expiration_time),
+        );
+
+        return Ok(connect_response);
+    }
 
-    let drone = DroneDatabase::new(pool)
-        .pick_drone_for_spawn(cluster, &spawn_config.pool)
-        .await?
-        .ok_or(ConnectError::NoDroneAvailable)?;
+    let (placement, at_capacity) = pick_placement(pool, cluster, spawn_config).await?;
 
     // If the spawn config specifies a static token, create one and use it.
     // Note that if this is non-None, the call to create_token below will be skipped.
@@ -327,55 +934,94 @@ async fn attempt_connect(
         .use_static_token
         .then(BearerToken::new_random_static);
 
-    let backend_id = create_backend_with_key(
-        pool,
-        &key,
-        spawn_config,
-        cluster,
-        &drone,
-        bearer_token.as_ref(),
-    )
-    .await?;
-    tracing::info!(backend_id = backend_id.as_value(), "Created backend");
+    let (backend_id, status, drone, drone_pool) =
+        match (placement, spawn_config.queue_timeout_seconds) {
+            (Some((drone_pool, drone)), _) => {
+                let backend_id = create_backend_with_key(
+                    pool,
+                    &key,
+                    spawn_config,
+                    cluster,
+                    &drone,
+                    bearer_token.as_ref(),
+                    None,
+                )
+                .await?;
+                tracing::info!(backend_id = backend_id.as_value(), "Created backend");
+
+                (
+                    backend_id,
+                    BackendStatus::Scheduled,
+                    Some(drone.drone),
+                    Some(drone_pool.clone()),
+                )
+            }
+            (None, Some(queue_timeout_seconds)) => {
+                let backend_id = queue_backend_with_key(
+                    pool,
+                    &key,
+                    spawn_config,
+                    cluster,
+                    bearer_token.as_ref(),
+                    queue_timeout_seconds,
+                )
+                .await?;
+                tracing::info!(backend_id = backend_id.as_value(), "Queued backend");
+
+                (backend_id, BackendStatus::Queued, None, None)
+            }
+            (None, None) if at_capacity => return Err(ConnectError::ClusterAtCapacity),
+            (None, None) => return Err(ConnectError::NoDroneAvailable),
+        };
+
+    save_spawn_config(pool, request, &key, spawn_config, cluster).await?;
 
-    let (token, secret_token) = if let Some(token) = bearer_token {
-        (token, None)
+    let (token, secret_token, token_expiration_time) = if let Some(token) = bearer_token {
+        (token, None, None)
     } else {
-        let (token, secret_token) = create_token(
+        let (token, secret_token, expiration_time) = create_token(
             pool,
             &backend_id,
             request.user.as_deref(),
             request.auth.clone(),
+            &token_lifetime,
         )
         .await?;
 
-        (token, Some(secret_token))
+        (token, Some(secret_token), Some(expiration_time))
     };
 
     let connect_response = ConnectResponse::new(
         backend_id,
         cluster,
         true,
-        BackendStatus::Scheduled,
+        status,
         token,
         secret_token,
         spawn_config.subdomain.clone(),
         client,
-        Some(drone.drone),
+        drone,
+        drone_pool,
+        token_expiration_time,
     );
 
     Ok(connect_response)
 }
 
+/// Connects to the backend held by the request's key, or spawns one.
+///
+/// If `allowed_cluster` is provided, the request fails with `ConnectError::ClusterNotAllowed`
+/// before any token is issued or backend spawned if the backend is in another cluster.
 pub async fn connect(
     pool: &PgPool,
     default_cluster: Option<&ClusterName>,
+    allowed_cluster: Option<&ClusterName>,
     request: &ConnectRequest,
     client: &PlaneClient,
 ) -> Result<ConnectResponse> {
     let mut attempt = 1;
     loop {
-        match attempt_connect(pool, default_cluster, request, client).await {
+        match attempt_connect(pool, default_cluster, allowed_cluster, request, client).await {
             Ok(response) => return Ok(response),
             Err(error) => {
                 if !error.retryable() || attempt >= 3 {
@@ -399,6 +1045,7 @@ pub async fn clean_up_tokens(pool: &PgPool) -> std::result::Result<(), sqlx::Err
     .await?;
 
     let row_count = result.rows_affected();
+    DATABASE_METRICS.record_rows_deleted("token", row_count);
     tracing::info!(row_count, "Cleaned up expired tokens");
 
     Ok(())
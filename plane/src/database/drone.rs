@@ -1,10 +1,16 @@
 use chrono::{DateTime, Utc};
 use plane_common::names::{ControllerName, DroneName};
-use plane_common::types::{BackendStatus, ClusterName, DronePoolName, NodeId};
+use plane_common::protocol::DroneResources;
+use plane_common::types::{
+    BackendStatus, ClusterName, DroneLabels, DronePoolName, LabelSelector, NodeId, ResourceLimits,
+};
+use rand::seq::SliceRandom;
 use sqlx::{postgres::types::PgInterval, query, PgPool};
 use std::str::FromStr;
 use std::time::Duration;
 
+use super::metrics::DATABASE_METRICS;
+use super::util::MapSqlxError;
 use crate::heartbeat_consts::UNHEALTHY_SECONDS;
 
 pub struct DroneDatabase<'a> {
@@ -21,17 +27,23 @@ impl<'a> DroneDatabase<'a> {
         id: NodeId,
         ready: bool,
         pool: DronePoolName,
+        labels: &DroneLabels,
+        max_backends: Option<u32>,
     ) -> sqlx::Result<()> {
         query!(
             r#"
-            insert into drone (id, draining, ready, pool)
-            values ($1, false, $2, $3)
+            insert into drone (id, draining, ready, pool, labels, max_backends)
+            values ($1, false, $2, $3, $4, $5)
             on conflict (id) do update set
-                ready = $2
+                ready = $2,
+                labels = $4,
+                max_backends = $5
             "#,
             id.as_i32(),
             ready,
             pool.to_string(),
+            serde_json::to_value(labels).map_sqlx_error()?,
+            max_backends.map(|max_backends| max_backends as i32),
         )
         .execute(self.pool)
         .await?;
@@ -63,15 +75,25 @@ impl<'a> DroneDatabase<'a> {
         }
     }
 
-    pub async fn heartbeat(&self, id: NodeId, local_time: DateTime<Utc>) -> sqlx::Result<()> {
+    pub async fn heartbeat(
+        &self,
+        id: NodeId,
+        local_time: DateTime<Utc>,
+        resources: Option<&DroneResources>,
+    ) -> sqlx::Result<()> {
+        let _timer = DATABASE_METRICS.query_timer("drone.heartbeat");
         query!(
             r#"
             update drone
-            set last_heartbeat = now(), last_local_time = $2
+            set last_heartbeat = now(), last_local_time = $2, resources = $3
             where id = $1
             "#,
             id.as_i32(),
             local_time,
+            resources
+                .map(serde_json::to_value)
+                .transpose()
+                .map_sqlx_error()?,
         )
         .execute(self.pool)
         .await?;
@@ -170,68 +192,223 @@ impl<'a> DroneDatabase<'a> {
         Ok(drones)
     }
 
-    /// TODO: simple algorithm until we collect more metrics.
+    /// Picks a drone to spawn a backend with the given resource limits on.
+    ///
+    /// Only drones with every label required by `selector` are considered, and drones that
+    /// are already running their `max_backends` are skipped. Drones matching more of the
+    /// selector's preferred labels are chosen over those matching fewer.
+    ///
+    /// Drones that report their resources are only considered if the backend fits in their
+    /// headroom, and the drone with the most headroom (as a fraction of its allocatable
+    /// resources) is preferred. Drones that do not report their resources are only used
+    /// if no drone that does can fit the backend, in which case the one with the fewest
+    /// backends is preferred.
     pub async fn pick_drone_for_spawn(
         &self,
         cluster: &ClusterName,
         pool: &DronePoolName,
-    ) -> sqlx::Result<Option<DroneForSpawn>> {
+        selector: &LabelSelector,
+        limits: &ResourceLimits,
+    ) -> sqlx::Result<DronePick> {
         let result = query!(
             r#"
             select
                 drone.id,
                 node.name,
-                drone.last_local_time as "last_local_time!"
+                drone.last_local_time as "last_local_time!",
+                drone.resources,
+                drone.labels,
+                drone.max_backends,
+                count(backend.id) as "backend_count!",
+                coalesce(sum(backend.memory_limit_bytes), 0)::bigint as "committed_memory_bytes!",
+                coalesce(sum(backend.cpu_limit_millicores), 0)::bigint as "committed_cpu_millicores!"
             from node
             left join drone
                 on node.id = drone.id
             left join controller
                 on node.controller = controller.id
+            left join backend
+                on backend.drone_id = node.id
+                and backend.last_status != $4
             where
                 drone.ready = true
                 and controller is not null
-                and cluster = $1
+                and node.cluster = $1
                 and now() - drone.last_heartbeat < $2
                 and now() - controller.last_heartbeat < $2
                 and controller.is_online = true
                 and draining = false
                 and last_local_time is not null
                 and pool = $3
-            order by (
-                select
-                    count(*)
-                from backend
-                where drone_id = node.id
-                and last_status != $4
-            ) asc, random()
-            limit 1
+                and drone.labels @> $5
+            group by drone.id, node.name
             "#,
             cluster.to_string(),
             PgInterval::try_from(Duration::from_secs(UNHEALTHY_SECONDS as _))
                 .expect("valid interval"),
             pool.to_string(),
             BackendStatus::Terminated.to_string(),
+            serde_json::to_value(&selector.required).map_sqlx_error()?,
         )
-        .fetch_optional(self.pool)
+        .fetch_all(self.pool)
         .await?;
 
-        let result = match result {
-            Some(result) => {
-                let id = NodeId::from(result.id);
-                let drone = DroneName::try_from(result.name).expect("valid drone name");
-                let last_local_time = result.last_local_time;
+        let candidates = result
+            .into_iter()
+            .map(|row| {
+                let resources: Option<DroneResources> = row
+                    .resources
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_sqlx_error()?;
+                let labels: DroneLabels = serde_json::from_value(row.labels).map_sqlx_error()?;
 
-                Some(DroneForSpawn {
-                    id,
-                    drone,
-                    last_local_time,
+                Ok(SpawnCandidate {
+                    drone: DroneForSpawn {
+                        id: NodeId::from(row.id),
+                        drone: DroneName::try_from(row.name).expect("valid drone name"),
+                        last_local_time: row.last_local_time,
+                    },
+                    resources,
+                    preference_score: selector.preference_score(&labels),
+                    max_backends: row.max_backends,
+                    backend_count: row.backend_count,
+                    committed_memory_bytes: row.committed_memory_bytes.max(0) as u64,
+                    committed_cpu_millicores: row.committed_cpu_millicores.max(0) as u64,
                 })
+            })
+            .collect::<sqlx::Result<Vec<_>>>()?;
+
+        if candidates.is_empty() {
+            return Ok(DronePick::NoDrone);
+        }
+
+        Ok(match choose_drone(candidates, limits) {
+            Some(drone) => DronePick::Drone(drone),
+            None => DronePick::AtCapacity,
+        })
+    }
+}
+
+/// The result of picking a drone to spawn a backend on.
+pub enum DronePick {
+    Drone(DroneForSpawn),
+
+    /// There are drones that could run the backend, but none of them has room for it.
+    AtCapacity,
+
+    /// There are no drones that could run the backend.
+    NoDrone,
+}
+
+/// A drone that a backend could be spawned on, along with what is needed to decide whether
+/// the backend fits on it.
+struct SpawnCandidate {
+    drone: DroneForSpawn,
+    resources: Option<DroneResources>,
+
+    /// The number of the spawn request's preferred labels that the drone has.
+    preference_score: usize,
+
+    /// The maximum number of backends the drone can run at once, if it has a limit.
+    max_backends: Option<i32>,
+
+    backend_count: i64,
+
+    /// The sum of the memory limits of the drone's non-terminated backends.
+    committed_memory_bytes: u64,
+
+    /// The sum of the CPU limits of the drone's non-terminated backends.
+    committed_cpu_millicores: u64,
+}
+
+/// The fraction of `allocatable` that would remain after placing `requested`, or `None` if it
+/// does not fit. The drone's use of a resource is the greater of what its backends are using,
+/// and what they have been allocated (since newly-scheduled backends may not be using their
+/// allocation yet).
+fn headroom(allocatable: u64, used: u64, committed: u64, requested: u64) -> Option<f64> {
+    let remaining = allocatable
+        .checked_sub(used.max(committed))?
+        .checked_sub(requested)?;
+
+    if allocatable == 0 {
+        return Some(0.0);
+    }
+
+    Some(remaining as f64 / allocatable as f64)
+}
+
+impl SpawnCandidate {
+    fn has_capacity(&self) -> bool {
+        self.max_backends
+            .is_none_or(|max_backends| self.backend_count < max_backends as i64)
+    }
+
+    /// The fraction of the drone's memory or CPU (whichever is lower) that would remain after
+    /// placing a backend with the given limits. `None` if the backend does not fit.
+    fn headroom(&self, resources: &DroneResources, limits: &ResourceLimits) -> Option<f64> {
+        let requested_memory = limits.memory_limit_bytes.unwrap_or_default().max(0) as u64;
+        let memory = headroom(
+            resources.allocatable_memory_bytes,
+            resources.used_memory_bytes,
+            self.committed_memory_bytes,
+            requested_memory,
+        )?;
+
+        let cpu = headroom(
+            resources.allocatable_cpu_millicores,
+            resources.used_cpu_millicores,
+            self.committed_cpu_millicores,
+            limits.cpu_millicores().unwrap_or_default(),
+        )?;
+
+        Some(memory.min(cpu))
+    }
+}
+
+fn choose_drone(
+    mut candidates: Vec<SpawnCandidate>,
+    limits: &ResourceLimits,
+) -> Option<DroneForSpawn> {
+    // Shuffle first, so that ties are broken randomly by the stable sort below.
+    candidates.shuffle(&mut rand::thread_rng());
+
+    let mut fits = Vec::new();
+    let mut unknown = Vec::new();
+    for candidate in candidates {
+        if !candidate.has_capacity() {
+            continue;
+        }
+
+        match candidate.resources {
+            Some(resources) => {
+                if let Some(headroom) = candidate.headroom(&resources, limits) {
+                    fits.push((headroom, candidate));
+                }
             }
-            None => return Ok(None),
-        };
+            None => unknown.push(candidate),
+        }
+    }
 
-        Ok(result)
+    if !fits.is_empty() {
+        fits.sort_by(|(a_headroom, a), (b_headroom, b)| {
+            b.preference_score
+                .cmp(&a.preference_score)
+                .then(b_headroom.total_cmp(a_headroom))
+        });
+        return fits
+            .into_iter()
+            .next()
+            .map(|(_, candidate)| candidate.drone);
     }
+
+    unknown.sort_by_key(|candidate| {
+        (
+            std::cmp::Reverse(candidate.preference_score),
+            candidate.backend_count,
+        )
+    });
+    unknown.into_iter().next().map(|candidate| candidate.drone)
 }
 
 pub struct DroneForSpawn {
@@ -254,3 +431,129 @@ pub struct DroneWithMetadata {
     pub controller: Option<ControllerName>,
     pub last_connection_start_time: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plane_common::names::Name;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn candidate(id: i32, memory_bytes: Option<u64>, used_memory_bytes: u64) -> SpawnCandidate {
+        SpawnCandidate {
+            drone: DroneForSpawn {
+                id: NodeId::from(id),
+                drone: DroneName::new_random(),
+                last_local_time: Utc::now(),
+            },
+            resources: memory_bytes.map(|memory_bytes| DroneResources {
+                total_memory_bytes: memory_bytes,
+                allocatable_memory_bytes: memory_bytes,
+                used_memory_bytes,
+                total_cpu_millicores: 4000,
+                allocatable_cpu_millicores: 4000,
+                used_cpu_millicores: 0,
+            }),
+            preference_score: 0,
+            max_backends: None,
+            backend_count: 0,
+            committed_memory_bytes: 0,
+            committed_cpu_millicores: 0,
+        }
+    }
+
+    fn limits(memory_bytes: u64) -> ResourceLimits {
+        ResourceLimits {
+            memory_limit_bytes: Some(memory_bytes as i64),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_prefers_most_headroom() {
+        // The small drone has more memory free, but less as a fraction of its total.
+        let small = candidate(1, Some(4 * GIB), GIB);
+        let large = candidate(2, Some(16 * GIB), 6 * GIB);
+
+        let chosen = choose_drone(vec![small, large], &limits(GIB)).unwrap();
+        assert_eq!(chosen.id, NodeId::from(2));
+    }
+
+    #[test]
+    fn test_refuses_drones_without_room() {
+        let small = candidate(1, Some(4 * GIB), 3 * GIB);
+        assert!(choose_drone(vec![small], &limits(2 * GIB)).is_none());
+    }
+
+    #[test]
+    fn test_counts_committed_resources() {
+        // Nothing is running yet, but the drone's memory has been allocated to backends.
+        let mut drone = candidate(1, Some(4 * GIB), 0);
+        drone.committed_memory_bytes = 3 * GIB;
+        assert!(choose_drone(vec![drone], &limits(2 * GIB)).is_none());
+    }
+
+    #[test]
+    fn test_checks_cpu() {
+        let drone = candidate(1, Some(4 * GIB), 0);
+        let limits = ResourceLimits {
+            cpu_period_percent: Some(250),
+            ..Default::default()
+        };
+        assert!(choose_drone(vec![drone], &limits).is_some());
+
+        let mut drone = candidate(1, Some(4 * GIB), 0);
+        drone.committed_cpu_millicores = 2000;
+        assert!(choose_drone(vec![drone], &limits).is_none());
+    }
+
+    #[test]
+    fn test_prefers_preferred_labels() {
+        // The preferred drone is chosen even though the other drone has more headroom.
+        let mut preferred = candidate(1, Some(4 * GIB), 2 * GIB);
+        preferred.preference_score = 1;
+        let other = candidate(2, Some(4 * GIB), 0);
+
+        let chosen = choose_drone(vec![preferred, other], &limits(GIB)).unwrap();
+        assert_eq!(chosen.id, NodeId::from(1));
+
+        // But not if the backend does not fit on it.
+        let mut preferred = candidate(1, Some(4 * GIB), 4 * GIB);
+        preferred.preference_score = 1;
+        let other = candidate(2, Some(4 * GIB), 0);
+
+        let chosen = choose_drone(vec![preferred, other], &limits(GIB)).unwrap();
+        assert_eq!(chosen.id, NodeId::from(2));
+    }
+
+    #[test]
+    fn test_skips_drones_at_capacity() {
+        let mut full = candidate(1, Some(4 * GIB), 0);
+        full.max_backends = Some(2);
+        full.backend_count = 2;
+        let mut unknown_full = candidate(2, None, 0);
+        unknown_full.max_backends = Some(1);
+        unknown_full.backend_count = 1;
+        assert!(choose_drone(vec![full, unknown_full], &limits(GIB)).is_none());
+
+        let mut available = candidate(3, Some(4 * GIB), 3 * GIB);
+        available.max_backends = Some(2);
+        available.backend_count = 1;
+        let mut full = candidate(1, Some(4 * GIB), 0);
+        full.max_backends = Some(2);
+        full.backend_count = 2;
+        let chosen = choose_drone(vec![full, available], &limits(GIB)).unwrap();
+        assert_eq!(chosen.id, NodeId::from(3));
+    }
+
+    #[test]
+    fn test_falls_back_to_drones_without_resources() {
+        let full = candidate(1, Some(4 * GIB), 4 * GIB);
+        let mut busy = candidate(2, None, 0);
+        busy.backend_count = 5;
+        let idle = candidate(3, None, 0);
+
+        let chosen = choose_drone(vec![full, busy, idle], &limits(GIB)).unwrap();
+        assert_eq!(chosen.id, NodeId::from(3));
+    }
+}
@@ -0,0 +1,121 @@
+use super::util::hash_secret;
+use plane_common::{
+    names::{JoinTokenName, Name},
+    types::{ClusterName, MintJoinTokenRequest, MintJoinTokenResponse},
+    util::random_token,
+};
+use sqlx::PgPool;
+
+pub struct JoinTokenDatabase<'a> {
+    pool: &'a PgPool,
+}
+
+/// A join token that has been presented by a node and verified.
+#[derive(Clone, Debug)]
+pub struct JoinToken {
+    pub id: JoinTokenName,
+    pub cluster: Option<ClusterName>,
+}
+
+impl JoinToken {
+    /// Whether the token may be used by a node in the given cluster. Nodes that do not
+    /// belong to a cluster (DNS servers) may only use tokens that are not restricted to one.
+    pub fn allows_cluster(&self, cluster: Option<&ClusterName>) -> bool {
+        match (&self.cluster, cluster) {
+            (None, _) => true,
+            (Some(allowed), Some(cluster)) => allowed == cluster,
+            (Some(_), None) => false,
+        }
+    }
+}
+
+impl<'a> JoinTokenDatabase<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Creates a new join token. The returned token is not stored, only a hash of its secret part.
+    pub async fn mint(
+        &self,
+        request: &MintJoinTokenRequest,
+    ) -> sqlx::Result<MintJoinTokenResponse> {
+        let id = JoinTokenName::new_random();
+        let secret = random_token();
+
+        sqlx::query!(
+            r#"
+            insert into node_join_token (id, secret_hash, cluster, description)
+            values ($1, $2, $3, $4)
+            "#,
+            id.to_string(),
+            hash_secret(&secret),
+            request.cluster.as_ref().map(|c| c.to_string()),
+            request.description,
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(MintJoinTokenResponse {
+            join_token: format!("{}.{}", id, secret),
+            id,
+        })
+    }
+
+    /// Returns the join token if it exists, has not been revoked, and its secret matches.
+    pub async fn authenticate(&self, join_token: &str) -> sqlx::Result<Option<JoinToken>> {
+        let Some((id, secret)) = join_token.split_once('.') else {
+            return Ok(None);
+        };
+
+        let Ok(id) = JoinTokenName::try_from(id.to_string()) else {
+            return Ok(None);
+        };
+
+        let result = sqlx::query!(
+            r#"
+            select secret_hash, cluster
+            from node_join_token
+            where id = $1 and revoked_at is null
+            "#,
+            id.to_string(),
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        let Some(result) = result else {
+            return Ok(None);
+        };
+
+        if !openssl::memcmp::eq(
+            hash_secret(secret).as_bytes(),
+            result.secret_hash.as_bytes(),
+        ) {
+            return Ok(None);
+        }
+
+        let cluster = result
+            .cluster
+            .map(|cluster| cluster.parse::<ClusterName>())
+            .transpose()
+            .map_err(|err| sqlx::Error::Decode(err.into()))?;
+
+        Ok(Some(JoinToken { id, cluster }))
+    }
+
+    /// Revokes a join token. Returns false if the token does not exist or was already revoked.
+    /// Nodes that are already connected are not disconnected, but may not reconnect.
+    pub async fn revoke(&self, id: &JoinTokenName) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            update node_join_token
+            set revoked_at = now()
+            where id = $1 and revoked_at is null
+            "#,
+            id.to_string(),
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
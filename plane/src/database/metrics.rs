@@ -0,0 +1,188 @@
+use crate::heartbeat_consts::UNHEALTHY_SECONDS;
+use plane_common::types::BackendStatus;
+use prometheus::{
+    core::Collector, histogram_opts, opts, HistogramTimer, HistogramVec, IntCounterVec,
+};
+use sqlx::{postgres::types::PgInterval, query, PgPool};
+use std::{sync::LazyLock, time::Duration};
+
+/// Metrics recorded by database operations. They are reported by the controller, which
+/// registers them alongside its own metrics.
+pub struct DatabaseMetrics {
+    /// Latency of database operations, labeled by operation.
+    pub query_duration_seconds: HistogramVec,
+
+    /// Rows deleted by cleanup, labeled by table.
+    pub cleanup_rows_deleted: IntCounterVec,
+
+    /// Time from a backend being created to it becoming ready, labeled by drone pool.
+    pub spawn_to_ready_seconds: HistogramVec,
+}
+
+pub static DATABASE_METRICS: LazyLock<DatabaseMetrics> = LazyLock::new(DatabaseMetrics::new);
+
+impl DatabaseMetrics {
+    fn new() -> Self {
+        let query_duration_seconds = HistogramVec::new(
+            histogram_opts!(
+                "plane_db_query_duration_seconds",
+                "Latency of database operations."
+            ),
+            &["query"],
+        )
+        .expect("Metric options are valid.");
+
+        let cleanup_rows_deleted = IntCounterVec::new(
+            opts!(
+                "plane_cleanup_rows_deleted_total",
+                "Rows deleted by the cleanup loop, by table."
+            ),
+            &["table"],
+        )
+        .expect("Metric options are valid.");
+
+        let spawn_to_ready_seconds = HistogramVec::new(
+            histogram_opts!(
+                "plane_backend_spawn_to_ready_seconds",
+                "Time from a backend being created to it becoming ready.",
+                vec![0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0]
+            ),
+            &["pool"],
+        )
+        .expect("Metric options are valid.");
+
+        Self {
+            query_duration_seconds,
+            cleanup_rows_deleted,
+            spawn_to_ready_seconds,
+        }
+    }
+
+    /// Returns the metrics, to be registered with a registry.
+    pub fn collectors(&self) -> Vec<Box<dyn Collector>> {
+        vec![
+            Box::new(self.query_duration_seconds.clone()),
+            Box::new(self.cleanup_rows_deleted.clone()),
+            Box::new(self.spawn_to_ready_seconds.clone()),
+        ]
+    }
+
+    /// Starts a timer that records the latency of a database operation when dropped.
+    pub fn query_timer(&self, query: &str) -> HistogramTimer {
+        self.query_duration_seconds
+            .with_label_values(&[query])
+            .start_timer()
+    }
+
+    pub fn record_rows_deleted(&self, table: &str, count: u64) {
+        self.cleanup_rows_deleted
+            .with_label_values(&[table])
+            .inc_by(count);
+    }
+}
+
+/// Cluster-wide counts that are reported as gauges when metrics are scraped.
+pub struct MetricsDatabase<'a> {
+    pool: &'a PgPool,
+}
+
+pub struct BackendCount {
+    pub cluster: String,
+    pub status: String,
+    pub count: i64,
+}
+
+pub struct NodeCount {
+    pub cluster: String,
+    pub kind: String,
+    pub count: i64,
+}
+
+pub struct PendingActionCount {
+    pub cluster: String,
+    pub count: i64,
+}
+
+impl<'a> MetricsDatabase<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Counts non-terminated backends by cluster and status.
+    pub async fn backend_counts(&self) -> sqlx::Result<Vec<BackendCount>> {
+        let rows = query!(
+            r#"
+            select cluster, last_status, count(*) as "count!"
+            from backend
+            where last_status != $1
+            group by cluster, last_status
+            "#,
+            BackendStatus::Terminated.to_string(),
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| BackendCount {
+                cluster: row.cluster,
+                status: row.last_status,
+                count: row.count,
+            })
+            .collect())
+    }
+
+    /// Counts nodes that are connected to a live controller, by cluster and kind.
+    pub async fn connected_node_counts(&self) -> sqlx::Result<Vec<NodeCount>> {
+        let rows = query!(
+            r#"
+            select node.cluster as "cluster!", node.kind, count(*) as "count!"
+            from node
+            inner join controller on controller.id = node.controller
+            where
+                controller.is_online
+                and now() - controller.last_heartbeat < $1
+                and node.cluster is not null
+            group by node.cluster, node.kind
+            "#,
+            PgInterval::try_from(Duration::from_secs(UNHEALTHY_SECONDS as _))
+                .expect("valid interval"),
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| NodeCount {
+                cluster: row.cluster,
+                kind: row.kind,
+                count: row.count,
+            })
+            .collect())
+    }
+
+    /// Counts backend actions that have not been acknowledged by their drone, by cluster.
+    pub async fn pending_action_counts(&self) -> sqlx::Result<Vec<PendingActionCount>> {
+        let rows = query!(
+            r#"
+            select node.cluster as "cluster!", count(*) as "count!"
+            from backend_action
+            inner join node on node.id = backend_action.drone_id
+            where
+                backend_action.acked_at is null
+                and node.cluster is not null
+            group by node.cluster
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PendingActionCount {
+                cluster: row.cluster,
+                count: row.count,
+            })
+            .collect())
+    }
+}
@@ -1,17 +1,26 @@
 use self::{
     acme::AcmeDatabase,
+    api_key::ApiKeyDatabase,
     backend::BackendDatabase,
     backend_actions::BackendActionDatabase,
     backend_key::KeysDatabase,
+    backend_log::BackendLogDatabase,
     cluster::ClusterDatabase,
     connect::ConnectError,
     controller::ControllerDatabase,
     drone::DroneDatabase,
+    join_token::JoinTokenDatabase,
+    metrics::{MetricsDatabase, DATABASE_METRICS},
     node::NodeDatabase,
+    spawn_queue::SpawnQueueDatabase,
     subscribe::{EventSubscriptionManager, Notification, NotificationPayload, Subscription},
+    warm_pool::WarmPoolDatabase,
+    webhook::WebhookDatabase,
 };
+use chrono::{DateTime, Utc};
 use plane_common::{
-    types::{ClusterName, ConnectRequest, ConnectResponse, RevokeRequest},
+    names::BackendName,
+    types::{ClusterName, ConnectRequest, ConnectResponse, RevokeRequest, TokenRefreshRequest},
     PlaneClient,
 };
 use serde_json::Value;
@@ -20,16 +29,23 @@ use std::sync::{Arc, OnceLock};
 use tokio::sync::broadcast::Receiver;
 
 pub mod acme;
+pub mod api_key;
 pub mod backend;
 pub mod backend_actions;
 pub mod backend_key;
+pub mod backend_log;
 pub mod cluster;
 pub mod connect;
 pub mod controller;
 pub mod drone;
+pub mod join_token;
+pub mod metrics;
 pub mod node;
+pub mod spawn_queue;
 pub mod subscribe;
 pub mod util;
+pub mod warm_pool;
+pub mod webhook;
 
 pub async fn connect_and_migrate(db: &str) -> sqlx::Result<PlaneDatabase> {
     let db_pool = PgPoolOptions::new().connect(db).await?;
@@ -60,6 +76,14 @@ impl PlaneDatabase {
         AcmeDatabase::new(&self.pool)
     }
 
+    pub fn api_keys(&self) -> ApiKeyDatabase {
+        ApiKeyDatabase::new(&self.pool)
+    }
+
+    pub fn join_tokens(&self) -> JoinTokenDatabase {
+        JoinTokenDatabase::new(&self.pool)
+    }
+
     pub fn drone(&self) -> DroneDatabase {
         DroneDatabase::new(&self.pool)
     }
@@ -80,6 +104,10 @@ impl PlaneDatabase {
         BackendActionDatabase::new(&self.pool)
     }
 
+    pub fn backend_logs(&self) -> BackendLogDatabase {
+        BackendLogDatabase::new(self)
+    }
+
     pub fn keys(&self) -> backend_key::KeysDatabase {
         KeysDatabase::new(&self.pool)
     }
@@ -88,6 +116,22 @@ impl PlaneDatabase {
         ControllerDatabase::new(&self.pool)
     }
 
+    pub fn spawn_queue(&self) -> SpawnQueueDatabase {
+        SpawnQueueDatabase::new(self)
+    }
+
+    pub fn warm_pools(&self) -> WarmPoolDatabase {
+        WarmPoolDatabase::new(&self.pool)
+    }
+
+    pub fn webhook(&self) -> WebhookDatabase {
+        WebhookDatabase::new(&self.pool)
+    }
+
+    pub fn metrics(&self) -> MetricsDatabase {
+        MetricsDatabase::new(&self.pool)
+    }
+
     pub async fn health_check(&self) -> Result<(), sqlx::Error> {
         sqlx::query_scalar!("select 1")
             .fetch_one(&self.pool)
@@ -98,15 +142,34 @@ impl PlaneDatabase {
     pub async fn connect(
         &self,
         default_cluster: Option<&ClusterName>,
+        allowed_cluster: Option<&ClusterName>,
         request: &ConnectRequest,
         client: &PlaneClient,
     ) -> Result<ConnectResponse, ConnectError> {
-        connect::connect(&self.pool, default_cluster, request, client).await
+        let _timer = DATABASE_METRICS.query_timer("connect");
+        connect::connect(
+            &self.pool,
+            default_cluster,
+            allowed_cluster,
+            request,
+            client,
+        )
+        .await
     }
     pub async fn revoke(&self, request: &RevokeRequest) -> Result<(), ConnectError> {
+        let _timer = DATABASE_METRICS.query_timer("revoke");
         connect::revoke(&self.pool, request).await
     }
 
+    pub async fn refresh_token(
+        &self,
+        backend: &BackendName,
+        request: &TokenRefreshRequest,
+    ) -> Result<Option<DateTime<Utc>>, ConnectError> {
+        let _timer = DATABASE_METRICS.query_timer("refresh_token");
+        connect::refresh_token(&self.pool, backend, request).await
+    }
+
     pub async fn clean_up_tokens(&self) -> Result<(), sqlx::Error> {
         connect::clean_up_tokens(&self.pool).await
     }
@@ -1,5 +1,6 @@
+use super::metrics::DATABASE_METRICS;
 use super::{
-    subscribe::{emit, NotificationPayload},
+    subscribe::{emit, emit_with_key, NotificationPayload},
     util::MapSqlxError,
 };
 use crate::heartbeat_consts::UNHEALTHY_SECONDS;
@@ -7,7 +8,7 @@ use anyhow::Result;
 use chrono::{DateTime, Utc};
 use plane_common::{
     names::{AnyNodeName, ControllerName, NodeName},
-    types::{ClusterName, NodeId, NodeKind},
+    types::{ClusterEvent, ClusterName, NodeId, NodeKind},
     version::PlaneVersionInfo,
 };
 use serde::{Deserialize, Serialize};
@@ -64,6 +65,7 @@ impl<'a> NodeDatabase<'a> {
         version: &PlaneVersionInfo,
         ip: IpAddr,
     ) -> sqlx::Result<(NodeId, DateTime<Utc>)> {
+        let _timer = DATABASE_METRICS.query_timer("node.register");
         let mut txn = self.pool.begin().await?;
 
         let ip: IpNetwork = ip.into();
@@ -108,6 +110,15 @@ impl<'a> NodeDatabase<'a> {
         )
         .await?;
 
+        if let Some(cluster) = cluster {
+            emit_with_key(
+                &mut txn,
+                &cluster.to_string(),
+                &ClusterEvent::NodeConnected { node: name.clone() },
+            )
+            .await?;
+        }
+
         txn.commit().await?;
 
         Ok((NodeId::from(result.id), result.connection_start_time))
@@ -130,21 +141,34 @@ impl<'a> NodeDatabase<'a> {
         )
         .await?;
 
-        query!(
+        let result = query!(
             r#"
             update node
             set controller = null
             where id = $1
             and controller = $2
             and last_connection_start_time = $3
+            returning cluster, name
             "#,
             node_id.as_i32(),
             controller.to_string(),
             connection_start_time,
         )
-        .execute(&mut *txn)
+        .fetch_optional(&mut *txn)
         .await?;
 
+        if let Some(result) = result {
+            if let Some(cluster) = result.cluster {
+                match AnyNodeName::try_from(result.name) {
+                    Ok(node) => {
+                        emit_with_key(&mut txn, &cluster, &ClusterEvent::NodeDisconnected { node })
+                            .await?;
+                    }
+                    Err(err) => tracing::warn!(?err, "Invalid node name in database."),
+                }
+            }
+        }
+
         txn.commit().await?;
 
         Ok(())
@@ -0,0 +1,271 @@
+use super::{
+    backend::emit_state_change,
+    backend_actions::create_pending_action,
+    backend_key::KEY_LEASE_EXPIRATION,
+    connect::{check_drone_capacity, pick_placement, spawn_action, ConnectError},
+    PlaneDatabase,
+};
+use plane_common::{
+    names::BackendName,
+    types::{
+        BackendState, BackendStatus, BearerToken, ClusterName, KeyConfig, SpawnConfig,
+        TerminationReason,
+    },
+};
+use sqlx::postgres::types::PgInterval;
+use std::{str::FromStr, time::Duration};
+
+pub struct SpawnQueueDatabase<'a> {
+    db: &'a PlaneDatabase,
+}
+
+impl<'a> SpawnQueueDatabase<'a> {
+    pub fn new(db: &'a PlaneDatabase) -> Self {
+        Self { db }
+    }
+
+    /// Removes a backend from the queue, so that it will not be scheduled. Returns false if the
+    /// backend was not queued, e.g. because it was scheduled concurrently.
+    pub async fn dequeue(&self, backend_id: &BackendName) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            delete from backend_queue
+            where backend_id = $1
+            "#,
+            backend_id.to_string(),
+        )
+        .execute(&self.db.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Terminates queued backends whose queue timeout has passed. Returns the number of
+    /// backends terminated.
+    pub async fn expire_queued_backends(&self) -> Result<u32, ConnectError> {
+        let mut txn = self.db.pool.begin().await?;
+
+        let expired = sqlx::query_scalar!(
+            r#"
+            select backend_id
+            from backend_queue
+            where expires_at < now()
+            for update skip locked
+            "#,
+        )
+        .fetch_all(&mut *txn)
+        .await?;
+
+        let mut count = 0;
+        for backend_id in expired {
+            let backend_id = BackendName::try_from(backend_id)
+                .map_err(|_| ConnectError::Other("Invalid backend name.".to_string()))?;
+
+            // The queue row stays locked until the backend is terminated, so the backend can't
+            // be scheduled in the meantime.
+            let terminated = self
+                .db
+                .backend()
+                .update_state(
+                    &backend_id,
+                    BackendState::Queued
+                        .to_terminating(TerminationReason::QueueTimeout)
+                        .to_terminated(None),
+                )
+                .await?;
+
+            remove_from_queue(&mut txn, &backend_id).await?;
+
+            if terminated {
+                tracing::info!(%backend_id, "Queued backend timed out.");
+                count += 1;
+            }
+        }
+
+        txn.commit().await?;
+
+        Ok(count)
+    }
+
+    /// Schedules queued backends to drones, in the order they were queued. Backends that no
+    /// drone can currently run stay queued. Returns the number of backends scheduled.
+    pub async fn place_queued_backends(&self) -> Result<u32, ConnectError> {
+        let queued = sqlx::query_scalar!(
+            r#"
+            select backend_id
+            from backend_queue
+            where expires_at >= now()
+            order by created_at
+            "#,
+        )
+        .fetch_all(&self.db.pool)
+        .await?;
+
+        let mut count = 0;
+        for backend_id in queued {
+            let backend_id = BackendName::try_from(backend_id)
+                .map_err(|_| ConnectError::Other("Invalid backend name.".to_string()))?;
+
+            if self.place_queued_backend(&backend_id).await? {
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Attempts to schedule a single queued backend to a drone. Returns false if the backend
+    /// is being scheduled by another controller, or no drone can currently run it.
+    async fn place_queued_backend(&self, backend_id: &BackendName) -> Result<bool, ConnectError> {
+        let mut txn = self.db.pool.begin().await?;
+
+        let Some(queued) = sqlx::query!(
+            r#"
+            select
+                backend_queue.cluster,
+                backend_queue.spawn_config,
+                backend.last_status,
+                backend.static_token,
+                backend_key.key_name as "key_name?",
+                backend_key.namespace as "namespace?",
+                backend_key.tag as "tag?"
+            from backend_queue
+            inner join backend on backend.id = backend_queue.backend_id
+            left join backend_key on backend_key.id = backend_queue.backend_id
+            where backend_queue.backend_id = $1
+            for update of backend_queue, backend skip locked
+            "#,
+            backend_id.to_string(),
+        )
+        .fetch_optional(&mut *txn)
+        .await?
+        else {
+            return Ok(false);
+        };
+
+        let (Some(name), Some(namespace), Some(tag)) =
+            (queued.key_name, queued.namespace, queued.tag)
+        else {
+            // The backend no longer holds its key, so it was terminated while queued.
+            remove_from_queue(&mut txn, backend_id).await?;
+            txn.commit().await?;
+            return Ok(false);
+        };
+
+        if queued.last_status != BackendStatus::Queued.to_string() {
+            remove_from_queue(&mut txn, backend_id).await?;
+            txn.commit().await?;
+            return Ok(false);
+        }
+
+        let key = KeyConfig {
+            name,
+            namespace,
+            tag,
+        };
+        let cluster = ClusterName::from_str(&queued.cluster)
+            .map_err(|_| ConnectError::Other("Invalid cluster name.".to_string()))?;
+        let spawn_config: SpawnConfig = serde_json::from_value(queued.spawn_config)?;
+        let static_token = queued.static_token.map(BearerToken::from);
+
+        let (Some((_, drone)), _) = pick_placement(&self.db.pool, &cluster, &spawn_config).await?
+        else {
+            return Ok(false);
+        };
+
+        match check_drone_capacity(&mut txn, &drone).await {
+            Ok(()) => {}
+            Err(ConnectError::DroneAtCapacity) => return Ok(false),
+            Err(err) => return Err(err),
+        }
+
+        let new_state = BackendState::Scheduled;
+        let new_status = new_state.status();
+
+        sqlx::query!(
+            r#"
+            update backend
+            set
+                drone_id = $2,
+                last_status = $3,
+                last_status_time = now(),
+                last_status_number = $4,
+                state = $5,
+                expiration_time = now() + $6,
+                last_keepalive = now()
+            where id = $1
+            "#,
+            backend_id.to_string(),
+            drone.id.as_i32(),
+            new_status.to_string(),
+            new_status.as_int(),
+            serde_json::to_value(&new_state).expect("state is always serializable"),
+            spawn_config
+                .lifetime_limit_seconds
+                .map(
+                    |limit| PgInterval::try_from(Duration::from_secs(limit as _))
+                        .expect("valid interval")
+                ),
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        // From here on, the drone renews the key as it would for any other backend.
+        let fencing_token = sqlx::query_scalar!(
+            r#"
+            update backend_key
+            set expires_at = now() + $2
+            where id = $1
+            returning fencing_token
+            "#,
+            backend_id.to_string(),
+            PgInterval::try_from(KEY_LEASE_EXPIRATION).expect("valid constant interval"),
+        )
+        .fetch_one(&mut *txn)
+        .await?;
+
+        emit_state_change(
+            &mut txn,
+            backend_id,
+            &cluster,
+            Some(&key),
+            Some(BackendStatus::Queued),
+            &new_state,
+        )
+        .await?;
+
+        let pending_action = spawn_action(
+            &key,
+            fencing_token,
+            &drone,
+            &spawn_config,
+            static_token.as_ref(),
+        );
+        create_pending_action(&mut txn, backend_id, drone.id, &pending_action).await?;
+
+        remove_from_queue(&mut txn, backend_id).await?;
+
+        txn.commit().await?;
+
+        tracing::info!(%backend_id, drone = %drone.drone, "Scheduled queued backend.");
+
+        Ok(true)
+    }
+}
+
+async fn remove_from_queue(
+    txn: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    backend_id: &BackendName,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"
+        delete from backend_queue
+        where backend_id = $1
+        "#,
+        backend_id.to_string(),
+    )
+    .execute(&mut **txn)
+    .await?;
+
+    Ok(())
+}
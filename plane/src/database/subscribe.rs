@@ -14,6 +14,7 @@ use tokio::{
     task::JoinHandle,
 };
 
+use super::metrics::DATABASE_METRICS;
 use crate::database::util::MapSqlxError;
 
 type ListenerMap = Arc<RwLock<HashMap<(String, Option<String>), Box<dyn TypedSender>>>>;
@@ -266,7 +267,7 @@ impl EventSubscriptionManager {
     }
 
     pub async fn clean_up_events(db: &PgPool, min_age_days: i32) -> Result<(), sqlx::Error> {
-        sqlx::query!(
+        let result = sqlx::query!(
             r#"
             delete from event
             where created_at < now() - make_interval(days => $1)
@@ -276,6 +277,8 @@ impl EventSubscriptionManager {
         .execute(db)
         .await?;
 
+        DATABASE_METRICS.record_rows_deleted("event", result.rows_affected());
+
         Ok(())
     }
 
@@ -399,6 +402,35 @@ pub async fn emit_with_key<T: NotificationPayload>(
     emit_impl(db, Some(key), payload).await
 }
 
+/// Notifies subscribers without storing the notification in the event table, so it has no id
+/// and is not replayed to subscribers that reconnect.
+pub async fn emit_ephemeral_with_key<T: NotificationPayload>(
+    db: &mut PgConnection,
+    key: &str,
+    payload: &T,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        select pg_notify(
+            $4,
+            json_build_object(
+                'payload', $3::jsonb,
+                'timestamp', now(),
+                'kind', $1::text,
+                'key', $2::text
+            )::text
+        )"#,
+        T::kind().to_string(),
+        key,
+        serde_json::to_value(payload).map_sqlx_error()?,
+        EVENT_CHANNEL,
+    )
+    .execute(db)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn emit_backend_metrics(
     db: &mut PgConnection,
     key: &str,
@@ -1,8 +1,16 @@
+use data_encoding::HEXLOWER;
+
 /// Unique violation error code in Postgres.
 /// From: https://www.postgresql.org/docs/9.2/errcodes-appendix.html
 pub const PG_UNIQUE_VIOLATION_ERROR: &str = "23505";
 pub const PG_FOREIGN_KEY_VIOLATION_ERROR: &str = "23503";
 
+/// Hashes the secret part of a credential (such as an API key) for storage, so that the
+/// secret itself does not need to be stored.
+pub fn hash_secret(secret: &str) -> String {
+    HEXLOWER.encode(&openssl::sha::sha256(secret.as_bytes()))
+}
+
 pub fn unique_violation_to_option<T>(result: sqlx::Result<T>) -> sqlx::Result<Option<T>> {
     match result {
         Ok(result) => Ok(Some(result)),
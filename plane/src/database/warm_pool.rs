@@ -0,0 +1,215 @@
+use super::{
+    backend_actions::create_pending_action,
+    connect::{spawn_warm_backend, ConnectError},
+};
+use plane_common::{
+    names::{BackendName, Name, WarmPoolName},
+    protocol::BackendAction,
+    types::{
+        BackendStatus, ClusterName, CreateWarmPoolRequest, DockerExecutorConfig, DronePoolName,
+        NodeId, TerminationKind, TerminationReason, WarmPool,
+    },
+};
+use serde_json::Value;
+use sqlx::PgPool;
+
+/// Returns the executable config in a canonical form, so that configs which differ only in
+/// omitted default fields compare equal.
+pub fn normalize_executable(executable: &Value) -> Value {
+    serde_json::from_value::<DockerExecutorConfig>(executable.clone())
+        .ok()
+        .and_then(|config| serde_json::to_value(config).ok())
+        .unwrap_or_else(|| executable.clone())
+}
+
+pub struct WarmPoolDatabase<'a> {
+    pool: &'a PgPool,
+}
+
+impl<'a> WarmPoolDatabase<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        cluster: &ClusterName,
+        request: &CreateWarmPoolRequest,
+    ) -> sqlx::Result<WarmPool> {
+        let id = WarmPoolName::new_random();
+        let executable = normalize_executable(&request.executable);
+
+        sqlx::query!(
+            r#"
+            insert into warm_pool (id, cluster, pool, executable, size)
+            values ($1, $2, $3, $4, $5)
+            "#,
+            id.to_string(),
+            cluster.to_string(),
+            request.pool.to_string(),
+            executable,
+            request.size as i32,
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(WarmPool {
+            id,
+            cluster: cluster.clone(),
+            pool: request.pool.clone(),
+            executable,
+            size: request.size,
+            ready: 0,
+            starting: 0,
+        })
+    }
+
+    /// Lists all warm pools, with the number of unclaimed backends in each.
+    pub async fn list(&self) -> sqlx::Result<Vec<WarmPool>> {
+        let rows = sqlx::query!(
+            r#"
+            select
+                warm_pool.id,
+                warm_pool.cluster,
+                warm_pool.pool,
+                warm_pool.executable,
+                warm_pool.size,
+                count(backend.id) filter (where backend.last_status = $1) as "ready!",
+                count(backend.id) filter (where backend.last_status_number < $2) as "starting!"
+            from warm_pool
+            left join backend on backend.warm_pool_id = warm_pool.id
+            group by warm_pool.id
+            order by warm_pool.created_at
+            "#,
+            BackendStatus::Ready.to_string(),
+            BackendStatus::Ready.as_int(),
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(WarmPool {
+                    id: WarmPoolName::try_from(row.id)
+                        .map_err(|_| sqlx::Error::Decode("Invalid warm pool name.".into()))?,
+                    cluster: row
+                        .cluster
+                        .parse()
+                        .map_err(|err: &str| sqlx::Error::Decode(err.into()))?,
+                    pool: DronePoolName::from(row.pool),
+                    executable: row.executable,
+                    size: row.size.max(0) as u32,
+                    ready: row.ready as u32,
+                    starting: row.starting as u32,
+                })
+            })
+            .collect()
+    }
+
+    /// Deletes a warm pool, and soft-terminates its unclaimed backends. Returns false if the
+    /// warm pool does not exist.
+    pub async fn delete(&self, id: &WarmPoolName) -> Result<bool, ConnectError> {
+        let mut txn = self.pool.begin().await?;
+
+        let backends = sqlx::query!(
+            r#"
+            select id, drone_id
+            from backend
+            where warm_pool_id = $1 and last_status != $2
+            for update
+            "#,
+            id.to_string(),
+            BackendStatus::Terminated.to_string(),
+        )
+        .fetch_all(&mut *txn)
+        .await?;
+
+        let result = sqlx::query!(
+            r#"
+            delete from warm_pool
+            where id = $1
+            "#,
+            id.to_string(),
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        for backend in backends {
+            let backend_id = BackendName::try_from(backend.id)
+                .map_err(|_| ConnectError::Other("Invalid backend name.".to_string()))?;
+            // Warm pool backends are never queued, so they always have a drone.
+            let Some(drone_id) = backend.drone_id else {
+                continue;
+            };
+            create_pending_action(
+                &mut txn,
+                &backend_id,
+                NodeId::from(drone_id),
+                &BackendAction::Terminate {
+                    kind: TerminationKind::Soft,
+                    reason: TerminationReason::External,
+                },
+            )
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Spawns backends for the warm pool until it has as many unclaimed, non-terminating
+    /// backends as its size. Returns the number of backends spawned.
+    ///
+    /// The warm pool is locked while it is replenished, so that controllers replenishing
+    /// concurrently skip it rather than spawning duplicate backends. The lock does not block
+    /// the foreign key checks of the backends being spawned.
+    pub async fn replenish(&self, id: &WarmPoolName) -> Result<u32, ConnectError> {
+        let mut txn = self.pool.begin().await?;
+
+        let Some(warm_pool) = sqlx::query!(
+            r#"
+            select cluster, pool, executable, size
+            from warm_pool
+            where id = $1
+            for no key update skip locked
+            "#,
+            id.to_string(),
+        )
+        .fetch_optional(&mut *txn)
+        .await?
+        else {
+            return Ok(0);
+        };
+
+        let unclaimed = sqlx::query_scalar!(
+            r#"
+            select count(1) as "count!"
+            from backend
+            where warm_pool_id = $1 and last_status_number <= $2
+            "#,
+            id.to_string(),
+            BackendStatus::Ready.as_int(),
+        )
+        .fetch_one(&mut *txn)
+        .await?;
+
+        let cluster: ClusterName = warm_pool
+            .cluster
+            .parse()
+            .map_err(|_| ConnectError::Other("Invalid cluster name.".to_string()))?;
+        let drone_pool = DronePoolName::from(warm_pool.pool);
+        let deficit = (warm_pool.size as i64 - unclaimed).max(0) as u32;
+
+        for _ in 0..deficit {
+            let backend_id =
+                spawn_warm_backend(self.pool, id, &cluster, &drone_pool, &warm_pool.executable)
+                    .await?;
+            tracing::info!(%backend_id, warm_pool = %id, "Spawned warm backend.");
+        }
+
+        txn.commit().await?;
+
+        Ok(deficit)
+    }
+}
@@ -0,0 +1,225 @@
+use super::metrics::DATABASE_METRICS;
+use plane_common::util::random_token;
+use serde_json::Value;
+use sqlx::{postgres::types::PgInterval, PgPool};
+use std::time::Duration;
+
+/// How long a controller's claim on a delivery lasts. Controllers renew their claims while
+/// they are delivering, so a delivery is only retried by another controller once its
+/// controller has stopped.
+pub const DELIVERY_CLAIM_DURATION: Duration = Duration::from_secs(90);
+
+pub struct WebhookDatabase<'a> {
+    pool: &'a PgPool,
+}
+
+/// A controller's claim on a delivery. Claims can only be renewed, and attempts recorded,
+/// by the holder of the claim's token, so a controller whose claim lapsed and was taken over
+/// by another controller can't interfere with that controller's delivery.
+#[derive(Clone, Debug)]
+pub struct DeliveryClaim {
+    pub delivery_id: i32,
+    token: String,
+}
+
+/// A delivery that has neither been delivered nor abandoned.
+pub struct PendingDelivery {
+    pub claim: DeliveryClaim,
+    pub url: String,
+    pub payload: Value,
+    pub attempts: i32,
+}
+
+/// The outcome of a single attempt to deliver a webhook.
+pub enum DeliveryAttempt {
+    /// The receiver returned a successful status code.
+    Delivered { status_code: u16 },
+
+    /// The attempt failed and will be retried.
+    Failed {
+        status_code: Option<u16>,
+        error: String,
+    },
+
+    /// The attempt failed, and no further attempts will be made.
+    Abandoned {
+        status_code: Option<u16>,
+        error: String,
+    },
+}
+
+impl<'a> WebhookDatabase<'a> {
+    pub fn new(pool: &'a PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Records a new delivery of the given event to the given URL, claimed by the calling
+    /// controller, and returns the claim.
+    ///
+    /// Returns None if a delivery for this event and URL already exists, which happens when
+    /// another controller observed the same event first. In that case, that controller is
+    /// responsible for delivering it.
+    pub async fn create_delivery(
+        &self,
+        event_id: i32,
+        url: &str,
+        payload: &Value,
+    ) -> sqlx::Result<Option<DeliveryClaim>> {
+        let token = random_token();
+        let result = sqlx::query!(
+            r#"
+            insert into webhook_delivery (event_id, url, payload, claimed_until, claim_token)
+            values ($1, $2, $3, now() + $4, $5)
+            on conflict (event_id, url) do nothing
+            returning id
+            "#,
+            event_id,
+            url,
+            payload,
+            claim_interval(),
+            token,
+        )
+        .fetch_optional(self.pool)
+        .await?;
+
+        Ok(result.map(|row| DeliveryClaim {
+            delivery_id: row.id,
+            token,
+        }))
+    }
+
+    /// Claims up to `limit` pending deliveries to the given URLs that no controller holds a
+    /// claim on, such as deliveries whose controller stopped before finishing them.
+    pub async fn claim_pending_deliveries(
+        &self,
+        urls: &[String],
+        limit: i64,
+    ) -> sqlx::Result<Vec<PendingDelivery>> {
+        let token = random_token();
+        let rows = sqlx::query!(
+            r#"
+            update webhook_delivery
+            set claimed_until = now() + $1, claim_token = $4
+            where id in (
+                select id
+                from webhook_delivery
+                where delivered_at is null
+                and failed_at is null
+                and (claimed_until is null or claimed_until < now())
+                and url = any($2)
+                order by id
+                limit $3
+                for update skip locked
+            )
+            returning id, url, payload, attempts
+            "#,
+            claim_interval(),
+            urls,
+            limit,
+            token,
+        )
+        .fetch_all(self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| PendingDelivery {
+                claim: DeliveryClaim {
+                    delivery_id: row.id,
+                    token: token.clone(),
+                },
+                url: row.url,
+                payload: row.payload,
+                attempts: row.attempts,
+            })
+            .collect())
+    }
+
+    /// Extends a claim on a delivery. Returns false if the claim is no longer held, because
+    /// the delivery was claimed by another controller or has finished.
+    pub async fn renew_claim(&self, claim: &DeliveryClaim) -> sqlx::Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            update webhook_delivery
+            set claimed_until = now() + $3
+            where id = $1
+            and claim_token = $2
+            and delivered_at is null
+            and failed_at is null
+            "#,
+            claim.delivery_id,
+            claim.token,
+            claim_interval(),
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Records an attempt made under a claim. Returns false, without recording the attempt,
+    /// if the claim is no longer held.
+    pub async fn record_attempt(
+        &self,
+        claim: &DeliveryClaim,
+        attempt: &DeliveryAttempt,
+    ) -> sqlx::Result<bool> {
+        let (status_code, error, delivered, abandoned) = match attempt {
+            DeliveryAttempt::Delivered { status_code } => (Some(*status_code), None, true, false),
+            DeliveryAttempt::Failed { status_code, error } => {
+                (*status_code, Some(error.as_str()), false, false)
+            }
+            DeliveryAttempt::Abandoned { status_code, error } => {
+                (*status_code, Some(error.as_str()), false, true)
+            }
+        };
+
+        let result = sqlx::query!(
+            r#"
+            update webhook_delivery
+            set
+                attempts = attempts + 1,
+                last_attempt_at = now(),
+                last_status_code = $2,
+                last_error = $3,
+                delivered_at = case when $4 then now() else null end,
+                failed_at = case when $5 then now() else null end
+            where id = $1
+            and claim_token = $6
+            and delivered_at is null
+            and failed_at is null
+            "#,
+            claim.delivery_id,
+            status_code.map(i32::from),
+            error,
+            delivered,
+            abandoned,
+            claim.token,
+        )
+        .execute(self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Deletes deliveries older than the given number of days.
+    pub async fn clean_up_deliveries(&self, min_age_days: i32) -> sqlx::Result<()> {
+        let result = sqlx::query!(
+            r#"
+            delete from webhook_delivery
+            where created_at < now() - make_interval(days => $1)
+            "#,
+            min_age_days,
+        )
+        .execute(self.pool)
+        .await?;
+
+        DATABASE_METRICS.record_rows_deleted("webhook_delivery", result.rows_affected());
+
+        Ok(())
+    }
+}
+
+fn claim_interval() -> PgInterval {
+    PgInterval::try_from(DELIVERY_CLAIM_DURATION).expect("valid constant interval")
+}
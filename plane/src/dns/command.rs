@@ -10,6 +10,10 @@ pub struct DnsOpts {
     #[clap(long)]
     controller_url: Url,
 
+    /// Join token to present to the controller, if it requires one.
+    #[clap(long)]
+    join_token: Option<String>,
+
     /// Suffix to strip from requests before looking up TXT records.
     /// E.g. if the zone is "example.com", a TXT record lookup
     /// for foo.bar.baz.example.com
@@ -29,6 +33,7 @@ impl DnsOpts {
         crate::dns::DnsConfig {
             name: self.name.or_random(),
             controller_url: self.controller_url,
+            join_token: self.join_token,
             port: self.port,
             zone: Some(self.zone),
         }
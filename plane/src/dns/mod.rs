@@ -254,12 +254,19 @@ pub async fn run_dns_with_listener(
 pub struct DnsConfig {
     pub name: AcmeDnsServerName,
     pub controller_url: Url,
+
+    /// Join token presented to the controller, if it requires one.
+    pub join_token: Option<String>,
+
     pub port: u16,
     pub zone: Option<String>,
 }
 
 pub async fn run_dns(config: DnsConfig) -> anyhow::Result<()> {
-    let client = PlaneClient::new(config.controller_url);
+    let mut client = PlaneClient::new(config.controller_url);
+    if let Some(join_token) = &config.join_token {
+        client = client.with_join_token(join_token);
+    }
     let ip_port_pair = (Ipv4Addr::UNSPECIFIED, config.port);
     let listener = TcpListener::bind(ip_port_pair).await?;
     run_dns_with_listener(config.name, client, listener, config.zone)
@@ -1,20 +1,28 @@
-use crate::{drone::runtime::Runtime, util::GuardHandle};
+use crate::{
+    drone::{
+        metrics::DRONE_METRICS,
+        runtime::{docker::SpawnResult, Runtime},
+    },
+    util::GuardHandle,
+};
 use anyhow::Result;
 use futures_util::Future;
 use plane_common::{
     exponential_backoff::ExponentialBackoff,
+    log_types::{BackendAddr, NamedBackendAddrs},
     names::BackendName,
     protocol::AcquiredKey,
     types::{
         backend_state::{BackendError, TerminationReason},
-        BackendState, BearerToken, TerminationKind,
+        BackendState, BackendStatus, BearerToken, DockerExecutorConfig, LivenessProbe,
+        RestartPolicy, TerminationKind,
     },
 };
 use std::{error::Error, fmt::Debug};
 use std::{future::pending, pin::Pin};
 use std::{
     net::IpAddr,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, MutexGuard},
 };
 use valuable::Valuable;
 
@@ -77,6 +85,9 @@ pub struct BackendManager {
 
     /// Static token to use for the backend.
     static_token: Option<BearerToken>,
+
+    /// Whether to restart the backend's process when it exits with an error.
+    restart_policy: RestartPolicy,
 }
 
 impl Debug for BackendManager {
@@ -87,6 +98,27 @@ impl Debug for BackendManager {
     }
 }
 
+/// Returns the addresses of a spawned backend's named ports on the drone.
+fn named_addresses(ip: IpAddr, spawn_result: &SpawnResult) -> NamedBackendAddrs {
+    spawn_result
+        .named_ports
+        .iter()
+        .map(|(name, port)| (name.clone(), BackendAddr((ip, *port).into())))
+        .collect()
+}
+
+/// Returns the liveness probe of the backend's executor config, if it has one.
+fn liveness_probe(executable: &serde_json::Value) -> Option<LivenessProbe> {
+    let executable: DockerExecutorConfig = match serde_json::from_value(executable.clone()) {
+        Ok(executable) => executable,
+        Err(err) => {
+            tracing::error!(?err, "Invalid executor config.");
+            return None;
+        }
+    };
+    executable.liveness_probe
+}
+
 fn handle_terminating(
     runtime: Arc<Box<dyn Runtime>>,
     backend_id: &BackendName,
@@ -94,8 +126,28 @@ fn handle_terminating(
     hard_terminate: bool,
 ) -> StepStatusResult {
     let backend_id = backend_id.clone();
+    let suspended = matches!(
+        state,
+        BackendState::Terminating {
+            last_status: BackendStatus::Suspended,
+            ..
+        } | BackendState::HardTerminating {
+            last_status: BackendStatus::Suspended,
+            ..
+        }
+    );
 
     StepStatusResult::future_status(async move {
+        // A paused process can't handle the signal to exit, so it is unpaused first.
+        if suspended {
+            if let Err(err) = runtime.resume(&backend_id).await {
+                tracing::warn!(
+                    ?err,
+                    "failed to resume suspended backend before terminating"
+                );
+            }
+        }
+
         let mut backoff = ExponentialBackoff::default();
 
         loop {
@@ -126,6 +178,7 @@ impl BackendManager {
         ip: IpAddr,
         acquired_key: AcquiredKey,
         static_token: Option<BearerToken>,
+        restart_policy: RestartPolicy,
     ) -> Arc<Self> {
         let manager = Arc::new(Self {
             state: Mutex::new(BackendManagerState {
@@ -139,6 +192,7 @@ impl BackendManager {
             ip,
             acquired_key,
             static_token,
+            restart_policy,
         });
 
         manager.set_state(state);
@@ -147,7 +201,10 @@ impl BackendManager {
 
     fn step_state(&self, state: BackendState) -> StepStatusResult {
         match state {
-            BackendState::Scheduled => StepStatusResult::SetState(state.to_loading()),
+            // Backends are only sent to the drone once they leave the queue.
+            BackendState::Queued | BackendState::Scheduled => {
+                StepStatusResult::SetState(state.to_loading())
+            }
             BackendState::Loading => {
                 let executor_config = self.backend_config.clone();
                 let runtime = self.runtime.clone();
@@ -156,6 +213,7 @@ impl BackendManager {
                     tracing::info!(%backend_id, "preparing...");
                     if let Err(err) = runtime.prepare(&executor_config).await {
                         tracing::error!(?err, %backend_id, "failed to prepare");
+                        DRONE_METRICS.record_spawn_failure("prepare");
                         state.to_terminated(None)
                     } else {
                         tracing::info!(%backend_id, "done preparing...");
@@ -185,32 +243,107 @@ impl BackendManager {
                         Ok(spawn_result) => spawn_result,
                         Err(err) => {
                             tracing::error!(?err, "failed to spawn backend");
+                            DRONE_METRICS.record_spawn_failure("spawn");
                             return state.to_terminated(None);
                         }
                     };
 
                     let address = (ip, spawn_result.port).into();
-                    state.to_waiting(address)
+                    state.to_waiting(address, named_addresses(ip, &spawn_result))
+                })
+            }
+            BackendState::Restarting { restart } => {
+                let backend_id = self.backend_id.clone();
+                let runtime = self.runtime.clone();
+                let ip = self.ip;
+                // The restart count includes this restart, so the delay is based on the
+                // number of restarts before it.
+                let delay = self
+                    .restart_policy
+                    .restart_delay(
+                        restart.restart_count.saturating_sub(1),
+                        restart.last_exit_code,
+                    )
+                    .unwrap_or_default();
+
+                StepStatusResult::future_status(async move {
+                    tokio::time::sleep(delay).await;
+
+                    match runtime.restart(&backend_id).await {
+                        Ok(spawn_result) => {
+                            DRONE_METRICS.record_restart();
+                            state.to_restarted(
+                                (ip, spawn_result.port).into(),
+                                named_addresses(ip, &spawn_result),
+                            )
+                        }
+                        Err(err) => {
+                            tracing::error!(?err, "failed to restart backend");
+                            state.to_terminated(restart.last_exit_code)
+                        }
+                    }
                 })
             }
-            BackendState::Waiting { address } => {
+            BackendState::Waiting { address, .. } => {
                 let backend_id = self.backend_id.clone();
                 let runtime = self.runtime.clone();
+                let executor_config = self.backend_config.clone();
                 StepStatusResult::future_status(async move {
-                    match runtime.wait_for_backend(&backend_id, address.0).await {
+                    match runtime
+                        .wait_for_backend(&backend_id, &executor_config, address.0)
+                        .await
+                    {
                         Ok(()) => state.to_ready(address),
                         Err(BackendError::StartupTimeout) => {
                             tracing::error!("Backend startup timeout");
+                            DRONE_METRICS.record_spawn_failure("startup_timeout");
                             state.to_hard_terminating(TerminationReason::StartupTimeout)
                         }
                         Err(BackendError::Other(msg)) => {
                             tracing::error!("Failed to wait for backend: {}", msg);
+                            DRONE_METRICS.record_spawn_failure("wait");
                             state.to_hard_terminating(TerminationReason::InternalError)
                         }
                     }
                 })
             }
-            BackendState::Ready { .. } => StepStatusResult::DoNothing,
+            BackendState::Ready { address, .. } => {
+                let Some(probe) = liveness_probe(&self.backend_config) else {
+                    return StepStatusResult::DoNothing;
+                };
+                let backend_id = self.backend_id.clone();
+                let runtime = self.runtime.clone();
+
+                // The probe runs until the backend leaves the ready state, which drops this task.
+                StepStatusResult::future_status(async move {
+                    let mut failures = 0;
+                    loop {
+                        tokio::time::sleep(probe.interval()).await;
+
+                        let passed = tokio::time::timeout(
+                            probe.timeout(),
+                            runtime.check_liveness(&backend_id, address.0, &probe.check),
+                        )
+                        .await
+                        .unwrap_or(false);
+
+                        if passed {
+                            failures = 0;
+                            continue;
+                        }
+
+                        failures += 1;
+                        tracing::warn!(%backend_id, failures, "Backend failed liveness check.");
+
+                        if failures >= probe.failure_threshold() {
+                            tracing::error!(%backend_id, "Terminating unhealthy backend.");
+                            DRONE_METRICS.record_unhealthy_termination();
+                            return state.to_hard_terminating(TerminationReason::Unhealthy);
+                        }
+                    }
+                })
+            }
+            BackendState::Suspended { .. } => StepStatusResult::DoNothing,
             BackendState::Terminating { .. } => {
                 handle_terminating(self.runtime.clone(), &self.backend_id, state, false)
             }
@@ -222,8 +355,27 @@ impl BackendManager {
     }
 
     pub fn set_state(self: &Arc<Self>, state: BackendState) {
-        let mut lock = self.state.lock().expect("State lock is poisoned");
+        let lock = self.state.lock().expect("State lock is poisoned");
+        self.set_state_locked(lock, state);
+    }
+
+    /// Sets the state only if it is still `expected`, checking and setting it under the same
+    /// lock. Returns false, leaving the state unchanged, if another transition happened first.
+    pub fn set_state_if(self: &Arc<Self>, expected: &BackendState, state: BackendState) -> bool {
+        let lock = self.state.lock().expect("State lock is poisoned");
+        if lock.state != *expected {
+            return false;
+        }
+
+        self.set_state_locked(lock, state);
+        true
+    }
 
+    fn set_state_locked(
+        self: &Arc<Self>,
+        mut lock: MutexGuard<'_, BackendManagerState>,
+        state: BackendState,
+    ) {
         tracing::info!(
             backend_id = self.backend_id.as_value(),
             state = state.as_value(),
@@ -274,6 +426,102 @@ impl BackendManager {
         self.set_state(new_state);
     }
 
+    /// Pauses the backend's process, if the backend is ready.
+    pub fn suspend(self: &Arc<Self>) {
+        let mut lock = self.state.lock().expect("State lock is poisoned");
+        let state = lock.state.clone();
+
+        if !matches!(state, BackendState::Ready { .. }) {
+            tracing::warn!(
+                backend_id = self.backend_id.as_value(),
+                state = state.as_value(),
+                "Not suspending backend that is not ready."
+            );
+            return;
+        }
+
+        let self_clone = self.clone();
+        lock.handle = Some(GuardHandle::new(async move {
+            match self_clone.runtime.suspend(&self_clone.backend_id).await {
+                Ok(()) => self_clone.set_state(state.to_suspended()),
+                // The backend stays ready, and will be suspended on the next sweep.
+                Err(err) => tracing::error!(?err, "failed to suspend backend"),
+            }
+        }));
+    }
+
+    /// Unpauses the backend's process, if the backend is suspended.
+    pub fn resume(self: &Arc<Self>) {
+        let mut lock = self.state.lock().expect("State lock is poisoned");
+        let state = lock.state.clone();
+
+        if !matches!(state, BackendState::Suspended { .. }) {
+            tracing::info!(
+                backend_id = self.backend_id.as_value(),
+                state = state.as_value(),
+                "Not resuming backend that is not suspended."
+            );
+            return;
+        }
+
+        let self_clone = self.clone();
+        lock.handle = Some(GuardHandle::new(async move {
+            let new_state = match self_clone.runtime.resume(&self_clone.backend_id).await {
+                Ok(()) => state.to_resumed(),
+                Err(err) => {
+                    tracing::error!(?err, "failed to resume backend");
+                    state.to_hard_terminating(TerminationReason::InternalError)
+                }
+            };
+            self_clone.set_state(new_state);
+        }));
+    }
+
+    /// Called when the backend's process exits. If the restart policy allows it, moves the
+    /// backend to the restarting state, which restarts the process after the policy's delay,
+    /// and returns true. Otherwise, returns false and the caller should mark the backend as
+    /// terminated.
+    pub fn try_restart(self: &Arc<Self>, exit_code: Option<i32>) -> bool {
+        let state = self
+            .state
+            .lock()
+            .expect("State lock is poisoned")
+            .state
+            .clone();
+
+        // Only a backend that was running is restarted; one that is being terminated, or has
+        // not yet started, is not.
+        if !matches!(
+            state,
+            BackendState::Waiting { .. } | BackendState::Ready { .. }
+        ) {
+            return false;
+        }
+
+        let Some(delay) = self
+            .restart_policy
+            .restart_delay(state.restart_count(), exit_code)
+        else {
+            return false;
+        };
+
+        // The old process is gone, so the backend must not be routed to until the new one
+        // is ready. If the backend was terminated in the meantime, it is not restarted.
+        if !self.set_state_if(&state, state.to_restarting(exit_code)) {
+            return false;
+        }
+
+        tracing::info!(
+            backend_id = self.backend_id.as_value(),
+            exit_code,
+            restart_count = state.restart_count(),
+            ?delay,
+            "Restarting backend."
+        );
+
+        true
+    }
+
     pub fn mark_terminated(self: &Arc<Self>, exit_code: Option<i32>) -> Result<()> {
         let state = self
             .state
@@ -2,7 +2,7 @@ use super::{
     runtime::{docker::DockerRuntimeConfig, unix_socket::UnixSocketRuntimeConfig},
     DroneConfig, ExecutorConfig,
 };
-use crate::util::resolve_hostname;
+use crate::util::{parse_label, resolve_hostname};
 use anyhow::Result;
 use chrono::Duration;
 use clap::Parser;
@@ -21,6 +21,10 @@ pub struct DroneOpts {
     #[clap(long)]
     controller_url: Url,
 
+    /// Join token to present to the controller, if it requires one.
+    #[clap(long)]
+    join_token: Option<String>,
+
     #[clap(long)]
     cluster: ClusterName,
 
@@ -47,6 +51,11 @@ pub struct DroneOpts {
     #[clap(long, default_value_t = DronePoolName::default())]
     pool: DronePoolName,
 
+    /// Label to advertise to the scheduler, as `key=value`. May be repeated. Spawn requests can
+    /// require or prefer drones with particular labels.
+    #[clap(long = "label", value_parser = parse_label)]
+    labels: Vec<(String, String)>,
+
     /// Optional base directory under which backends are allowed to mount directories.
     #[clap(long)]
     mount_base: Option<PathBuf>,
@@ -61,6 +70,25 @@ pub struct DroneOpts {
     /// only backends that were created more than this many seconds ago.
     #[clap(long, default_value = "0")]
     auto_prune_containers_older_than_seconds: i32,
+
+    /// Port to serve Prometheus metrics on. If not set, metrics are not served.
+    #[clap(long)]
+    metrics_port: Option<u16>,
+
+    /// Memory (in bytes) to hold back from backends for the host and drone. The scheduler
+    /// only places backends in the drone's remaining memory.
+    #[clap(long)]
+    reserved_memory_bytes: Option<u64>,
+
+    /// CPU (in millicores, i.e. thousandths of a core) to hold back from backends for the
+    /// host and drone.
+    #[clap(long)]
+    reserved_cpu_millicores: Option<u64>,
+
+    /// Maximum number of backends to run on this drone at once. The controller will not
+    /// schedule backends on the drone while it is running this many.
+    #[clap(long)]
+    max_backends: Option<u32>,
 }
 
 impl DroneOpts {
@@ -94,15 +122,21 @@ impl DroneOpts {
         #[allow(deprecated)]
         let drone_config = DroneConfig {
             controller_url: self.controller_url,
+            join_token: self.join_token,
             name: name.clone(),
             cluster: self.cluster.clone(),
             ip,
             db_path: self.db,
             pool: self.pool,
+            labels: self.labels.into_iter().collect(),
             auto_prune: None,      // deprecated
             cleanup_min_age: None, // deprecated
             docker_config: None,   // deprecated
             executor_config: Some(executor_config),
+            metrics_port: self.metrics_port,
+            reserved_memory_bytes: self.reserved_memory_bytes,
+            reserved_cpu_millicores: self.reserved_cpu_millicores,
+            max_backends: self.max_backends,
         };
 
         Ok(drone_config)
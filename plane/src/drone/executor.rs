@@ -34,6 +34,15 @@ impl Executor {
             .await
             .expect("Failed to terminate all preexisting backends! Locks may be violated, Drone aborting startup.");
 
+        {
+            // A backend's exited process is kept for as long as the backend is tracked, since
+            // the backend may restart it.
+            let backends = backends.clone();
+            runtime.retain_callback(Box::new(move |backend_id| {
+                backends.contains_key(backend_id)
+            }));
+        }
+
         let backend_event_listener = {
             let docker = runtime.clone();
             let backends = backends.clone();
@@ -41,6 +50,13 @@ impl Executor {
             GuardHandle::new(async move {
                 let mut events = docker.events();
                 while let Some(event) = events.next().await {
+                    let manager = backends
+                        .get(&event.backend_id)
+                        .map(|manager| manager.clone());
+                    if manager.is_some_and(|manager| manager.try_restart(event.exit_code)) {
+                        continue;
+                    }
+
                     if let Some((_, manager)) = backends.remove(&event.backend_id) {
                         tracing::info!(
                             backend_id = event.backend_id.as_value(),
@@ -163,6 +179,14 @@ impl Executor {
             .ack_event(event_id)
     }
 
+    /// Counts the backends that are not in a Terminated state, by status.
+    pub fn backend_status_counts(&self) -> Result<Vec<(String, i64)>> {
+        self.state_store
+            .lock()
+            .expect("State store lock poisoned.")
+            .backend_status_counts()
+    }
+
     pub async fn apply_action(
         &self,
         backend_id: &BackendName,
@@ -173,6 +197,7 @@ impl Executor {
                 executable,
                 key,
                 static_token,
+                restart_policy,
             } => {
                 let callback = {
                     let state_store = self.state_store.clone();
@@ -197,6 +222,7 @@ impl Executor {
                     self.ip,
                     key.clone(),
                     static_token.clone(),
+                    restart_policy.clone(),
                 );
                 tracing::info!(backend_id = backend_id.as_value(), "Inserting backend.");
                 self.backends.insert(backend_id.clone(), manager);
@@ -234,6 +260,23 @@ impl Executor {
 
                 manager.terminate(*kind, *reason).await;
             }
+            BackendAction::Suspend | BackendAction::Resume => {
+                let Some(manager) = self.backends.get(backend_id).map(|manager| manager.clone())
+                else {
+                    tracing::warn!(
+                        backend_id = backend_id.as_value(),
+                        ?action,
+                        "Backend not found when handling action."
+                    );
+                    return Ok(());
+                };
+
+                if matches!(action, BackendAction::Suspend) {
+                    manager.suspend();
+                } else {
+                    manager.resume();
+                }
+            }
         }
 
         Ok(())
@@ -1,6 +1,8 @@
+use super::resources::ResourceTracker;
 use crate::heartbeat_consts::HEARTBEAT_INTERVAL;
 use chrono::Utc;
 use plane_common::{log_types::LoggableTime, protocol::Heartbeat, typed_socket::TypedSocketSender};
+use std::sync::Arc;
 use tokio::task::JoinHandle;
 
 /// A background task that sends heartbeats to the server.
@@ -9,11 +11,15 @@ pub struct HeartbeatLoop {
 }
 
 impl HeartbeatLoop {
-    pub fn start(sender: TypedSocketSender<Heartbeat>) -> Self {
+    pub fn start(sender: TypedSocketSender<Heartbeat>, resources: Arc<ResourceTracker>) -> Self {
         let handle = tokio::spawn(async move {
             loop {
                 let local_time = LoggableTime(Utc::now());
-                if let Err(err) = sender.send(Heartbeat { local_time }) {
+                let resources = resources.snapshot();
+                if let Err(err) = sender.send(Heartbeat {
+                    local_time,
+                    resources,
+                }) {
                     tracing::error!(?err, "failed to send heartbeat");
                 }
 
@@ -1,4 +1,4 @@
-use super::executor::Executor;
+use super::{executor::Executor, metrics::DRONE_METRICS};
 use crate::util::GuardHandle;
 use chrono::Utc;
 use plane_common::{
@@ -8,7 +8,11 @@ use plane_common::{
     typed_socket::TypedSocketSender,
     types::{backend_state::TerminationReason, TerminationKind},
 };
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 use tokio::time::sleep;
 use valuable::Valuable;
 
@@ -21,6 +25,10 @@ pub struct KeyManager {
     handles: HashMap<BackendName, (AcquiredKey, GuardHandle)>,
 
     sender: Option<TypedSocketSender<RenewKeyRequest>>,
+
+    /// Map from a backend to the time its outstanding renewal request was sent, used to
+    /// measure renewal latency.
+    pending_renewals: Arc<Mutex<HashMap<BackendName, Instant>>>,
 }
 
 async fn renew_key_loop(
@@ -28,6 +36,7 @@ async fn renew_key_loop(
     backend: BackendName,
     sender: Option<TypedSocketSender<RenewKeyRequest>>,
     executor: Arc<Executor>,
+    pending_renewals: Arc<Mutex<HashMap<BackendName, Instant>>>,
 ) {
     loop {
         let now = Utc::now();
@@ -67,6 +76,7 @@ async fn renew_key_loop(
                 sleep(Duration::from_secs(1)).await;
                 continue;
             }
+            DRONE_METRICS.record_key_renewal_failure("expired");
 
             if let Ok(time_to_sleep) = deadlines
                 .hard_terminate_at
@@ -89,8 +99,14 @@ async fn renew_key_loop(
                     local_time: LoggableTime(Utc::now()),
                 };
 
+                pending_renewals
+                    .lock()
+                    .expect("Pending renewals lock poisoned.")
+                    .insert(backend.clone(), Instant::now());
+
                 if let Err(err) = sender.send(request) {
                     tracing::error!(%err, "Error sending renew key request.");
+                    DRONE_METRICS.record_key_renewal_failure("send_failed");
                 }
             }
 
@@ -117,6 +133,7 @@ impl KeyManager {
             executor,
             handles: HashMap::new(),
             sender: None,
+            pending_renewals: Arc::default(),
         }
     }
 
@@ -129,6 +146,7 @@ impl KeyManager {
                 backend.clone(),
                 self.sender.clone(),
                 self.executor.clone(),
+                self.pending_renewals.clone(),
             ));
 
             *handle = new_handle;
@@ -148,6 +166,7 @@ impl KeyManager {
             backend.clone(),
             self.sender.clone(),
             self.executor.clone(),
+            self.pending_renewals.clone(),
         ));
 
         self.handles.insert(backend, (key, handle));
@@ -155,7 +174,24 @@ impl KeyManager {
         true
     }
 
+    /// Records the latency of a renewal request, if one is outstanding for the backend.
+    fn observe_renewal_response(&self, backend: &BackendName) {
+        let sent_at = self
+            .pending_renewals
+            .lock()
+            .expect("Pending renewals lock poisoned.")
+            .remove(backend);
+
+        if let Some(sent_at) = sent_at {
+            DRONE_METRICS
+                .key_renewal_duration_seconds
+                .observe(sent_at.elapsed().as_secs_f64());
+        }
+    }
+
     pub fn update_deadlines(&mut self, backend: &BackendName, deadlines: KeyDeadlines) {
+        self.observe_renewal_response(backend);
+
         if let Some((key, handle)) = self.handles.get_mut(backend) {
             key.deadlines = deadlines;
 
@@ -164,11 +200,22 @@ impl KeyManager {
                 backend.clone(),
                 self.sender.clone(),
                 self.executor.clone(),
+                self.pending_renewals.clone(),
             ));
         }
     }
 
+    /// Called when the controller declines to renew a backend's key.
+    pub fn renewal_rejected(&mut self, backend: &BackendName) {
+        self.observe_renewal_response(backend);
+        DRONE_METRICS.record_key_renewal_failure("rejected");
+    }
+
     pub fn unregister_key(&mut self, backend: &BackendName) {
         self.handles.remove(backend);
+        self.pending_renewals
+            .lock()
+            .expect("Pending renewals lock poisoned.")
+            .remove(backend);
     }
 }
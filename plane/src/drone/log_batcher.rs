@@ -0,0 +1,79 @@
+use super::metrics::DRONE_METRICS;
+use crate::util::GuardHandle;
+use plane_common::{protocol::BackendLogMessage, typed_socket::TypedSocketSender};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Maximum number of lines sent to the controller in one message.
+const MAX_BATCH_LINES: usize = 200;
+
+/// Maximum time a line waits for other lines to be batched with it.
+const MAX_BATCH_DELAY: Duration = Duration::from_millis(250);
+
+/// Number of lines that can wait to be batched. Lines written while the queue is full are
+/// dropped, so that a noisy backend cannot hold up the drone's other messages.
+const QUEUE_LINES: usize = 10_000;
+
+/// A background task that collects backend log lines and sends them to the controller in
+/// batches, on a queue separate from the drone's other messages.
+pub struct LogBatcher {
+    queue: mpsc::Sender<BackendLogMessage>,
+    _handle: GuardHandle,
+}
+
+impl LogBatcher {
+    pub fn start(sender: TypedSocketSender<Vec<BackendLogMessage>>) -> Self {
+        let (queue, receiver) = mpsc::channel(QUEUE_LINES);
+        let handle = GuardHandle::new(batch_loop(receiver, sender));
+
+        Self {
+            queue,
+            _handle: handle,
+        }
+    }
+
+    /// Returns a handle for queueing lines, which does not keep the batcher running.
+    pub fn queue(&self) -> LogQueue {
+        LogQueue {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LogQueue {
+    queue: mpsc::Sender<BackendLogMessage>,
+}
+
+impl LogQueue {
+    /// Queues a line to be sent. The line is dropped if the queue is full, or if the batcher
+    /// has stopped because the drone is disconnected.
+    pub fn push(&self, message: BackendLogMessage) {
+        if self.queue.try_send(message).is_err() {
+            DRONE_METRICS.record_dropped_log_lines(1);
+        }
+    }
+}
+
+async fn batch_loop(
+    mut receiver: mpsc::Receiver<BackendLogMessage>,
+    sender: TypedSocketSender<Vec<BackendLogMessage>>,
+) {
+    while let Some(first) = receiver.recv().await {
+        let mut batch = vec![first];
+        let deadline = tokio::time::Instant::now() + MAX_BATCH_DELAY;
+
+        while batch.len() < MAX_BATCH_LINES {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(message)) => batch.push(message),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        let count = batch.len();
+        if let Err(err) = sender.send(batch) {
+            tracing::error!(?err, count, "Error sending log lines.");
+            DRONE_METRICS.record_dropped_log_lines(count);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use super::executor::Executor;
+use crate::metrics::metrics_response;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use plane_common::{names::BackendName, protocol::BackendMetricsMessage};
+use prometheus::{
+    histogram_opts, opts, GaugeVec, Histogram, HistogramVec, IntCounter, IntCounterVec,
+    IntGaugeVec, Registry,
+};
+use std::sync::{Arc, LazyLock};
+use tokio::net::TcpListener;
+
+/// Metrics reported by the drone on its metrics listener.
+pub struct DroneMetrics {
+    registry: Registry,
+
+    /// Non-terminated backends on this drone, labeled by status.
+    pub backends: IntGaugeVec,
+
+    /// Time taken to pull images, labeled by `result` (`success` or `error`).
+    pub image_pull_duration_seconds: HistogramVec,
+
+    /// Backends that failed to start, labeled by the `stage` at which they failed.
+    pub spawn_failures: IntCounterVec,
+
+    /// Backend processes restarted under their restart policy.
+    pub backend_restarts: IntCounter,
+
+    /// Backends terminated after failing their liveness probe.
+    pub unhealthy_terminations: IntCounter,
+
+    /// Time from sending a key renewal request to receiving the controller's response.
+    pub key_renewal_duration_seconds: Histogram,
+
+    /// Key renewals that failed, labeled by `reason`.
+    pub key_renewal_failures: IntCounterVec,
+
+    /// Times the drone has reconnected to the controller after losing its connection.
+    pub controller_reconnects: IntCounter,
+
+    /// Backend log lines dropped because the drone could not forward them fast enough.
+    pub dropped_log_lines: IntCounter,
+
+    /// Memory used by each backend, in bytes.
+    pub backend_memory_used_bytes: GaugeVec,
+
+    /// Fraction of the drone's total CPU time used by each backend.
+    pub backend_cpu_usage_ratio: GaugeVec,
+}
+
+pub static DRONE_METRICS: LazyLock<DroneMetrics> = LazyLock::new(DroneMetrics::new);
+
+impl DroneMetrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let backends = IntGaugeVec::new(
+            opts!(
+                "plane_drone_backends",
+                "Non-terminated backends on this drone by status."
+            ),
+            &["status"],
+        )
+        .expect("Metric options are valid.");
+
+        let image_pull_duration_seconds = HistogramVec::new(
+            histogram_opts!(
+                "plane_drone_image_pull_duration_seconds",
+                "Time taken to pull images.",
+                vec![0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0, 120.0, 300.0, 600.0]
+            ),
+            &["result"],
+        )
+        .expect("Metric options are valid.");
+
+        let spawn_failures = IntCounterVec::new(
+            opts!(
+                "plane_drone_spawn_failures_total",
+                "Backends that failed to start, by stage."
+            ),
+            &["stage"],
+        )
+        .expect("Metric options are valid.");
+
+        let backend_restarts = IntCounter::new(
+            "plane_drone_backend_restarts_total",
+            "Backend processes restarted after exiting with an error.",
+        )
+        .expect("Metric options are valid.");
+
+        let unhealthy_terminations = IntCounter::new(
+            "plane_drone_unhealthy_terminations_total",
+            "Backends terminated after failing their liveness probe.",
+        )
+        .expect("Metric options are valid.");
+
+        let key_renewal_duration_seconds = Histogram::with_opts(histogram_opts!(
+            "plane_drone_key_renewal_duration_seconds",
+            "Time from requesting a key renewal to receiving the response."
+        ))
+        .expect("Metric options are valid.");
+
+        let key_renewal_failures = IntCounterVec::new(
+            opts!(
+                "plane_drone_key_renewal_failures_total",
+                "Key renewals that failed, by reason."
+            ),
+            &["reason"],
+        )
+        .expect("Metric options are valid.");
+
+        let controller_reconnects = IntCounter::new(
+            "plane_drone_controller_reconnects_total",
+            "Times the drone has reconnected to the controller.",
+        )
+        .expect("Metric options are valid.");
+
+        let dropped_log_lines = IntCounter::new(
+            "plane_drone_dropped_log_lines_total",
+            "Backend log lines dropped because they could not be forwarded fast enough.",
+        )
+        .expect("Metric options are valid.");
+
+        let backend_memory_used_bytes = GaugeVec::new(
+            opts!(
+                "plane_drone_backend_memory_used_bytes",
+                "Memory used by each backend."
+            ),
+            &["backend"],
+        )
+        .expect("Metric options are valid.");
+
+        let backend_cpu_usage_ratio = GaugeVec::new(
+            opts!(
+                "plane_drone_backend_cpu_usage_ratio",
+                "Fraction of the drone's total CPU time used by each backend."
+            ),
+            &["backend"],
+        )
+        .expect("Metric options are valid.");
+
+        for collector in [
+            Box::new(backends.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(image_pull_duration_seconds.clone()),
+            Box::new(spawn_failures.clone()),
+            Box::new(backend_restarts.clone()),
+            Box::new(unhealthy_terminations.clone()),
+            Box::new(key_renewal_duration_seconds.clone()),
+            Box::new(key_renewal_failures.clone()),
+            Box::new(controller_reconnects.clone()),
+            Box::new(dropped_log_lines.clone()),
+            Box::new(backend_memory_used_bytes.clone()),
+            Box::new(backend_cpu_usage_ratio.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("Metric names are unique.");
+        }
+
+        Self {
+            registry,
+            backends,
+            image_pull_duration_seconds,
+            spawn_failures,
+            backend_restarts,
+            unhealthy_terminations,
+            key_renewal_duration_seconds,
+            key_renewal_failures,
+            controller_reconnects,
+            dropped_log_lines,
+            backend_memory_used_bytes,
+            backend_cpu_usage_ratio,
+        }
+    }
+
+    pub fn record_spawn_failure(&self, stage: &str) {
+        self.spawn_failures.with_label_values(&[stage]).inc();
+    }
+
+    pub fn record_restart(&self) {
+        self.backend_restarts.inc();
+    }
+
+    pub fn record_unhealthy_termination(&self) {
+        self.unhealthy_terminations.inc();
+    }
+
+    pub fn record_key_renewal_failure(&self, reason: &str) {
+        self.key_renewal_failures.with_label_values(&[reason]).inc();
+    }
+
+    pub fn record_dropped_log_lines(&self, count: usize) {
+        self.dropped_log_lines.inc_by(count as u64);
+    }
+
+    pub fn record_backend_metrics(&self, metrics: &BackendMetricsMessage) {
+        let backend = metrics.backend_id.to_string();
+
+        self.backend_memory_used_bytes
+            .with_label_values(&[&backend])
+            .set(metrics.mem_used as f64);
+
+        if metrics.sys_cpu > 0 {
+            self.backend_cpu_usage_ratio
+                .with_label_values(&[&backend])
+                .set(metrics.cpu_used as f64 / metrics.sys_cpu as f64);
+        }
+    }
+
+    /// Stops reporting resource usage for a backend, once it has terminated.
+    pub fn remove_backend(&self, backend: &BackendName) {
+        let backend = backend.to_string();
+        // An error here just means that no metrics were reported for the backend.
+        let _ = self
+            .backend_memory_used_bytes
+            .remove_label_values(&[&backend]);
+        let _ = self
+            .backend_cpu_usage_ratio
+            .remove_label_values(&[&backend]);
+    }
+
+    fn refresh_backends(&self, executor: &Executor) -> anyhow::Result<()> {
+        let counts = executor.backend_status_counts()?;
+
+        // Reset the gauge, so that statuses that no longer have any backends are not reported.
+        self.backends.reset();
+        for (status, count) in counts {
+            self.backends.with_label_values(&[&status]).set(count);
+        }
+
+        Ok(())
+    }
+}
+
+async fn handle_metrics(State(executor): State<Arc<Executor>>) -> Response {
+    if let Err(err) = DRONE_METRICS.refresh_backends(&executor) {
+        tracing::error!(?err, "Failed to read backend states for metrics.");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    metrics_response(&DRONE_METRICS.registry)
+}
+
+/// Serves the drone's metrics on `/metrics`. Metrics are read locally, so they remain
+/// available while the drone is disconnected from the controller.
+pub async fn run_metrics_server(
+    listener: TcpListener,
+    executor: Arc<Executor>,
+) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/metrics", get(handle_metrics))
+        .with_state(executor);
+
+    axum::serve(listener, app).await
+}
@@ -2,14 +2,17 @@ use self::{
     executor::Executor,
     heartbeat::HeartbeatLoop,
     key_manager::KeyManager,
+    log_batcher::LogBatcher,
+    metrics::{run_metrics_server, DRONE_METRICS},
+    resources::ResourceTracker,
     runtime::{
         docker::DockerRuntimeConfig,
         unix_socket::{UnixSocketRuntime, UnixSocketRuntimeConfig},
-        Runtime,
+        Runtime, RuntimeResources,
     },
     state_store::StateStore,
 };
-use crate::signals::wait_for_shutdown_signal;
+use crate::{signals::wait_for_shutdown_signal, util::GuardHandle};
 use anyhow::{anyhow, Result};
 use chrono::{Duration, Utc};
 use plane_common::{
@@ -18,7 +21,7 @@ use plane_common::{
         BackendAction, BackendActionMessage, MessageFromDrone, MessageToDrone, RenewKeyResponse,
     },
     typed_socket::{client::TypedSocketConnector, TypedSocketSender},
-    types::{BackendState, ClusterName, DronePoolName},
+    types::{BackendState, ClusterName, DroneLabels, DronePoolName},
     PlaneClient,
 };
 use runtime::docker::DockerRuntime;
@@ -26,12 +29,12 @@ use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::{
     fs::{set_permissions, File, Permissions},
-    net::IpAddr,
+    net::{IpAddr, Ipv4Addr},
     os::unix::fs::PermissionsExt,
     path::PathBuf,
     sync::{Arc, Mutex},
 };
-use tokio::task::JoinHandle;
+use tokio::{net::TcpListener, task::JoinHandle};
 use url::Url;
 use valuable::Valuable;
 
@@ -40,32 +43,54 @@ pub mod command;
 mod executor;
 mod heartbeat;
 mod key_manager;
+mod log_batcher;
+pub mod metrics;
+mod resources;
 pub mod runtime;
 mod state_store;
 
 pub async fn drone_loop(
     name: DroneName,
     mut connection: TypedSocketConnector<MessageFromDrone>,
-    executor: Executor,
+    executor: Arc<Executor>,
+    resources: Arc<ResourceTracker>,
 ) {
-    let executor = Arc::new(executor);
     let key_manager = Arc::new(Mutex::new(KeyManager::new(executor.clone())));
+    let mut connected_before = false;
 
     loop {
         let mut socket = connection.connect_with_retry(&name).await;
-        let _heartbeat_guard = HeartbeatLoop::start(socket.sender(MessageFromDrone::Heartbeat));
+        if connected_before {
+            DRONE_METRICS.controller_reconnects.inc();
+        }
+        connected_before = true;
+        let _heartbeat_guard = HeartbeatLoop::start(
+            socket.sender(MessageFromDrone::Heartbeat),
+            resources.clone(),
+        );
 
         {
             let socket = socket.sender(MessageFromDrone::BackendMetrics);
+            let resources = resources.clone();
             executor
                 .runtime
                 .metrics_callback(Box::new(move |metrics_message| {
+                    DRONE_METRICS.record_backend_metrics(&metrics_message);
+                    resources.record(&metrics_message);
                     if let Err(err) = socket.send(metrics_message) {
                         tracing::error!(?err, "Error sending metrics message.");
                     }
                 }));
         };
 
+        let log_batcher = LogBatcher::start(socket.sender(MessageFromDrone::BackendLogs));
+        {
+            let queue = log_batcher.queue();
+            executor
+                .runtime
+                .log_callback(Box::new(move |log_message| queue.push(log_message)));
+        };
+
         key_manager
             .lock()
             .expect("Key manager lock poisoned")
@@ -76,12 +101,15 @@ pub async fn drone_loop(
             // This will start by sending any existing unacked events.
             let sender = socket.sender(MessageFromDrone::BackendEvent);
             let key_manager = key_manager.clone();
+            let resources = resources.clone();
             if let Err(err) = executor.register_listener(move |message| {
                 if matches!(message.state, BackendState::Terminated { .. }) {
                     key_manager
                         .lock()
                         .expect("Key manager lock poisoned.")
                         .unregister_key(&message.backend_id);
+                    DRONE_METRICS.remove_backend(&message.backend_id);
+                    resources.remove(&message.backend_id);
                 }
 
                 if let Err(e) = sender.send(message) {
@@ -181,6 +209,10 @@ pub async fn drone_loop(
                 } else {
                     // TODO: we could begin the graceful termiation here.
                     tracing::warn!("Key renewal failed.");
+                    key_manager
+                        .lock()
+                        .expect("Key manager lock poisoned.")
+                        .renewal_rejected(&backend);
                 }
             }
         }
@@ -189,12 +221,16 @@ pub async fn drone_loop(
 
 pub struct Drone {
     drone_loop: JoinHandle<()>,
+    _metrics_handle: Option<GuardHandle>,
     pub id: DroneName,
 }
 
 impl Drone {
     pub async fn run(config: DroneConfig) -> Result<Self> {
-        let client = PlaneClient::new(config.controller_url);
+        let mut client = PlaneClient::new(config.controller_url);
+        if let Some(join_token) = &config.join_token {
+            client = client.with_join_token(join_token);
+        }
 
         #[allow(deprecated)]
         let runtime: Box<dyn Runtime> = match (config.docker_config, config.executor_config) {
@@ -232,7 +268,23 @@ impl Drone {
             }
         };
 
-        let connector = client.drone_connection(&config.cluster, &config.pool);
+        let total_resources = match runtime.resources().await {
+            Ok(resources) => resources,
+            Err(err) => {
+                tracing::warn!(?err, "Unable to determine drone resources.");
+                None
+            }
+        };
+        let reserved_resources = RuntimeResources {
+            memory_bytes: config.reserved_memory_bytes.unwrap_or_default(),
+            cpu_millicores: config.reserved_cpu_millicores.unwrap_or_default(),
+        };
+        let resources = Arc::new(ResourceTracker::new(total_resources, reserved_resources));
+
+        let connector = client
+            .drone_connection(&config.cluster, &config.pool)
+            .with_labels(config.labels.clone())
+            .with_max_backends(config.max_backends);
 
         let sqlite_connection = if let Some(db_path) = config.db_path.as_ref() {
             if !db_path.exists() {
@@ -249,12 +301,30 @@ impl Drone {
         let state_store = StateStore::new(sqlite_connection)?;
 
         let runtime = Arc::new(runtime);
-        let executor = Executor::new(runtime, state_store, config.ip).await;
+        let executor = Arc::new(Executor::new(runtime, state_store, config.ip).await);
+
+        let metrics_handle = match config.metrics_port {
+            Some(metrics_port) => {
+                let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, metrics_port)).await?;
+                tracing::info!(metrics_port, "Serving metrics.");
+                let executor = executor.clone();
+                Some(GuardHandle::new(async move {
+                    if let Err(err) = run_metrics_server(listener, executor).await {
+                        tracing::error!(?err, "Metrics server failed.");
+                    }
+                }))
+            }
+            None => None,
+        };
 
         let id = config.name.clone();
-        let drone_loop = tokio::spawn(drone_loop(id.clone(), connector, executor));
+        let drone_loop = tokio::spawn(drone_loop(id.clone(), connector, executor, resources));
 
-        Ok(Self { drone_loop, id })
+        Ok(Self {
+            drone_loop,
+            _metrics_handle: metrics_handle,
+            id,
+        })
     }
 
     pub async fn terminate(self) {
@@ -280,8 +350,17 @@ pub struct DroneConfig {
     pub executor_config: Option<ExecutorConfig>,
 
     pub controller_url: Url,
+
+    /// Join token presented to the controller, if it requires one.
+    pub join_token: Option<String>,
+
     pub cluster: ClusterName,
     pub pool: DronePoolName,
+
+    /// Labels advertised to the controller, which spawn requests can select drones by.
+    #[serde(default)]
+    pub labels: DroneLabels,
+
     pub ip: IpAddr,
     pub db_path: Option<PathBuf>,
 
@@ -296,6 +375,21 @@ pub struct DroneConfig {
         note = "Moved to `executor_config` (only applies to DockerRuntimeConfig)."
     )]
     pub cleanup_min_age: Option<Duration>,
+
+    /// If set, Prometheus metrics are served on `/metrics` on this port.
+    pub metrics_port: Option<u16>,
+
+    /// Memory (in bytes) to hold back from backends for the host and drone. This is
+    /// subtracted from the total memory the drone reports as available for backends.
+    pub reserved_memory_bytes: Option<u64>,
+
+    /// CPU (in millicores, i.e. thousandths of a core) to hold back from backends for the
+    /// host and drone.
+    pub reserved_cpu_millicores: Option<u64>,
+
+    /// Maximum number of backends the controller will schedule on this drone at once.
+    #[serde(default)]
+    pub max_backends: Option<u32>,
 }
 
 pub async fn run_drone(config: DroneConfig) -> Result<()> {
@@ -0,0 +1,142 @@
+use super::runtime::RuntimeResources;
+use plane_common::{
+    names::BackendName,
+    protocol::{BackendMetricsMessage, DroneResources},
+};
+use std::{collections::HashMap, sync::Mutex};
+
+/// Tracks the drone's resources and the latest usage of each backend, so that they can be
+/// reported to the controller in heartbeats.
+pub struct ResourceTracker {
+    /// Total resources of the drone's host, if the runtime was able to determine them.
+    total: Option<RuntimeResources>,
+
+    /// Resources held back from backends for the host and drone.
+    reserved: RuntimeResources,
+
+    usage: Mutex<HashMap<BackendName, RuntimeResources>>,
+}
+
+impl ResourceTracker {
+    pub fn new(total: Option<RuntimeResources>, reserved: RuntimeResources) -> Self {
+        Self {
+            total,
+            reserved,
+            usage: Mutex::default(),
+        }
+    }
+
+    pub fn record(&self, metrics: &BackendMetricsMessage) {
+        let Some(total) = self.total else {
+            return;
+        };
+
+        // `cpu_used` is a share of `sys_cpu`, which is the CPU time of every core on the host.
+        let cpu_millicores = if metrics.sys_cpu > 0 {
+            (metrics.cpu_used as u128 * total.cpu_millicores as u128 / metrics.sys_cpu as u128)
+                as u64
+        } else {
+            0
+        };
+
+        self.usage.lock().expect("Usage lock poisoned.").insert(
+            metrics.backend_id.clone(),
+            RuntimeResources {
+                memory_bytes: metrics.mem_used,
+                cpu_millicores,
+            },
+        );
+    }
+
+    /// Stops counting a backend's usage, once it has terminated.
+    pub fn remove(&self, backend: &BackendName) {
+        self.usage
+            .lock()
+            .expect("Usage lock poisoned.")
+            .remove(backend);
+    }
+
+    pub fn snapshot(&self) -> Option<DroneResources> {
+        let total = self.total?;
+
+        let usage = self.usage.lock().expect("Usage lock poisoned.");
+        let used_memory_bytes = usage.values().map(|usage| usage.memory_bytes).sum();
+        let used_cpu_millicores = usage.values().map(|usage| usage.cpu_millicores).sum();
+
+        Some(DroneResources {
+            total_memory_bytes: total.memory_bytes,
+            allocatable_memory_bytes: total
+                .memory_bytes
+                .saturating_sub(self.reserved.memory_bytes),
+            used_memory_bytes,
+            total_cpu_millicores: total.cpu_millicores,
+            allocatable_cpu_millicores: total
+                .cpu_millicores
+                .saturating_sub(self.reserved.cpu_millicores),
+            used_cpu_millicores,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plane_common::names::Name;
+
+    fn metrics(backend_id: &BackendName, mem_used: u64, cpu_used: u64) -> BackendMetricsMessage {
+        BackendMetricsMessage {
+            backend_id: backend_id.clone(),
+            mem_used,
+            mem_total: mem_used,
+            mem_active: mem_used,
+            mem_inactive: 0,
+            mem_unevictable: 0,
+            mem_limit: 0,
+            cpu_used,
+            sys_cpu: 1_000,
+        }
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let tracker = ResourceTracker::new(
+            Some(RuntimeResources {
+                memory_bytes: 8_000,
+                cpu_millicores: 4_000,
+            }),
+            RuntimeResources {
+                memory_bytes: 1_000,
+                cpu_millicores: 500,
+            },
+        );
+
+        let backend1 = BackendName::new_random();
+        let backend2 = BackendName::new_random();
+        tracker.record(&metrics(&backend1, 2_000, 250));
+        tracker.record(&metrics(&backend2, 1_000, 125));
+
+        assert_eq!(
+            tracker.snapshot(),
+            Some(DroneResources {
+                total_memory_bytes: 8_000,
+                allocatable_memory_bytes: 7_000,
+                used_memory_bytes: 3_000,
+                total_cpu_millicores: 4_000,
+                allocatable_cpu_millicores: 3_500,
+                used_cpu_millicores: 1_500,
+            })
+        );
+
+        tracker.remove(&backend1);
+        let snapshot = tracker.snapshot().unwrap();
+        assert_eq!(snapshot.used_memory_bytes, 1_000);
+        assert_eq!(snapshot.used_cpu_millicores, 500);
+    }
+
+    #[test]
+    fn test_unknown_resources() {
+        let tracker = ResourceTracker::new(None, RuntimeResources::default());
+        tracker.record(&metrics(&BackendName::new_random(), 2_000, 250));
+        assert_eq!(tracker.snapshot(), None);
+    }
+}
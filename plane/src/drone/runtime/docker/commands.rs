@@ -1,4 +1,5 @@
 use super::{types::ContainerId, DockerRuntime};
+use crate::drone::metrics::DRONE_METRICS;
 use anyhow::Result;
 use bollard::{
     auth::DockerCredentials,
@@ -9,16 +10,22 @@ use futures_util::StreamExt;
 use plane_common::{
     names::BackendName,
     protocol::AcquiredKey,
-    types::{BearerToken, DockerExecutorConfig, Mount},
+    types::{BearerToken, DockerExecutorConfig, Mount, ProbeCheck},
 };
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     path::{Component, Path, PathBuf},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-/// Port inside the container to expose.
-const CONTAINER_PORT: u16 = 8080;
+/// Port inside the container to expose, if the executor config does not set one.
+const DEFAULT_CONTAINER_PORT: u16 = 8080;
+/// Label recording the port the backend's HTTP server listens on inside the container.
+const PORT_LABEL: &str = "dev.plane.port";
+/// Prefix of the labels recording the backend's named ports, e.g. `dev.plane.port.metrics`.
+const NAMED_PORT_LABEL_PREFIX: &str = "dev.plane.port.";
+/// Prefix of the labels Plane sets on containers, which executor configs may not set.
+const RESERVED_LABEL_PREFIX: &str = "dev.plane.";
 /// Base directory for any data mounted in the container from the host
 const PLANE_DATA_DIR: &str = "/plane-data";
 
@@ -38,34 +45,131 @@ pub async fn pull_image(
         ..Default::default()
     };
 
-    let mut result = docker.create_image(Some(options), None, credentials.cloned());
+    let start_time = Instant::now();
+    let mut stream = docker.create_image(Some(options), None, credentials.cloned());
     // create_image returns a stream; the image is not fully pulled until the stream is consumed.
-    while let Some(next) = result.next().await {
-        let info = next?;
-        if let Some(progress) = info.progress_detail {
-            tracing::debug!(?progress, "Image pull progress.");
+    let result: Result<()> = async {
+        while let Some(next) = stream.next().await {
+            let info = next?;
+            if let Some(progress) = info.progress_detail {
+                tracing::debug!(?progress, "Image pull progress.");
+            }
         }
+        Ok(())
     }
+    .await;
+
+    DRONE_METRICS
+        .image_pull_duration_seconds
+        .with_label_values(&[if result.is_ok() { "success" } else { "error" }])
+        .observe(start_time.elapsed().as_secs_f64());
+    result?;
 
     tracing::info!(?image, "Pulled image.");
 
     Ok(())
 }
 
-fn create_labels() -> HashMap<String, String> {
-    HashMap::from([(super::PLANE_DOCKER_LABEL.to_string(), "true".to_string())])
+/// Ports inside a backend's container: the port its HTTP server listens on, and its named ports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContainerPorts {
+    pub port: u16,
+    pub named_ports: BTreeMap<String, u16>,
+}
+
+impl ContainerPorts {
+    fn from_executor_config(exec_config: &DockerExecutorConfig) -> Result<Self> {
+        for name in exec_config.ports.keys() {
+            validate_port_name(name)?;
+        }
+
+        Ok(Self {
+            port: exec_config.port.unwrap_or(DEFAULT_CONTAINER_PORT),
+            named_ports: exec_config
+                .ports
+                .iter()
+                .map(|(name, port)| (name.clone(), *port))
+                .collect(),
+        })
+    }
+
+    /// Reads the ports from the labels of a container created by `run_container`. Containers
+    /// without port labels were created before ports were configurable, and use the default.
+    fn from_labels(labels: &HashMap<String, String>) -> Self {
+        let port = labels
+            .get(PORT_LABEL)
+            .and_then(|port| port.parse().ok())
+            .unwrap_or(DEFAULT_CONTAINER_PORT);
+        let named_ports = labels
+            .iter()
+            .filter_map(|(label, port)| {
+                let name = label.strip_prefix(NAMED_PORT_LABEL_PREFIX)?;
+                Some((name.to_string(), port.parse().ok()?))
+            })
+            .collect();
+
+        Self { port, named_ports }
+    }
+
+    fn all(&self) -> impl Iterator<Item = u16> + '_ {
+        std::iter::once(self.port).chain(self.named_ports.values().copied())
+    }
+}
+
+/// Port names appear in URLs, so they are limited to lowercase letters, digits, and dashes.
+fn validate_port_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid port name {:?}: port names may only contain lowercase letters, digits, and dashes",
+            name
+        ));
+    }
+    Ok(())
 }
 
-fn create_port_bindings() -> HashMap<String, Option<Vec<PortBinding>>> {
-    vec![(
-        format!("{}/tcp", CONTAINER_PORT),
-        Some(vec![bollard::models::PortBinding {
-            host_ip: None,
-            host_port: None,
-        }]),
-    )]
-    .into_iter()
-    .collect()
+fn create_labels(
+    mut labels: HashMap<String, String>,
+    ports: &ContainerPorts,
+) -> Result<HashMap<String, String>> {
+    if let Some(label) = labels
+        .keys()
+        .find(|label| label.starts_with(RESERVED_LABEL_PREFIX))
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid label {:?}: labels starting with {:?} are reserved",
+            label,
+            RESERVED_LABEL_PREFIX
+        ));
+    }
+
+    labels.insert(super::PLANE_DOCKER_LABEL.to_string(), "true".to_string());
+    labels.insert(PORT_LABEL.to_string(), ports.port.to_string());
+    for (name, port) in &ports.named_ports {
+        labels.insert(
+            format!("{}{}", NAMED_PORT_LABEL_PREFIX, name),
+            port.to_string(),
+        );
+    }
+    Ok(labels)
+}
+
+fn create_port_bindings(ports: &ContainerPorts) -> HashMap<String, Option<Vec<PortBinding>>> {
+    ports
+        .all()
+        .map(|port| {
+            (
+                format!("{}/tcp", port),
+                Some(vec![bollard::models::PortBinding {
+                    host_ip: None,
+                    host_port: None,
+                }]),
+            )
+        })
+        .collect()
 }
 
 pub async fn image_exists(docker: &Docker, image: &str) -> Result<bool> {
@@ -78,32 +182,50 @@ pub async fn image_exists(docker: &Docker, image: &str) -> Result<bool> {
     }
 }
 
-async fn try_get_port(docker: &Docker, container_id: &ContainerId) -> Result<u16> {
+async fn try_get_ports(docker: &Docker, container_id: &ContainerId) -> Result<ContainerPorts> {
     let info = docker
         .inspect_container(&container_id.to_string(), None)
         .await?;
 
-    // TODO: avoid clones.
+    let container_ports = ContainerPorts::from_labels(
+        &info
+            .config
+            .and_then(|config| config.labels)
+            .unwrap_or_default(),
+    );
 
-    let port = info
+    let bindings = info
         .network_settings
         .and_then(|settings| settings.ports)
-        .and_then(|ports| ports.get(&format!("{}/tcp", CONTAINER_PORT)).cloned())
-        .and_then(|bindings| bindings.clone())
-        .and_then(|bindings| bindings.first().cloned())
-        .and_then(|binding| binding.host_port.clone())
-        .and_then(|port| port.parse::<u16>().ok())
-        .ok_or_else(|| anyhow::anyhow!("Failed to get port for container."))?;
-
-    Ok(port)
+        .unwrap_or_default();
+
+    let host_port = |container_port: u16| {
+        bindings
+            .get(&format!("{}/tcp", container_port))
+            .and_then(|bindings| bindings.as_ref())
+            .and_then(|bindings| bindings.first())
+            .and_then(|binding| binding.host_port.as_ref())
+            .and_then(|port| port.parse::<u16>().ok())
+            .ok_or_else(|| anyhow::anyhow!("Failed to get port {} for container.", container_port))
+    };
+
+    Ok(ContainerPorts {
+        port: host_port(container_ports.port)?,
+        named_ports: container_ports
+            .named_ports
+            .iter()
+            .map(|(name, port)| Ok((name.clone(), host_port(*port)?)))
+            .collect::<Result<_>>()?,
+    })
 }
 
-pub async fn get_port(docker: &Docker, container_id: &ContainerId) -> Result<u16> {
+/// Returns the host ports that the container's ports are bound to.
+pub async fn get_ports(docker: &Docker, container_id: &ContainerId) -> Result<ContainerPorts> {
     // There can be a race condition where the container is ready but has
     // not yet received a port assignment, so we retry a few times.
     for _ in 0..3 {
-        match try_get_port(docker, container_id).await {
-            Ok(port) => return Ok(port),
+        match try_get_ports(docker, container_id).await {
+            Ok(ports) => return Ok(ports),
             Err(e) => {
                 tracing::info!(?e, "Failed to get port, retrying...");
             }
@@ -130,6 +252,20 @@ pub fn validate_mount_path(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// HTTP probe paths are written into the request line, so they must start with a slash and
+/// may not contain spaces or line breaks.
+fn validate_probe_check(check: &ProbeCheck) -> Result<()> {
+    if let ProbeCheck::Http { path, .. } = check {
+        if !path.starts_with('/') || path.chars().any(|c| c == ' ' || c.is_ascii_control()) {
+            return Err(anyhow::anyhow!(
+                "Invalid probe path {:?}: paths must start with a slash and may not contain spaces or control characters",
+                path
+            ));
+        }
+    }
+    Ok(())
+}
+
 pub fn get_container_config_from_executor_config(
     backend_id: Option<&BackendName>,
     exec_config: DockerExecutorConfig,
@@ -139,8 +275,18 @@ pub fn get_container_config_from_executor_config(
     log_config: Option<&HostConfigLogConfig>,
     mount_base: Option<&PathBuf>,
 ) -> Result<bollard::container::Config<String>> {
+    let ports = ContainerPorts::from_executor_config(&exec_config)?;
+    let probe_checks = exec_config
+        .readiness_probe
+        .iter()
+        .map(|probe| &probe.check)
+        .chain(exec_config.liveness_probe.iter().map(|probe| &probe.check));
+    for check in probe_checks {
+        validate_probe_check(check)?;
+    }
+    let labels = create_labels(exec_config.labels, &ports)?;
     let mut env = exec_config.env;
-    env.insert("PORT".to_string(), CONTAINER_PORT.to_string());
+    env.insert("PORT".to_string(), ports.port.to_string());
 
     if let Some(backend_id) = backend_id {
         env.insert("SESSION_BACKEND_ID".to_string(), backend_id.to_string());
@@ -203,15 +349,21 @@ pub fn get_container_config_from_executor_config(
 
     Ok(bollard::container::Config {
         image: Some(exec_config.image.clone()),
-        labels: Some(create_labels()),
+        labels: Some(labels),
         env: Some(env),
+        cmd: exec_config.cmd,
+        entrypoint: exec_config.entrypoint,
+        user: exec_config.user,
+        working_dir: exec_config.working_dir,
+        hostname: exec_config.hostname,
         exposed_ports: Some(
-            vec![(format!("{}/tcp", CONTAINER_PORT), HashMap::new())]
-                .into_iter()
+            ports
+                .all()
+                .map(|port| (format!("{}/tcp", port), HashMap::new()))
                 .collect(),
         ),
         host_config: Some(HostConfig {
-            port_bindings: Some(create_port_bindings()),
+            port_bindings: Some(create_port_bindings(&ports)),
             runtime: runtime.map(|s| s.to_string()),
             memory: exec_config.resource_limits.memory_limit_bytes,
             log_config: log_config.cloned(),
@@ -298,7 +450,7 @@ mod tests {
         log_types::LoggableTime,
         names::Name,
         protocol::{AcquiredKey, KeyDeadlines},
-        types::{DockerExecutorConfig, KeyConfig, Mount},
+        types::{DockerExecutorConfig, KeyConfig, LivenessProbe, Mount, ReadinessProbe},
     };
     use std::time::UNIX_EPOCH;
 
@@ -449,6 +601,240 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_default_port() {
+        let exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        let config = get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(config.env.unwrap().contains(&"PORT=8080".to_string()));
+        assert_eq!(
+            config
+                .exposed_ports
+                .unwrap()
+                .into_keys()
+                .collect::<Vec<_>>(),
+            vec!["8080/tcp".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_configured_ports() {
+        let mut exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        exec_config.port = Some(3000);
+        exec_config.ports = HashMap::from([("metrics".to_string(), 9090)]);
+        let config = get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert!(config.env.unwrap().contains(&"PORT=3000".to_string()));
+
+        let mut bound_ports: Vec<String> = config
+            .host_config
+            .unwrap()
+            .port_bindings
+            .unwrap()
+            .into_keys()
+            .collect();
+        bound_ports.sort();
+        assert_eq!(bound_ports, vec!["3000/tcp", "9090/tcp"]);
+
+        // The ports are recorded on the container, so that they can be found after a restart.
+        assert_eq!(
+            ContainerPorts::from_labels(&config.labels.unwrap()),
+            ContainerPorts {
+                port: 3000,
+                named_ports: BTreeMap::from([("metrics".to_string(), 9090)]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_ports_from_labels_without_port_labels() {
+        let labels = create_labels(
+            HashMap::new(),
+            &ContainerPorts {
+                port: DEFAULT_CONTAINER_PORT,
+                named_ports: BTreeMap::new(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            ContainerPorts::from_labels(&HashMap::new()),
+            ContainerPorts::from_labels(&labels)
+        );
+    }
+
+    #[test]
+    fn test_invalid_port_name() {
+        let mut exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        exec_config.ports = HashMap::from([("Metrics~1".to_string(), 9090)]);
+        let err = get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("Invalid port name"));
+    }
+
+    #[test]
+    fn test_process_overrides() {
+        let mut exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        exec_config.cmd = Some(vec!["serve".to_string(), "--verbose".to_string()]);
+        exec_config.entrypoint = Some(vec!["/bin/app".to_string()]);
+        exec_config.user = Some("1000:1000".to_string());
+        exec_config.working_dir = Some("/app".to_string());
+        exec_config.hostname = Some("backend".to_string());
+        let config = get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.cmd,
+            Some(vec!["serve".to_string(), "--verbose".to_string()])
+        );
+        assert_eq!(config.entrypoint, Some(vec!["/bin/app".to_string()]));
+        assert_eq!(config.user, Some("1000:1000".to_string()));
+        assert_eq!(config.working_dir, Some("/app".to_string()));
+        assert_eq!(config.hostname, Some("backend".to_string()));
+    }
+
+    #[test]
+    fn test_no_process_overrides() {
+        let exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        let config = get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(config.cmd, None);
+        assert_eq!(config.entrypoint, None);
+        assert_eq!(config.user, None);
+        assert_eq!(config.working_dir, None);
+        assert_eq!(config.hostname, None);
+    }
+
+    #[test]
+    fn test_labels() {
+        let mut exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        exec_config.labels = HashMap::from([("team".to_string(), "editor".to_string())]);
+        let labels = get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap()
+        .labels
+        .unwrap();
+
+        assert_eq!(labels.get("team"), Some(&"editor".to_string()));
+        assert_eq!(labels.get("dev.plane.backend"), Some(&"true".to_string()));
+    }
+
+    #[test]
+    fn test_reserved_label() {
+        let mut exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        exec_config.labels = HashMap::from([("dev.plane.port".to_string(), "80".to_string())]);
+        let err = get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("reserved"));
+    }
+
+    #[test]
+    fn test_invalid_probe_path() {
+        for path in ["health", "/health\r\nX-Injected: 1", "/health HTTP/1.0"] {
+            let mut exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+            exec_config.liveness_probe = Some(LivenessProbe {
+                check: ProbeCheck::Http {
+                    path: path.to_string(),
+                    expected_status: None,
+                },
+                interval_seconds: None,
+                timeout_seconds: None,
+                failure_threshold: None,
+            });
+            let err = get_container_config_from_executor_config(
+                None,
+                exec_config,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap_err();
+
+            assert!(err.to_string().contains("Invalid probe path"));
+        }
+
+        let mut exec_config = DockerExecutorConfig::from_image_with_defaults(String::default());
+        exec_config.readiness_probe = Some(ReadinessProbe {
+            check: ProbeCheck::Http {
+                path: "/health?ready=1".to_string(),
+                expected_status: None,
+            },
+            timeout_seconds: None,
+            interval_millis: None,
+        });
+        assert!(get_container_config_from_executor_config(
+            None,
+            exec_config,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .is_ok());
+    }
+
     // Test invalid mount paths
 
     #[test]
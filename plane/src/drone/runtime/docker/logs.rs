@@ -0,0 +1,133 @@
+use super::{types::ContainerId, LogCallback};
+use bollard::{
+    container::{LogOutput, LogsOptions},
+    Docker,
+};
+use chrono::{DateTime, Utc};
+use plane_common::{
+    names::BackendName,
+    protocol::BackendLogMessage,
+    types::{BackendLogEntry, LogStream},
+};
+use std::sync::{Arc, Mutex};
+use tokio_stream::StreamExt;
+
+/// Lines longer than this many bytes are truncated, so that each line fits in a single
+/// message to the controller.
+const MAX_LINE_BYTES: usize = 4096;
+
+/// Splits a chunk of Docker log output into entries. Docker is asked to prefix each line
+/// with its timestamp; lines without one are given the current time.
+fn log_entries(stream: LogStream, message: &[u8]) -> Vec<BackendLogEntry> {
+    String::from_utf8_lossy(message)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (timestamp, text) = match line.split_once(' ') {
+                Some((timestamp, text)) => match DateTime::parse_from_rfc3339(timestamp) {
+                    Ok(timestamp) => (timestamp.with_timezone(&Utc), text),
+                    Err(_) => (Utc::now(), line),
+                },
+                None => (Utc::now(), line),
+            };
+
+            let mut end = text.len().min(MAX_LINE_BYTES);
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            BackendLogEntry {
+                timestamp,
+                stream,
+                text: text[..end].to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Follows the output of a backend's container from the given UNIX time, passing each line
+/// to the callback, until the container stops.
+pub async fn log_loop(
+    backend_id: BackendName,
+    docker: Docker,
+    callback: Arc<Mutex<Option<LogCallback>>>,
+    since: i64,
+) {
+    let container_id = ContainerId::from(&backend_id);
+    let options = LogsOptions::<String> {
+        follow: true,
+        stdout: true,
+        stderr: true,
+        since,
+        timestamps: true,
+        tail: "all".to_string(),
+        ..Default::default()
+    };
+    let mut stream = docker.logs(container_id.as_str(), Some(options));
+
+    while let Some(output) = stream.next().await {
+        let (stream, message) = match output {
+            Err(err) => {
+                tracing::error!(?err, "Error getting logs for {container_id}");
+                break;
+            }
+            Ok(LogOutput::StdOut { message }) | Ok(LogOutput::Console { message }) => {
+                (LogStream::Stdout, message)
+            }
+            Ok(LogOutput::StdErr { message }) => (LogStream::Stderr, message),
+            Ok(LogOutput::StdIn { .. }) => continue,
+        };
+
+        let callback = callback.lock().expect("Log callback lock poisoned");
+        if let Some(callback) = callback.as_ref() {
+            for entry in log_entries(stream, &message) {
+                (callback)(BackendLogMessage {
+                    backend_id: backend_id.clone(),
+                    entry,
+                });
+            }
+        }
+    }
+
+    tracing::info!("Log stream for {container_id} ended.");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_entries_with_timestamps() {
+        let entries = log_entries(
+            LogStream::Stderr,
+            b"2024-10-28T12:00:00.123456789Z hello world\n2024-10-28T12:00:01Z second\n",
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].stream, LogStream::Stderr);
+        assert_eq!(entries[0].text, "hello world");
+        assert_eq!(
+            entries[0].timestamp,
+            DateTime::parse_from_rfc3339("2024-10-28T12:00:00.123456789Z").unwrap()
+        );
+        assert_eq!(entries[1].text, "second");
+    }
+
+    #[test]
+    fn test_log_entries_without_timestamp() {
+        let entries = log_entries(LogStream::Stdout, b"no timestamp here\n\n");
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].text, "no timestamp here");
+    }
+
+    #[test]
+    fn test_log_entries_truncates_long_lines() {
+        let line = format!("2024-10-28T12:00:00Z {}", "é".repeat(MAX_LINE_BYTES));
+        let entries = log_entries(LogStream::Stdout, line.as_bytes());
+
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].text.len() <= MAX_LINE_BYTES);
+        assert!(entries[0].text.chars().all(|c| c == 'é'));
+    }
+}
@@ -1,16 +1,19 @@
 use self::{
-    commands::{get_port, run_container},
+    commands::{get_ports, run_container},
     types::ContainerId,
-    wait_backend::wait_for_backend,
+    wait_backend::{run_check, wait_for_backend},
 };
 use crate::{
-    drone::runtime::{docker::metrics::metrics_loop, Runtime},
+    drone::runtime::{
+        docker::{logs::log_loop, metrics::metrics_loop},
+        Runtime, RuntimeResources,
+    },
     heartbeat_consts::KILL_AFTER_SOFT_TERMINATE_SECONDS,
     util::GuardHandle,
 };
 use anyhow::Result;
 use bollard::{
-    container::{PruneContainersOptions, StopContainerOptions},
+    container::{ListContainersOptions, StopContainerOptions},
     image::PruneImagesOptions,
     service::{EventMessage, HostConfigLogConfig},
     system::EventsOptions,
@@ -19,11 +22,17 @@ use bollard::{
 use chrono::{DateTime, Duration, Utc};
 use plane_common::{
     names::BackendName,
-    protocol::{AcquiredKey, BackendMetricsMessage},
-    types::{backend_state::BackendError, BearerToken, DockerExecutorConfig, PullPolicy},
+    protocol::{AcquiredKey, BackendLogMessage, BackendMetricsMessage},
+    types::{
+        backend_state::BackendError, BearerToken, DockerExecutorConfig, ProbeCheck, PullPolicy,
+    },
 };
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf, pin::Pin};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    pin::Pin,
+};
 use std::{
     net::SocketAddr,
     sync::{Arc, Mutex},
@@ -37,6 +46,7 @@ use valuable::Valuable;
 const CLEANUP_INTERVAL_SECS: i64 = 60;
 
 pub mod commands;
+pub mod logs;
 pub mod metrics;
 pub mod types;
 mod wait_backend;
@@ -60,10 +70,16 @@ pub struct DockerRuntimeConfig {
 
 pub type MetricsCallback = Box<dyn Fn(BackendMetricsMessage) + Send + Sync + 'static>;
 
+pub type LogCallback = Box<dyn Fn(BackendLogMessage) + Send + Sync + 'static>;
+
+pub type RetainCallback = Box<dyn Fn(&BackendName) -> bool + Send + Sync + 'static>;
+
 pub struct DockerRuntime {
     pub docker: Docker,
     config: DockerRuntimeConfig,
     metrics_callback: Arc<Mutex<Option<MetricsCallback>>>,
+    log_callback: Arc<Mutex<Option<LogCallback>>>,
+    retain_callback: Arc<Mutex<Option<RetainCallback>>>,
     events_sender: Sender<TerminateEvent>,
     _events_loop_handle: GuardHandle,
     _cleanup_handle: GuardHandle,
@@ -72,6 +88,7 @@ pub struct DockerRuntime {
 async fn events_loop(
     docker: Docker,
     metrics_callback: Arc<Mutex<Option<MetricsCallback>>>,
+    log_callback: Arc<Mutex<Option<LogCallback>>>,
     event_sender: Sender<TerminateEvent>,
 ) {
     let options = EventsOptions {
@@ -122,11 +139,24 @@ async fn events_loop(
         if e.action.as_deref() == Some("start") {
             tracing::info!(?backend_id, "Received start event.");
 
+            {
+                let docker = docker.clone();
+                let metrics_callback = metrics_callback.clone();
+                let backend_id = backend_id.clone();
+                tracing::info!(%backend_id, "Spawning metrics loop.");
+                tokio::spawn(async move {
+                    metrics_loop(backend_id, docker, metrics_callback).await;
+                });
+            }
+
+            // Only follow output from this start of the container, since a restarted
+            // container's earlier output has already been forwarded.
             let docker = docker.clone();
-            let metrics_callback = metrics_callback.clone();
-            tracing::info!(%backend_id, "Spawning metrics loop.");
+            let log_callback = log_callback.clone();
+            let since = e.time.unwrap_or_else(|| Utc::now().timestamp());
+            tracing::info!(%backend_id, "Spawning log loop.");
             tokio::spawn(async move {
-                metrics_loop(backend_id, docker, metrics_callback).await;
+                log_loop(backend_id, docker, log_callback, since).await;
             });
 
             continue;
@@ -184,14 +214,48 @@ impl Runtime for DockerRuntime {
         let executable: DockerExecutorConfig = serde_json::from_value(executable.clone())?;
         let container_id =
             run_container(self, backend_id, executable, acquired_key, static_token).await?;
-        let port = get_port(&self.docker, &container_id).await?;
+        let ports = get_ports(&self.docker, &container_id).await?;
 
         Ok(SpawnResult {
             container_id: container_id.clone(),
-            port,
+            port: ports.port,
+            named_ports: ports.named_ports,
         })
     }
 
+    async fn restart(&self, backend_id: &BackendName) -> Result<SpawnResult> {
+        let container_id: ContainerId = backend_id.into();
+
+        // The container has exited but has not been removed, so starting it again keeps its
+        // filesystem (and any mount) intact.
+        self.docker
+            .start_container::<String>(&container_id.to_string(), None)
+            .await?;
+        let ports = get_ports(&self.docker, &container_id).await?;
+
+        Ok(SpawnResult {
+            container_id,
+            port: ports.port,
+            named_ports: ports.named_ports,
+        })
+    }
+
+    async fn suspend(&self, backend_id: &BackendName) -> Result<()> {
+        let container_id: ContainerId = backend_id.into();
+        self.docker
+            .pause_container(&container_id.to_string())
+            .await?;
+        Ok(())
+    }
+
+    async fn resume(&self, backend_id: &BackendName) -> Result<()> {
+        let container_id: ContainerId = backend_id.into();
+        self.docker
+            .unpause_container(&container_id.to_string())
+            .await?;
+        Ok(())
+    }
+
     async fn terminate(&self, backend_id: &BackendName, hard: bool) -> Result<bool, anyhow::Error> {
         let container_id: ContainerId = backend_id.into();
 
@@ -275,12 +339,59 @@ impl Runtime for DockerRuntime {
         *lock = Some(Box::new(sender));
     }
 
+    fn log_callback(&self, sender: Box<dyn Fn(BackendLogMessage) + Send + Sync + 'static>) {
+        let mut lock = self
+            .log_callback
+            .lock()
+            .expect("Log callback lock poisoned.");
+        *lock = Some(Box::new(sender));
+    }
+
+    fn retain_callback(&self, retain: Box<dyn Fn(&BackendName) -> bool + Send + Sync + 'static>) {
+        let mut lock = self
+            .retain_callback
+            .lock()
+            .expect("Retain callback lock poisoned.");
+        *lock = Some(retain);
+    }
+
     async fn wait_for_backend(
         &self,
-        _backend: &BackendName,
+        backend: &BackendName,
+        executable: &serde_json::Value,
         address: SocketAddr,
     ) -> Result<(), BackendError> {
-        wait_for_backend(address).await
+        let executable: DockerExecutorConfig = serde_json::from_value(executable.clone())
+            .map_err(|err| BackendError::Other(format!("Invalid executor config: {}", err)))?;
+        wait_for_backend(
+            &self.docker,
+            backend,
+            address,
+            executable.readiness_probe.as_ref(),
+        )
+        .await
+    }
+
+    async fn check_liveness(
+        &self,
+        backend: &BackendName,
+        address: SocketAddr,
+        check: &ProbeCheck,
+    ) -> bool {
+        run_check(&self.docker, backend, address, check).await
+    }
+
+    async fn resources(&self) -> Result<Option<RuntimeResources>> {
+        let info = self.docker.info().await?;
+
+        let (Some(memory_bytes), Some(cpus)) = (info.mem_total, info.ncpu) else {
+            return Ok(None);
+        };
+
+        Ok(Some(RuntimeResources {
+            memory_bytes: memory_bytes.max(0) as u64,
+            cpu_millicores: cpus.max(0) as u64 * 1000,
+        }))
     }
 }
 
@@ -294,6 +405,10 @@ pub struct TerminateEvent {
 pub struct SpawnResult {
     pub container_id: ContainerId,
     pub port: u16,
+
+    /// Host ports of the backend's named ports, by name.
+    #[serde(default)]
+    pub named_ports: BTreeMap<String, u16>,
 }
 
 impl DockerRuntime {
@@ -301,13 +416,17 @@ impl DockerRuntime {
         let docker = Docker::connect_with_local_defaults()?;
         let (events_sender, _) = tokio::sync::broadcast::channel::<TerminateEvent>(128);
 
+        let retain_callback = Arc::new(Mutex::new(None));
+
         let cleanup_handle = {
             let docker = docker.clone();
+            let retain_callback = retain_callback.clone();
             let cleanup_min_age = config.cleanup_min_age.unwrap_or_default();
             let auto_prune = config.auto_prune.unwrap_or_default();
             GuardHandle::new(async move {
                 cleanup_loop(
                     docker.clone(),
+                    retain_callback,
                     cleanup_min_age,
                     Duration::try_seconds(CLEANUP_INTERVAL_SECS).expect("duration is always valid"),
                     auto_prune,
@@ -317,13 +436,21 @@ impl DockerRuntime {
         };
 
         let metrics_callback = Arc::new(Mutex::new(None));
+        let log_callback = Arc::new(Mutex::new(None));
 
         let event_loop_handle = {
             let metrics_callback = metrics_callback.clone();
+            let log_callback = log_callback.clone();
             let docker = docker.clone();
             let events_sender = events_sender.clone();
             GuardHandle::new(async move {
-                events_loop(docker.clone(), metrics_callback.clone(), events_sender).await;
+                events_loop(
+                    docker.clone(),
+                    metrics_callback.clone(),
+                    log_callback.clone(),
+                    events_sender,
+                )
+                .await;
             })
         };
 
@@ -331,6 +458,8 @@ impl DockerRuntime {
             docker,
             config,
             metrics_callback,
+            log_callback,
+            retain_callback,
             events_sender,
             _events_loop_handle: event_loop_handle,
             _cleanup_handle: cleanup_handle,
@@ -338,7 +467,21 @@ impl DockerRuntime {
     }
 }
 
-async fn cleanup_loop(docker: Docker, min_age: Duration, interval: Duration, auto_prune: bool) {
+impl DockerRuntime {
+    /// Prunes stopped backend containers created before `until`, as the cleanup loop does
+    /// periodically.
+    pub async fn prune_containers(&self, until: DateTime<Utc>) -> Result<()> {
+        prune(&self.docker, &self.retain_callback, until, false).await
+    }
+}
+
+async fn cleanup_loop(
+    docker: Docker,
+    retain_callback: Arc<Mutex<Option<RetainCallback>>>,
+    min_age: Duration,
+    interval: Duration,
+    auto_prune: bool,
+) {
     loop {
         tokio::time::sleep(
             interval
@@ -349,42 +492,79 @@ async fn cleanup_loop(docker: Docker, min_age: Duration, interval: Duration, aut
 
         let since = Utc::now() - min_age;
 
-        if let Err(e) = prune(&docker, since, auto_prune).await {
+        if let Err(e) = prune(&docker, &retain_callback, since, auto_prune).await {
             tracing::error!(?e, "Error pruning Docker containers and images.");
         }
     }
 }
 
-/// Prune stopped backend containers that are older than the prune threshold.
+/// Prune stopped backend containers that are older than the prune threshold, except those of
+/// backends that the retain callback says may still be restarted.
 /// Then, (optionally) remove unused images also older than the prune threshold.
-pub async fn prune(docker: &Docker, until: DateTime<Utc>, prune_images: bool) -> Result<()> {
+pub async fn prune(
+    docker: &Docker,
+    retain_callback: &Mutex<Option<RetainCallback>>,
+    until: DateTime<Utc>,
+    prune_images: bool,
+) -> Result<()> {
     tracing::info!("Pruning Docker containers and images.");
 
     let since_unixtime = until.timestamp();
     let filters: HashMap<String, Vec<String>> = vec![
-        ("until".to_string(), vec![since_unixtime.to_string()]),
+        (
+            "status".to_string(),
+            vec![
+                "created".to_string(),
+                "exited".to_string(),
+                "dead".to_string(),
+            ],
+        ),
         ("label".to_string(), vec![PLANE_DOCKER_LABEL.to_string()]),
     ]
     .into_iter()
     .collect();
 
-    match docker
-        .prune_containers(Some(PruneContainersOptions {
-            filters: filters.clone(),
+    let containers = docker
+        .list_containers(Some(ListContainersOptions {
+            all: true,
+            filters,
+            ..Default::default()
         }))
-        .await
-    {
-        Ok(result) => {
-            let num_containers_deleted = result.containers_deleted.map(|d| d.len()).unwrap_or(0);
-            let space_reclaimed_bytes = result.space_reclaimed;
-            tracing::info!(
-                num_containers_deleted,
-                space_reclaimed_bytes,
-                "Done pruning containers."
-            );
+        .await?;
+
+    let mut num_containers_deleted = 0;
+    for container in containers {
+        if container.created.unwrap_or_default() > since_unixtime {
+            continue;
+        }
+
+        // Docker reports container names with a leading slash.
+        let Some(name) = container
+            .names
+            .as_ref()
+            .and_then(|names| names.first())
+            .map(|name| name.trim_start_matches('/').to_string())
+        else {
+            continue;
+        };
+
+        if let Ok(backend_id) = BackendName::try_from(ContainerId::from(name.clone())) {
+            let retained = retain_callback
+                .lock()
+                .expect("Retain callback lock poisoned.")
+                .as_ref()
+                .is_some_and(|retain| retain(&backend_id));
+            if retained {
+                continue;
+            }
+        }
+
+        match docker.remove_container(&name, None).await {
+            Ok(()) => num_containers_deleted += 1,
+            Err(e) => tracing::error!(?e, container = name, "Error removing container."),
         }
-        Err(e) => tracing::error!(?e, "Error pruning containers."),
     }
+    tracing::info!(num_containers_deleted, "Done pruning containers.");
 
     if prune_images {
         let filters: HashMap<String, Vec<String>> =
@@ -1,63 +1,329 @@
-use plane_common::types::backend_state::BackendError;
-use std::{
-    net::SocketAddr,
-    time::{Duration, Instant},
+use super::types::ContainerId;
+use bollard::{
+    exec::{CreateExecOptions, StartExecResults},
+    Docker,
 };
+use plane_common::{
+    names::BackendName,
+    types::{backend_state::BackendError, ProbeCheck, ReadinessProbe},
+};
+use std::{future::Future, net::SocketAddr, ops::Range, time::Duration};
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     net::TcpStream,
 };
+use tokio_stream::StreamExt;
 
 const WAIT_TIMEOUT_MS: u64 = 100;
 const REQUEST_TIMEOUT_MS: u64 = 1_000;
 const STARTUP_TIMEOUT_SECS: u64 = 300; // 5 min timeout
+
+/// How long a single readiness check may take before it counts as failed.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
 const HEAD_REQUEST: &[u8] = b"HEAD / HTTP/1.1\r\nHost: localhost\r\n\r\n";
 
-/// Waits until an HTTP request to the given socket address succeeds or times out after 5 minutes.
-pub async fn wait_for_backend(address: SocketAddr) -> Result<(), BackendError> {
-    let start = Instant::now();
-    let timeout_duration = Duration::from_secs(STARTUP_TIMEOUT_SECS);
-    loop {
-        if start.elapsed() > timeout_duration {
-            return Err(BackendError::StartupTimeout);
+/// Statuses that pass an HTTP check that does not list its expected statuses.
+const DEFAULT_EXPECTED_STATUS: Range<u16> = 200..400;
+
+/// Longest status line we read before giving up on parsing it.
+const MAX_STATUS_LINE_BYTES: usize = 1024;
+
+/// Returns true if the backend responds to a request with anything that looks like HTTP.
+async fn check_any_http(address: SocketAddr) -> bool {
+    let Ok(mut conn) = TcpStream::connect(address).await else {
+        return false;
+    };
+
+    if conn.write_all(HEAD_REQUEST).await.is_err() {
+        return false;
+    }
+
+    let mut buffer = [0; 5]; // We expect the response to start with b"HTTP/"
+    let Ok(result) = tokio::time::timeout(
+        Duration::from_millis(REQUEST_TIMEOUT_MS),
+        conn.read_exact(&mut buffer),
+    )
+    .await
+    else {
+        // timed out
+        tracing::warn!("Timed out reading from socket.");
+        return false;
+    };
+
+    result.is_ok() && &buffer == b"HTTP/"
+}
+
+/// Sends a `GET` request for `path` and returns the status of the response.
+async fn http_status(address: SocketAddr, path: &str) -> Option<u16> {
+    let mut conn = TcpStream::connect(address).await.ok()?;
+
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    conn.write_all(request.as_bytes()).await.ok()?;
+
+    let read_status_line = async {
+        let mut buffer = Vec::new();
+        let mut chunk = [0; 256];
+        while !buffer.windows(2).any(|w| w == b"\r\n") && buffer.len() < MAX_STATUS_LINE_BYTES {
+            let n = conn.read(&mut chunk).await.ok()?;
+            if n == 0 {
+                break;
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        }
+        Some(buffer)
+    };
+
+    let Ok(buffer) =
+        tokio::time::timeout(Duration::from_millis(REQUEST_TIMEOUT_MS), read_status_line).await
+    else {
+        tracing::warn!("Timed out reading from socket.");
+        return None;
+    };
+
+    parse_status(&buffer?)
+}
+
+/// Parses the status code from the status line of an HTTP response, e.g. `HTTP/1.1 200 OK`.
+fn parse_status(response: &[u8]) -> Option<u16> {
+    let response = std::str::from_utf8(response).ok()?;
+    let status_line = response.lines().next()?;
+    let mut parts = status_line.split_whitespace();
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+    parts.next()?.parse().ok()
+}
+
+/// Returns true if a `GET` request for `path` gets a response with an expected status.
+async fn check_http(address: SocketAddr, path: &str, expected_status: Option<&[u16]>) -> bool {
+    let Some(status) = http_status(address, path).await else {
+        return false;
+    };
+
+    let passed = match expected_status {
+        Some(expected_status) => expected_status.contains(&status),
+        None => DEFAULT_EXPECTED_STATUS.contains(&status),
+    };
+    if !passed {
+        tracing::info!(status, "Backend responded with unexpected status.");
+    }
+    passed
+}
+
+/// Runs a command in the backend's container, returning true if it exits with code 0.
+async fn check_exec(docker: &Docker, backend_id: &BackendName, command: &[String]) -> bool {
+    let container_id = ContainerId::from(backend_id);
+    let exec = match docker
+        .create_exec(
+            container_id.as_str(),
+            CreateExecOptions {
+                cmd: Some(command.to_vec()),
+                attach_stdout: Some(true),
+                attach_stderr: Some(true),
+                ..Default::default()
+            },
+        )
+        .await
+    {
+        Ok(exec) => exec,
+        Err(err) => {
+            tracing::warn!(?err, %backend_id, "Failed to create probe command.");
+            return false;
         }
+    };
 
-        let Ok(mut conn) = TcpStream::connect(address).await else {
-            tokio::time::sleep(Duration::from_millis(WAIT_TIMEOUT_MS)).await;
-            continue;
-        };
+    match docker.start_exec(&exec.id, None).await {
+        Ok(StartExecResults::Attached { mut output, .. }) => {
+            // The output ends when the command exits.
+            while output.next().await.is_some() {}
+        }
+        Ok(StartExecResults::Detached) => {}
+        Err(err) => {
+            tracing::warn!(?err, %backend_id, "Failed to start probe command.");
+            return false;
+        }
+    }
 
-        if conn.write_all(HEAD_REQUEST).await.is_err() {
-            tokio::time::sleep(Duration::from_millis(WAIT_TIMEOUT_MS)).await;
-            continue;
+    match docker.inspect_exec(&exec.id).await {
+        Ok(result) => result.exit_code == Some(0),
+        Err(err) => {
+            tracing::warn!(?err, %backend_id, "Failed to inspect probe command.");
+            false
         }
+    }
+}
 
-        let mut buffer = [0; 5]; // We expect the response to start with b"HTTP/"
-        let Ok(result) = tokio::time::timeout(
+/// Runs a check against a backend once, returning true if it passes.
+pub async fn run_check(
+    docker: &Docker,
+    backend_id: &BackendName,
+    address: SocketAddr,
+    check: &ProbeCheck,
+) -> bool {
+    match check {
+        ProbeCheck::Http {
+            path,
+            expected_status,
+        } => check_http(address, path, expected_status.as_deref()).await,
+        ProbeCheck::Tcp => tokio::time::timeout(
             Duration::from_millis(REQUEST_TIMEOUT_MS),
-            conn.read(&mut buffer),
+            TcpStream::connect(address),
         )
         .await
-        else {
-            // timed out
-            tracing::warn!("Timed out reading from socket.");
-            tokio::time::sleep(Duration::from_millis(WAIT_TIMEOUT_MS)).await;
-            continue;
-        };
-
-        if result.is_err() {
-            // error
-            tokio::time::sleep(Duration::from_millis(WAIT_TIMEOUT_MS)).await;
-            continue;
-        }
-
-        if &buffer == b"HTTP/" {
-            // success
-            break Ok(());
-        } else {
+        .is_ok_and(|result| result.is_ok()),
+        ProbeCheck::Exec { command } => check_exec(docker, backend_id, command).await,
+    }
+}
+
+/// Runs `check` every `interval` until it passes, or fails with a startup timeout once
+/// `timeout` has passed. A check that takes longer than `check_timeout` counts as failed.
+async fn wait_for_check<F, Fut>(
+    timeout: Duration,
+    interval: Duration,
+    check_timeout: Duration,
+    mut check: F,
+) -> Result<(), BackendError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>,
+{
+    let wait = async {
+        while !tokio::time::timeout(check_timeout, check())
+            .await
+            .unwrap_or(false)
+        {
             // not ready yet
-            tokio::time::sleep(Duration::from_millis(WAIT_TIMEOUT_MS)).await;
-            continue;
+            tokio::time::sleep(interval).await;
+        }
+    };
+
+    tokio::time::timeout(timeout, wait)
+        .await
+        .map_err(|_| BackendError::StartupTimeout)
+}
+
+/// Waits until the backend passes its readiness probe, or times out. Without a probe, waits
+/// until an HTTP request to the given socket address succeeds or times out after 5 minutes.
+pub async fn wait_for_backend(
+    docker: &Docker,
+    backend_id: &BackendName,
+    address: SocketAddr,
+    probe: Option<&ReadinessProbe>,
+) -> Result<(), BackendError> {
+    match probe {
+        Some(probe) => {
+            wait_for_check(probe.timeout(), probe.interval(), CHECK_TIMEOUT, || {
+                run_check(docker, backend_id, address, &probe.check)
+            })
+            .await
+        }
+        None => {
+            wait_for_check(
+                Duration::from_secs(STARTUP_TIMEOUT_SECS),
+                Duration::from_millis(WAIT_TIMEOUT_MS),
+                CHECK_TIMEOUT,
+                || check_any_http(address),
+            )
+            .await
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+    use tokio::net::TcpListener;
+
+    /// Serves HTTP responses with status 503 to the first `loading_requests` requests, and
+    /// 200 after that.
+    async fn serve_loading_backend(loading_requests: usize) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        tokio::spawn(async move {
+            loop {
+                let (mut conn, _) = listener.accept().await.unwrap();
+                let mut buffer = [0; 1024];
+                let _ = conn.read(&mut buffer).await;
+                let response: &[u8] = if requests.fetch_add(1, Ordering::SeqCst) < loading_requests
+                {
+                    b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n"
+                } else {
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n"
+                };
+                let _ = conn.write_all(response).await;
+            }
+        });
+
+        address
+    }
+
+    async fn wait_for_http(address: SocketAddr, timeout: Duration) -> Result<(), BackendError> {
+        wait_for_check(timeout, Duration::from_millis(10), CHECK_TIMEOUT, || {
+            check_http(address, "/", None)
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_hung_check_is_retried() {
+        let attempts = AtomicUsize::new(0);
+
+        // The first check never completes, and the second passes.
+        let result = wait_for_check(
+            Duration::from_secs(10),
+            Duration::from_millis(10),
+            Duration::from_millis(100),
+            || async {
+                if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                    std::future::pending::<()>().await;
+                }
+                true
+            },
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_http_check_waits_for_expected_status() {
+        let address = serve_loading_backend(3).await;
+
+        let result = wait_for_http(address, Duration::from_secs(10)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_http_check_times_out_while_loading() {
+        let address = serve_loading_backend(usize::MAX).await;
+
+        let result = wait_for_http(address, Duration::from_secs(1)).await;
+        assert!(matches!(result, Err(BackendError::StartupTimeout)));
+
+        // Without a probe, any HTTP response means the backend is ready.
+        assert!(check_any_http(address).await);
+        assert!(check_http(address, "/", Some(&[503])).await);
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!(parse_status(b"HTTP/1.1 200 OK\r\n\r\n"), Some(200));
+        assert_eq!(
+            parse_status(b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n"),
+            Some(503)
+        );
+        assert_eq!(parse_status(b"HTTP/1.0 404\r\n"), Some(404));
+        assert_eq!(parse_status(b"SSH-2.0-OpenSSH\r\n"), None);
+        assert_eq!(parse_status(b""), None);
+    }
+}
@@ -3,8 +3,8 @@ use docker::{SpawnResult, TerminateEvent};
 use futures_util::Stream;
 use plane_common::{
     names::BackendName,
-    protocol::{AcquiredKey, BackendMetricsMessage},
-    types::{backend_state::BackendError, BearerToken},
+    protocol::{AcquiredKey, BackendLogMessage, BackendMetricsMessage},
+    types::{backend_state::BackendError, BearerToken, ProbeCheck},
 };
 use std::{net::SocketAddr, pin::Pin};
 
@@ -12,6 +12,15 @@ pub mod docker;
 #[allow(unused)] // for now, to disable clippy noise
 pub mod unix_socket;
 
+/// An amount of memory and CPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RuntimeResources {
+    pub memory_bytes: u64,
+
+    /// CPU, in millicores (thousandths of a core).
+    pub cpu_millicores: u64,
+}
+
 #[async_trait::async_trait]
 pub trait Runtime: Send + Sync + 'static {
     async fn prepare(&self, config: &serde_json::Value) -> Result<(), Error>;
@@ -24,6 +33,16 @@ pub trait Runtime: Send + Sync + 'static {
         static_token: Option<&BearerToken>,
     ) -> Result<SpawnResult, Error>;
 
+    /// Starts the process of a backend that has exited again, keeping its configuration and
+    /// any data it wrote.
+    async fn restart(&self, backend_id: &BackendName) -> Result<SpawnResult, Error>;
+
+    /// Pauses the backend's process, keeping its memory but stopping it from using CPU.
+    async fn suspend(&self, backend_id: &BackendName) -> Result<(), Error>;
+
+    /// Unpauses a process paused by `suspend`.
+    async fn resume(&self, backend_id: &BackendName) -> Result<(), Error>;
+
     /// Attempts to terminate the backend. If `hard` is true, the runtime should make every effort
     /// to forcibly terminate the backend immediately; otherwise it should invoke graceful shutdown.
     /// If the backend is already terminated or does not exist, this should return `Ok(false)`.
@@ -33,11 +52,35 @@ pub trait Runtime: Send + Sync + 'static {
     /// any backend.
     fn metrics_callback(&self, sender: Box<dyn Fn(BackendMetricsMessage) + Send + Sync + 'static>);
 
+    /// Provides a callback to be called with each line any backend's process writes to its
+    /// stdout or stderr.
+    fn log_callback(&self, sender: Box<dyn Fn(BackendLogMessage) + Send + Sync + 'static>);
+
+    /// Provides a callback that returns true for backends whose exited process must be kept,
+    /// because the backend may still restart it. Runtimes that clean up exited processes must
+    /// skip these backends.
+    fn retain_callback(&self, retain: Box<dyn Fn(&BackendName) -> bool + Send + Sync + 'static>);
+
     fn events(&self) -> Pin<Box<dyn Stream<Item = TerminateEvent> + Send>>;
 
+    /// Waits until the backend at the given address is ready to receive requests, using the
+    /// readiness check of its executor config.
     async fn wait_for_backend(
         &self,
         backend: &BackendName,
+        executable: &serde_json::Value,
         address: SocketAddr,
     ) -> Result<(), BackendError>;
+
+    /// Runs a liveness check against a ready backend once, returning true if it passes.
+    async fn check_liveness(
+        &self,
+        backend: &BackendName,
+        address: SocketAddr,
+        check: &ProbeCheck,
+    ) -> bool;
+
+    /// Returns the total memory and CPU of the host that backends run on, or `None` if the
+    /// runtime cannot determine them.
+    async fn resources(&self) -> Result<Option<RuntimeResources>, Error>;
 }
@@ -2,13 +2,13 @@ use crate::typed_unix_socket::client::TypedUnixSocketClient;
 
 use super::{
     docker::{SpawnResult, TerminateEvent},
-    Runtime,
+    Runtime, RuntimeResources,
 };
 use anyhow::{Error, Result};
 use plane_common::{
     names::BackendName,
-    protocol::{AcquiredKey, BackendMetricsMessage},
-    types::{backend_state::BackendError, BearerToken, DockerExecutorConfig},
+    protocol::{AcquiredKey, BackendLogMessage, BackendMetricsMessage},
+    types::{backend_state::BackendError, BearerToken, DockerExecutorConfig, ProbeCheck},
 };
 use serde::{Deserialize, Serialize};
 use std::{net::SocketAddr, path::PathBuf, pin::Pin};
@@ -23,17 +23,26 @@ pub enum MessageToServer {
         Option<AcquiredKey>,
         Option<BearerToken>,
     ),
+    Restart(BackendName),
+    Suspend(BackendName),
+    Resume(BackendName),
     Terminate(BackendName, bool),
     WaitForBackend(BackendName, SocketAddr),
+    CheckLiveness(BackendName, SocketAddr, ProbeCheck),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum MessageToClient {
     PrepareResult(Result<(), String>),
     SpawnResult(Result<SpawnResult, String>),
+    RestartResult(Result<SpawnResult, String>),
+    SuspendResult(Result<(), String>),
+    ResumeResult(Result<(), String>),
     TerminateResult(Result<bool, String>),
     WaitForBackendResult(Result<(), BackendError>),
+    CheckLivenessResult(bool),
     MetricsMessage(BackendMetricsMessage),
+    LogMessage(BackendLogMessage),
     TerminateEvent(TerminateEvent),
 }
 
@@ -87,6 +96,42 @@ impl Runtime for UnixSocketRuntime {
         }
     }
 
+    async fn restart(&self, backend_id: &BackendName) -> Result<SpawnResult> {
+        let response = self
+            .client
+            .send_request(MessageToServer::Restart(backend_id.clone()))
+            .await?;
+        match response {
+            MessageToClient::RestartResult(Ok(result)) => Ok(result),
+            MessageToClient::RestartResult(Err(e)) => Err(Error::msg(e)),
+            _ => Err(Error::msg("Unexpected response from server")),
+        }
+    }
+
+    async fn suspend(&self, backend_id: &BackendName) -> Result<()> {
+        let response = self
+            .client
+            .send_request(MessageToServer::Suspend(backend_id.clone()))
+            .await?;
+        match response {
+            MessageToClient::SuspendResult(Ok(())) => Ok(()),
+            MessageToClient::SuspendResult(Err(e)) => Err(Error::msg(e)),
+            _ => Err(Error::msg("Unexpected response from server")),
+        }
+    }
+
+    async fn resume(&self, backend_id: &BackendName) -> Result<()> {
+        let response = self
+            .client
+            .send_request(MessageToServer::Resume(backend_id.clone()))
+            .await?;
+        match response {
+            MessageToClient::ResumeResult(Ok(())) => Ok(()),
+            MessageToClient::ResumeResult(Err(e)) => Err(Error::msg(e)),
+            _ => Err(Error::msg("Unexpected response from server")),
+        }
+    }
+
     async fn terminate(&self, backend_id: &BackendName, hard: bool) -> Result<bool> {
         let response = self
             .client
@@ -121,11 +166,28 @@ impl Runtime for UnixSocketRuntime {
         });
     }
 
+    fn log_callback(&self, sender: Box<dyn Fn(BackendLogMessage) + Send + Sync + 'static>) {
+        let mut event_rx = self.client.subscribe_events();
+        tokio::spawn(async move {
+            while let Ok(event) = event_rx.recv().await {
+                if let MessageToClient::LogMessage(log) = event {
+                    sender(log);
+                }
+            }
+        });
+    }
+
+    fn retain_callback(&self, _retain: Box<dyn Fn(&BackendName) -> bool + Send + Sync + 'static>) {
+        // Exited processes are not cleaned up by this runtime.
+    }
+
     async fn wait_for_backend(
         &self,
         backend: &BackendName,
+        _executable: &serde_json::Value,
         address: SocketAddr,
     ) -> Result<(), BackendError> {
+        // The server runs its own readiness check.
         let response = self
             .client
             .send_request(MessageToServer::WaitForBackend(backend.clone(), address))
@@ -138,6 +200,38 @@ impl Runtime for UnixSocketRuntime {
             )),
         }
     }
+
+    async fn check_liveness(
+        &self,
+        backend: &BackendName,
+        address: SocketAddr,
+        check: &ProbeCheck,
+    ) -> bool {
+        let response = self
+            .client
+            .send_request(MessageToServer::CheckLiveness(
+                backend.clone(),
+                address,
+                check.clone(),
+            ))
+            .await;
+        match response {
+            Ok(MessageToClient::CheckLivenessResult(passed)) => passed,
+            Ok(_) => {
+                tracing::error!("Unexpected response from server");
+                false
+            }
+            Err(err) => {
+                tracing::error!(?err, "Failed to send liveness check");
+                false
+            }
+        }
+    }
+
+    async fn resources(&self) -> Result<Option<RuntimeResources>> {
+        // The external executor does not report its host's resources.
+        Ok(None)
+    }
 }
 
 impl UnixSocketRuntime {
@@ -226,13 +226,29 @@ impl StateStore {
 
         Ok(active_backends)
     }
+
+    /// Counts the backends that are not in a Terminated state, by status.
+    pub fn backend_status_counts(&self) -> Result<Vec<(String, i64)>> {
+        let mut stmt = self.db_conn.prepare(
+            r#"
+                select json_extract("state", '$.status') as "status", count(*)
+                from "backend"
+                where "status" != 'terminated'
+                group by "status"
+            "#,
+        )?;
+
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use plane_common::{
-        log_types::BackendAddr,
+        log_types::{BackendAddr, NamedBackendAddrs},
         names::Name,
         types::{BackendStatus, TerminationReason},
     };
@@ -259,6 +275,8 @@ mod test {
                 &backend_id,
                 &BackendState::Ready {
                     address: dummy_addr(),
+                    named_addresses: NamedBackendAddrs::default(),
+                    restart: None,
                 },
                 Utc::now(),
             )
@@ -268,7 +286,9 @@ mod test {
         assert_eq!(
             result,
             BackendState::Ready {
-                address: dummy_addr()
+                address: dummy_addr(),
+                named_addresses: NamedBackendAddrs::default(),
+                restart: None,
             }
         );
     }
@@ -281,6 +301,8 @@ mod test {
 
         let ready_state = BackendState::Ready {
             address: dummy_addr(),
+            named_addresses: NamedBackendAddrs::default(),
+            restart: None,
         };
         {
             state_store
@@ -291,7 +313,9 @@ mod test {
             assert_eq!(
                 result,
                 BackendState::Ready {
-                    address: dummy_addr()
+                    address: dummy_addr(),
+                    named_addresses: NamedBackendAddrs::default(),
+                    restart: None,
                 }
             );
         }
@@ -333,6 +357,8 @@ mod test {
 
         let ready_state = BackendState::Ready {
             address: dummy_addr(),
+            named_addresses: NamedBackendAddrs::default(),
+            restart: None,
         };
         state_store
             .register_event(&backend_id, &ready_state, Utc::now())
@@ -347,7 +373,9 @@ mod test {
             assert_eq!(
                 event.state,
                 BackendState::Ready {
-                    address: dummy_addr()
+                    address: dummy_addr(),
+                    named_addresses: NamedBackendAddrs::default(),
+                    restart: None,
                 }
             );
         }
@@ -390,6 +418,8 @@ mod test {
 
         let ready_state = BackendState::Ready {
             address: dummy_addr(),
+            named_addresses: NamedBackendAddrs::default(),
+            restart: None,
         };
         state_store
             .register_event(&backend_id, &ready_state, Utc::now())
@@ -416,7 +446,9 @@ mod test {
             assert_eq!(
                 event.state,
                 BackendState::Ready {
-                    address: dummy_addr()
+                    address: dummy_addr(),
+                    named_addresses: NamedBackendAddrs::default(),
+                    restart: None,
                 }
             );
         }
@@ -450,7 +482,9 @@ mod test {
             assert_eq!(
                 event.state,
                 BackendState::Ready {
-                    address: dummy_addr()
+                    address: dummy_addr(),
+                    named_addresses: NamedBackendAddrs::default(),
+                    restart: None,
                 }
             );
         }
@@ -494,4 +528,33 @@ mod test {
 
         assert!(recv.try_recv().is_err());
     }
+
+    #[test]
+    fn backend_status_counts() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut state_store = StateStore::new(conn).unwrap();
+
+        let ready_state = BackendState::Ready {
+            address: dummy_addr(),
+            named_addresses: NamedBackendAddrs::default(),
+            restart: None,
+        };
+
+        for _ in 0..2 {
+            state_store
+                .register_event(&BackendName::new_random(), &ready_state, Utc::now())
+                .unwrap();
+        }
+
+        state_store
+            .register_event(
+                &BackendName::new_random(),
+                &ready_state.to_terminated(None),
+                Utc::now(),
+            )
+            .unwrap();
+
+        let counts = state_store.backend_status_counts().unwrap();
+        assert_eq!(counts, vec![("ready".to_string(), 2)]);
+    }
 }
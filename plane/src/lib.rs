@@ -17,6 +17,7 @@ pub mod dns;
 pub mod drone;
 pub mod heartbeat_consts;
 pub mod init_tracing;
+pub mod metrics;
 pub mod proxy;
 pub mod signals;
 pub mod typed_unix_socket;
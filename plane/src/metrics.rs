@@ -0,0 +1,24 @@
+//! Helpers shared by the components that expose Prometheus metrics.
+
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use prometheus::{Encoder, Registry, TextEncoder};
+
+/// Renders the metrics in a registry in the Prometheus text exposition format.
+pub fn metrics_response(registry: &Registry) -> Response {
+    let encoder = TextEncoder::new();
+    let mut body = Vec::new();
+
+    if let Err(err) = encoder.encode(&registry.gather(), &mut body) {
+        tracing::error!(?err, "Failed to encode metrics.");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+
+    (
+        [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+        body,
+    )
+        .into_response()
+}
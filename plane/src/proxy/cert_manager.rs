@@ -1,4 +1,4 @@
-use super::{cert_pair::CertificatePair, AcmeConfig};
+use super::{cert_pair::CertificatePair, metrics::PROXY_METRICS, AcmeConfig};
 use acme2_eab::{
     gen_rsa_private_key, Account, AccountBuilder, AuthorizationStatus, ChallengeStatus, Csr,
     DirectoryBuilder, OrderBuilder, OrderStatus,
@@ -61,6 +61,9 @@ impl CertWatcher {
             .expect("Certified key lock poisoned.");
 
         if let Some(cert_pair) = cert_pair.as_ref() {
+            PROXY_METRICS
+                .certificate_expiry_timestamp_seconds
+                .set(cert_pair.validity_end.0.timestamp());
             lock.replace(Arc::new(cert_pair.certified_key.clone()));
         } else {
             lock.take();
@@ -21,6 +21,10 @@ pub struct ProxyOpts {
     #[clap(long)]
     controller_url: Url,
 
+    /// Join token to present to the controller, if it requires one.
+    #[clap(long)]
+    join_token: Option<String>,
+
     #[clap(long)]
     cluster: ClusterName,
 
@@ -53,6 +57,10 @@ pub struct ProxyOpts {
     /// URL to redirect the root path to.
     #[clap(long)]
     root_redirect_url: Option<Url>,
+
+    /// Port to serve Prometheus metrics on. If not set, metrics are not served.
+    #[clap(long)]
+    metrics_port: Option<u16>,
 }
 
 impl ProxyOpts {
@@ -115,11 +123,13 @@ impl ProxyOpts {
         Ok(ProxyConfig {
             name,
             controller_url: self.controller_url,
+            join_token: self.join_token,
             cluster: self.cluster,
             cert_path: self.cert_path,
             port_config,
             acme_config,
             root_redirect_url: self.root_redirect_url,
+            metrics_port: self.metrics_port,
         })
     }
 }
@@ -1,3 +1,4 @@
+use super::metrics::PROXY_METRICS;
 use crate::heartbeat_consts::HEARTBEAT_INTERVAL;
 use plane_common::names::BackendName;
 use std::{
@@ -53,6 +54,7 @@ impl ConnectionMonitor {
     }
 
     pub fn inc_connection(&mut self, backend_id: &BackendName) {
+        PROXY_METRICS.active_websocket_upgrades.inc();
         match self.backends.entry(backend_id.clone()) {
             Entry::Occupied(mut entry) => {
                 let backend_entry = entry.get_mut();
@@ -78,6 +80,7 @@ impl ConnectionMonitor {
         match self.backends.entry(backend_id.clone()) {
             Entry::Occupied(mut entry) => {
                 entry.get_mut().active_connections -= 1;
+                PROXY_METRICS.active_websocket_upgrades.dec();
             }
             Entry::Vacant(_) => {
                 tracing::warn!(
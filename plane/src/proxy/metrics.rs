@@ -0,0 +1,140 @@
+use crate::metrics::metrics_response;
+use axum::{routing::get, Router};
+use plane_dynamic_proxy::hyper::StatusCode;
+use prometheus::{
+    histogram_opts, opts, Histogram, HistogramVec, IntCounterVec, IntGauge, Registry,
+};
+use std::{sync::LazyLock, time::Duration};
+use tokio::net::TcpListener;
+
+/// Metrics reported by the proxy on its metrics listener.
+pub struct ProxyMetrics {
+    registry: Registry,
+
+    /// Latency of proxied requests until response headers are returned, labeled by status code.
+    /// The `_count` of this histogram is the number of requests.
+    pub request_duration_seconds: HistogramVec,
+
+    /// Upstream requests that failed, labeled by `kind` (`gateway_timeout` or `bad_gateway`).
+    pub upstream_errors: IntCounterVec,
+
+    /// Websocket (and other upgraded) connections currently open.
+    pub active_websocket_upgrades: IntGauge,
+
+    /// Route map lookups, labeled by `result` (`hit` or `miss`).
+    pub route_cache_lookups: IntCounterVec,
+
+    /// Time spent waiting for the controller to return route info on a cache miss.
+    pub route_info_wait_seconds: Histogram,
+
+    /// Expiry time of the current TLS certificate, as a Unix timestamp.
+    pub certificate_expiry_timestamp_seconds: IntGauge,
+}
+
+pub static PROXY_METRICS: LazyLock<ProxyMetrics> = LazyLock::new(ProxyMetrics::new);
+
+impl ProxyMetrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let request_duration_seconds = HistogramVec::new(
+            histogram_opts!(
+                "plane_proxy_request_duration_seconds",
+                "Latency of proxied requests by status code."
+            ),
+            &["status"],
+        )
+        .expect("Metric options are valid.");
+
+        let upstream_errors = IntCounterVec::new(
+            opts!(
+                "plane_proxy_upstream_errors_total",
+                "Upstream requests that failed, by kind."
+            ),
+            &["kind"],
+        )
+        .expect("Metric options are valid.");
+
+        let active_websocket_upgrades = IntGauge::new(
+            "plane_proxy_active_websocket_upgrades",
+            "Upgraded connections currently open.",
+        )
+        .expect("Metric options are valid.");
+
+        let route_cache_lookups = IntCounterVec::new(
+            opts!(
+                "plane_proxy_route_cache_lookups_total",
+                "Route map lookups by result."
+            ),
+            &["result"],
+        )
+        .expect("Metric options are valid.");
+
+        let route_info_wait_seconds = Histogram::with_opts(histogram_opts!(
+            "plane_proxy_route_info_wait_seconds",
+            "Time spent waiting for route info from the controller."
+        ))
+        .expect("Metric options are valid.");
+
+        let certificate_expiry_timestamp_seconds = IntGauge::new(
+            "plane_proxy_certificate_expiry_timestamp_seconds",
+            "Expiry time of the current TLS certificate, as a Unix timestamp.",
+        )
+        .expect("Metric options are valid.");
+
+        for collector in [
+            Box::new(request_duration_seconds.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(upstream_errors.clone()),
+            Box::new(active_websocket_upgrades.clone()),
+            Box::new(route_cache_lookups.clone()),
+            Box::new(route_info_wait_seconds.clone()),
+            Box::new(certificate_expiry_timestamp_seconds.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("Metric names are unique.");
+        }
+
+        Self {
+            registry,
+            request_duration_seconds,
+            upstream_errors,
+            active_websocket_upgrades,
+            route_cache_lookups,
+            route_info_wait_seconds,
+            certificate_expiry_timestamp_seconds,
+        }
+    }
+
+    pub fn observe_request(&self, status: StatusCode, duration: Duration) {
+        self.request_duration_seconds
+            .with_label_values(&[status.as_str()])
+            .observe(duration.as_secs_f64());
+    }
+
+    pub fn record_upstream_error(&self, status: StatusCode) {
+        let kind = if status == StatusCode::GATEWAY_TIMEOUT {
+            "gateway_timeout"
+        } else {
+            "bad_gateway"
+        };
+
+        self.upstream_errors.with_label_values(&[kind]).inc();
+    }
+
+    pub fn record_route_cache_lookup(&self, hit: bool) {
+        let result = if hit { "hit" } else { "miss" };
+        self.route_cache_lookups.with_label_values(&[result]).inc();
+    }
+}
+
+/// Serves the proxy's metrics on `/metrics`. This is served on its own listener, so that
+/// metrics are not exposed on the public proxy port.
+pub async fn run_metrics_server(listener: TcpListener) -> std::io::Result<()> {
+    let app = Router::new().route(
+        "/metrics",
+        get(|| async { metrics_response(&PROXY_METRICS.registry) }),
+    );
+
+    axum::serve(listener, app).await
+}
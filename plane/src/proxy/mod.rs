@@ -1,6 +1,8 @@
 use self::proxy_connection::ProxyConnection;
 use crate::proxy::cert_manager::watcher_manager_pair;
+use crate::proxy::metrics::run_metrics_server;
 use crate::signals::wait_for_shutdown_signal;
+use crate::util::GuardHandle;
 use anyhow::Result;
 use plane_common::names::ProxyName;
 use plane_common::types::ClusterName;
@@ -10,15 +12,18 @@ use plane_dynamic_proxy::server::{
 };
 use proxy_server::ProxyState;
 use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::net::TcpListener;
 use url::Url;
 
 pub mod cert_manager;
 mod cert_pair;
 pub mod command;
 pub mod connection_monitor;
+pub mod metrics;
 pub mod proxy_connection;
 pub mod proxy_server;
 mod request;
@@ -95,16 +100,26 @@ fn is_false(value: &bool) -> bool {
 pub struct ProxyConfig {
     pub name: ProxyName,
     pub controller_url: Url,
+
+    /// Join token presented to the controller, if it requires one.
+    pub join_token: Option<String>,
+
     pub cluster: ClusterName,
     pub cert_path: Option<PathBuf>,
     pub port_config: ServerPortConfig,
     pub acme_config: Option<AcmeConfig>,
     pub root_redirect_url: Option<Url>,
+
+    /// If set, Prometheus metrics are served on `/metrics` on this port.
+    pub metrics_port: Option<u16>,
 }
 
 pub async fn run_proxy(config: ProxyConfig) -> Result<()> {
     tracing::info!(name=%config.name, "Starting proxy");
-    let client = PlaneClient::new(config.controller_url);
+    let mut client = PlaneClient::new(config.controller_url);
+    if let Some(join_token) = &config.join_token {
+        client = client.with_join_token(join_token);
+    }
     let (mut cert_watcher, cert_manager) = watcher_manager_pair(
         config.cluster.clone(),
         config.cert_path.as_deref(),
@@ -116,6 +131,19 @@ pub async fn run_proxy(config: ProxyConfig) -> Result<()> {
         config.root_redirect_url.map(|u| u.to_string()),
     ));
 
+    let _metrics_handle = match config.metrics_port {
+        Some(metrics_port) => {
+            let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, metrics_port)).await?;
+            tracing::info!(metrics_port, "Serving metrics.");
+            Some(GuardHandle::new(async move {
+                if let Err(err) = run_metrics_server(listener).await {
+                    tracing::error!(?err, "Metrics server failed.");
+                }
+            }))
+        }
+        None => None,
+    };
+
     // This returns a guard, we need to keep it in scope so that the connection is not terminated.
     let _proxy_connection = ProxyConnection::new(
         config.name,
@@ -73,6 +73,17 @@ impl ProxyConnection {
                             MessageToProxy::BackendRemoved { backend } => {
                                 state.inner.route_map.remove_backend(&backend);
                             }
+                            MessageToProxy::BackendSuspended { backend }
+                            | MessageToProxy::InvalidateRoutes { backend } => {
+                                state.inner.route_map.evict_backend(&backend);
+                            }
+                            MessageToProxy::TokensRevoked(revoked) => {
+                                tracing::info!(
+                                    backend = revoked.backend.as_value(),
+                                    "Received token revocation"
+                                );
+                                state.revoke_tokens(revoked);
+                            }
                         }
                     }
                 }
@@ -1,10 +1,16 @@
 use super::{
     connection_monitor::ConnectionMonitorHandle,
-    request::{get_and_maybe_remove_bearer_token, subdomain_from_host},
+    metrics::PROXY_METRICS,
+    request::{get_and_maybe_remove_bearer_token, split_port_name, subdomain_from_host},
     route_map::RouteMap,
 };
 use bytes::Bytes;
-use plane_common::{names::Name, protocol::RouteInfo, version::SERVER_NAME};
+use plane_common::{
+    names::{BackendName, Name},
+    protocol::{RouteInfo, TokensRevoked},
+    types::BearerToken,
+    version::SERVER_NAME,
+};
 use plane_dynamic_proxy::{
     body::{simple_empty_body, SimpleBody},
     hyper::{
@@ -13,14 +19,21 @@ use plane_dynamic_proxy::{
         service::Service,
         Request, Response, StatusCode, Uri,
     },
-    proxy::ProxyClient,
+    proxy::{ProxyClient, UpstreamError},
     request::MutableRequest,
 };
 use std::{
     future::{ready, Future},
     sync::atomic::{AtomicBool, Ordering},
+    time::Instant,
 };
 use std::{pin::Pin, sync::Arc};
+use tokio::sync::broadcast::{self, error::RecvError};
+use valuable::Valuable;
+
+/// Number of token revocations that can be buffered for upgraded connections
+/// before they start missing revocations.
+const REVOCATION_CHANNEL_CAPACITY: usize = 128;
 
 pub struct ProxyStateInner {
     pub route_map: RouteMap,
@@ -28,6 +41,10 @@ pub struct ProxyStateInner {
     pub monitor: ConnectionMonitorHandle,
     pub connected: AtomicBool,
 
+    /// Broadcasts token revocations to upgraded connections, so that
+    /// connections established with revoked tokens can be closed.
+    pub revocations: broadcast::Sender<TokensRevoked>,
+
     /// If set, the "root" path (/) will redirect to this URL.
     pub root_redirect_url: Option<String>,
 }
@@ -50,6 +67,7 @@ impl ProxyState {
             proxy_client: ProxyClient::new(),
             monitor: ConnectionMonitorHandle::new(),
             connected: AtomicBool::new(false),
+            revocations: broadcast::channel(REVOCATION_CHANNEL_CAPACITY).0,
             root_redirect_url,
         };
 
@@ -65,18 +83,60 @@ impl ProxyState {
     pub fn is_ready(&self) -> bool {
         self.inner.connected.load(Ordering::Relaxed)
     }
+
+    /// Stops routing the revoked tokens and closes any upgraded connections
+    /// established with them.
+    pub fn revoke_tokens(&self, revoked: TokensRevoked) {
+        self.inner
+            .route_map
+            .remove_user_tokens(&revoked.backend, &revoked.user);
+        // An error here just means that there are no upgraded connections.
+        let _ = self.inner.revocations.send(revoked);
+    }
+}
+
+/// Resolves once the tokens of the given user for the given backend are revoked.
+/// Never resolves for connections without a user, since their tokens cannot be revoked.
+/// If revocations were missed, the token is looked up again and the connection is closed
+/// unless it still routes to the same backend.
+async fn wait_for_revocation(
+    mut revocations: broadcast::Receiver<TokensRevoked>,
+    route_map: &RouteMap,
+    token: &BearerToken,
+    backend: &BackendName,
+    user: Option<&str>,
+) {
+    let Some(user) = user else {
+        return std::future::pending().await;
+    };
+
+    loop {
+        match revocations.recv().await {
+            Ok(revoked) if revoked.backend == *backend && revoked.user == user => return,
+            Ok(_) => {}
+            Err(RecvError::Lagged(count)) => {
+                tracing::warn!(count, "Upgraded connection missed token revocations.");
+                match route_map.lookup(token).await {
+                    Some(route_info) if route_info.backend_id == *backend => {}
+                    _ => return,
+                }
+            }
+            Err(RecvError::Closed) => return std::future::pending().await,
+        }
+    }
 }
 
+type ProxyFuture = Pin<
+    Box<
+        dyn Future<Output = Result<Response<SimpleBody>, Box<dyn std::error::Error + Send + Sync>>>
+            + Send,
+    >,
+>;
+
 impl Service<Request<Incoming>> for ProxyState {
     type Response = Response<SimpleBody>;
     type Error = Box<dyn std::error::Error + Send + Sync>;
-    type Future = Pin<
-        Box<
-            dyn Future<
-                    Output = Result<Response<SimpleBody>, Box<dyn std::error::Error + Send + Sync>>,
-                > + Send,
-        >,
-    >;
+    type Future = ProxyFuture;
 
     fn call(&self, request: Request<Incoming>) -> Self::Future {
         // Handle "/ready"
@@ -90,6 +150,21 @@ impl Service<Request<Incoming>> for ProxyState {
             }
         }
 
+        let start = Instant::now();
+        let future = self.handle_request(request);
+
+        Box::pin(async move {
+            let result = future.await;
+            if let Ok(response) = &result {
+                PROXY_METRICS.observe_request(response.status(), start.elapsed());
+            }
+            result
+        })
+    }
+}
+
+impl ProxyState {
+    fn handle_request(&self, request: Request<Incoming>) -> ProxyFuture {
         if request.uri().path() == "/" {
             if let Some(root_redirect_url) = &self.inner.root_redirect_url {
                 let mut response = Response::builder()
@@ -117,6 +192,7 @@ impl Service<Request<Incoming>> for ProxyState {
             // This should have already been handled by the root redirect above.
             return Box::pin(ready(status_code_to_response(StatusCode::BAD_REQUEST)));
         };
+        let (bearer_token, port_name) = split_port_name(bearer_token);
 
         let Ok(uri) = Uri::from_parts(uri_parts) else {
             return Box::pin(ready(status_code_to_response(StatusCode::BAD_REQUEST)));
@@ -133,7 +209,12 @@ impl Service<Request<Incoming>> for ProxyState {
                 return status_code_to_response(StatusCode::GONE);
             };
 
-            if let Err(status_code) = prepare_request(&mut request, &route_info, &original_path) {
+            if let Err(status_code) = prepare_request(
+                &mut request,
+                &route_info,
+                port_name.as_deref(),
+                &original_path,
+            ) {
                 return status_code_to_response(status_code);
             }
 
@@ -142,7 +223,12 @@ impl Service<Request<Incoming>> for ProxyState {
             let result = inner.proxy_client.request(request).await;
 
             let (mut res, upgrade_handler) = match result {
-                Ok((res, upgrade_handler)) => (res, upgrade_handler),
+                Ok((res, upgrade_handler)) => {
+                    if res.extensions().get::<UpstreamError>().is_some() {
+                        PROXY_METRICS.record_upstream_error(res.status());
+                    }
+                    (res, upgrade_handler)
+                }
                 Err(e) => {
                     tracing::error!(?e, "Error proxying request");
                     return status_code_to_response(StatusCode::INTERNAL_SERVER_ERROR);
@@ -156,10 +242,30 @@ impl Service<Request<Incoming>> for ProxyState {
                     .expect("Monitor lock poisoned")
                     .inc_connection(&route_info.backend_id);
                 let backend_id = route_info.backend_id.clone();
+                let user = route_info.user.clone();
+                let revocations = inner.revocations.subscribe();
+                let inner = inner.clone();
                 tokio::spawn(async move {
-                    if let Err(err) = upgrade_handler.run().await {
-                        tracing::error!("Error running upgrade handler: {}", err);
-                    };
+                    // Dropping the upgrade handler's future closes the connection.
+                    tokio::select! {
+                        result = upgrade_handler.run() => {
+                            if let Err(err) = result {
+                                tracing::error!("Error running upgrade handler: {}", err);
+                            }
+                        }
+                        () = wait_for_revocation(
+                            revocations,
+                            &inner.route_map,
+                            &bearer_token,
+                            &backend_id,
+                            user.as_deref(),
+                        ) => {
+                            tracing::info!(
+                                backend = backend_id.as_value(),
+                                "Closing upgraded connection because its token was revoked."
+                            );
+                        }
+                    }
 
                     monitor
                         .lock()
@@ -185,6 +291,7 @@ impl Service<Request<Incoming>> for ProxyState {
 fn prepare_request<T>(
     request: &mut MutableRequest<T>,
     route_info: &RouteInfo,
+    port_name: Option<&str>,
     original_path: &str,
 ) -> Result<(), StatusCode>
 where
@@ -212,7 +319,14 @@ where
         }
     }
 
-    request.set_upstream_address(route_info.address.0);
+    let address = match port_name {
+        Some(port_name) => route_info
+            .named_addresses
+            .get(port_name)
+            .ok_or(StatusCode::NOT_FOUND)?,
+        None => &route_info.address,
+    };
+    request.set_upstream_address(address.0);
 
     // Remove x-verified-* headers from inbound request.
     {
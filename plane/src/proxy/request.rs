@@ -74,6 +74,22 @@ pub fn get_and_maybe_remove_bearer_token(parts: &mut uri::Parts) -> Option<Beare
     Some(token)
 }
 
+/// Separator between a token and the name of the backend port a request is for.
+const PORT_NAME_SEPARATOR: char = '~';
+
+/// Splits a token suffixed with `~<name>` into the token and the name of the backend port the
+/// request should be routed to. Tokens without a suffix are routed to the backend's main port.
+pub fn split_port_name(token: BearerToken) -> (BearerToken, Option<String>) {
+    let token = token.to_string();
+    match token.split_once(PORT_NAME_SEPARATOR) {
+        Some((token, port_name)) => (
+            BearerToken::from(token.to_string()),
+            Some(port_name.to_string()),
+        ),
+        None => (BearerToken::from(token), None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -202,4 +218,26 @@ mod tests {
             Some(PathAndQuery::from_str("/s.foo/bar").unwrap())
         );
     }
+
+    #[test]
+    fn test_split_port_name() {
+        assert_eq!(
+            split_port_name(BearerToken::from("abc123".to_string())),
+            (BearerToken::from("abc123".to_string()), None)
+        );
+        assert_eq!(
+            split_port_name(BearerToken::from("abc123~metrics".to_string())),
+            (
+                BearerToken::from("abc123".to_string()),
+                Some("metrics".to_string())
+            )
+        );
+        assert_eq!(
+            split_port_name(BearerToken::from("s.abc123~debug".to_string())),
+            (
+                BearerToken::from("s.abc123".to_string()),
+                Some("debug".to_string())
+            )
+        );
+    }
 }
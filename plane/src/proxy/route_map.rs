@@ -1,3 +1,4 @@
+use super::metrics::PROXY_METRICS;
 use lru::LruCache;
 use plane_common::{
     names::BackendName,
@@ -52,11 +53,23 @@ impl RouteMap {
     pub async fn lookup(&self, token: &BearerToken) -> Option<RouteInfo> {
         {
             let mut lock = self.routes.lock().expect("Routes lock was poisoned.");
-            if let Some(route_info) = lock.get(token) {
-                return route_info.clone();
+            match lock.get(token) {
+                // If the cached token has expired, it may have been refreshed since we cached
+                // it, so we fall through and ask the controller for fresh route info.
+                Some(Some(route_info)) if route_info.is_expired() => {
+                    lock.pop(token);
+                }
+                Some(route_info) => {
+                    PROXY_METRICS.record_route_cache_lookup(true);
+                    return route_info.clone();
+                }
+                None => {}
             }
         }
 
+        PROXY_METRICS.record_route_cache_lookup(false);
+        let _timer = PROXY_METRICS.route_info_wait_seconds.start_timer();
+
         let mut receiver = {
             let mut listener_lock = self.listeners.lock().expect("Listeners lock was poisoned.");
             let sender = listener_lock.entry(token.clone()).or_insert_with(|| {
@@ -93,6 +106,7 @@ impl RouteMap {
             .expect("Routes lock was poisoned.")
             .get(token)
             .and_then(|x| x.clone())
+            .filter(|route_info| !route_info.is_expired())
     }
 
     fn insert(&self, token: BearerToken, route_info: Option<RouteInfo>) {
@@ -139,4 +153,53 @@ impl RouteMap {
             );
         }
     }
+
+    pub fn evict_backend(&self, backend: &BackendName) {
+        // Unlike `remove_backend`, this drops the cached routes entirely rather than caching
+        // their absence, so that the next request for the backend asks the controller again
+        // (which resumes the backend if it is suspended, or waits for it to be ready if it
+        // is restarting).
+        let mut lock = self.routes.lock().expect("Routes lock was poisoned.");
+        let tokens: Vec<BearerToken> = lock
+            .iter()
+            .filter(|(_, route_info)| {
+                route_info
+                    .as_ref()
+                    .is_some_and(|route_info| route_info.backend_id == *backend)
+            })
+            .map(|(token, _)| token.clone())
+            .collect();
+        for token in &tokens {
+            lock.pop(token);
+        }
+        if !tokens.is_empty() {
+            tracing::info!(
+                count = tokens.len(),
+                backend = backend.as_value(),
+                "Evicted cached routes for backend."
+            );
+        }
+    }
+
+    pub fn remove_user_tokens(&self, backend: &BackendName, user: &str) {
+        // When a user's tokens are revoked, we invalidate the routes that were issued to
+        // that user for the backend. Like `remove_backend`, this loops over the cache.
+        let mut count = 0;
+        let mut lock = self.routes.lock().expect("Routes lock was poisoned.");
+        for (_, maybe_route_info) in lock.iter_mut() {
+            if let Some(route_info) = maybe_route_info.as_mut() {
+                if route_info.backend_id == *backend && route_info.user.as_deref() == Some(user) {
+                    *maybe_route_info = None;
+                    count += 1;
+                }
+            }
+        }
+        if count > 0 {
+            tracing::info!(
+                count,
+                backend = backend.as_value(),
+                "Removed routes for revoked tokens."
+            );
+        }
+    }
 }
@@ -46,6 +46,11 @@ impl GuardHandle {
         let handle = tokio::spawn(future);
         Self { handle }
     }
+
+    /// Returns true if the task has finished.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
 }
 
 impl Drop for GuardHandle {
@@ -54,6 +59,15 @@ impl Drop for GuardHandle {
     }
 }
 
+/// Parse a drone label given on the command line as `key=value`.
+pub fn parse_label(label: &str) -> anyhow::Result<(String, String)> {
+    let (key, value) = label
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Label must be in the form key=value."))?;
+
+    Ok((key.to_string(), value.to_string()))
+}
+
 /// Resolve a hostname to an IP address.
 pub fn resolve_hostname(hostname: &str) -> Option<IpAddr> {
     // The port is arbitrary, but needs to be provided.